@@ -29,6 +29,11 @@ if __name__ == "__main__":
             capture_output=True,
             encoding="utf8",
         ).stdout,
+        "rustracer_compare": subprocess.run(
+            "target/debug/rustracer compare -h".split(),
+            capture_output=True,
+            encoding="utf8",
+        ).stdout,
         "rustracer_completion": subprocess.run(
             "target/debug/rustracer completion -h".split(),
             capture_output=True,
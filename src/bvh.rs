@@ -0,0 +1,331 @@
+//! Bounding Volume Hierarchy module.
+//!
+//! Provides [`BoundingBox`](struct@BoundingBox) and [`Bvh`](struct@Bvh) structs,
+//! used to speed up the search of the closest [`HitRecord`] along a [`Ray`].
+use crate::{point::Point, ray::Ray, shape::HitRecord, transformation::Transformation};
+
+/// Maximum number of primitives stored inside a [`Bvh`] leaf.
+const MAX_LEAF_SIZE: usize = 4;
+
+/// Relative padding applied to the exit time of the slab test,
+/// to avoid missing hits on the boundary of a [`BoundingBox`] due to rounding errors.
+const SLAB_PADDING: f32 = 1e-5;
+
+/// Axis-aligned bounding box (AABB) struct.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingBox {
+    /// Corner with the minimum xyz components.
+    pub min: Point,
+    /// Corner with the maximum xyz components.
+    pub max: Point,
+}
+
+impl BoundingBox {
+    /// Create the smallest [`BoundingBox`] that contains two points.
+    pub fn new(p1: Point, p2: Point) -> Self {
+        Self {
+            min: Point::from((p1.x.min(p2.x), p1.y.min(p2.y), p1.z.min(p2.z))),
+            max: Point::from((p1.x.max(p2.x), p1.y.max(p2.y), p1.z.max(p2.z))),
+        }
+    }
+
+    /// Return the smallest [`BoundingBox`] that contains both `self` and `other`.
+    pub fn union(self, other: BoundingBox) -> Self {
+        Self {
+            min: BoundingBox::new(self.min, other.min).min,
+            max: BoundingBox::new(self.max, other.max).max,
+        }
+    }
+
+    /// Return the center of the box.
+    pub fn centroid(self) -> Point {
+        Point::from((
+            (self.min.x + self.max.x) * 0.5,
+            (self.min.y + self.max.y) * 0.5,
+            (self.min.z + self.max.z) * 0.5,
+        ))
+    }
+
+    /// Return the index (`0` for x, `1` for y, `2` for z) of the longest box side.
+    pub fn longest_axis(self) -> usize {
+        let diag = self.max - self.min;
+        if diag.x >= diag.y && diag.x >= diag.z {
+            0
+        } else if diag.y >= diag.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Return the box that contains `self` after a [`Transformation`].
+    ///
+    /// All the eight corners are transformed, so the result is still axis-aligned\
+    /// (but not necessarily the tightest box around the transformed shape).
+    pub fn transform(self, transformation: Transformation) -> Self {
+        let mut bbox = BoundingBox::new(transformation * self.min, transformation * self.min);
+        for x in [self.min.x, self.max.x] {
+            for y in [self.min.y, self.max.y] {
+                for z in [self.min.z, self.max.z] {
+                    let corner = transformation * Point::from((x, y, z));
+                    bbox = bbox.union(BoundingBox::new(corner, corner));
+                }
+            }
+        }
+        bbox
+    }
+
+    /// Slab test between a [`Ray`] and the box.
+    ///
+    /// Return `true` if the ray enters the box between `ray.tmin` and `tmax`.
+    pub fn hit(&self, ray: Ray, tmax: f32) -> bool {
+        self.ray_range(Ray { tmax, ..ray }).is_some()
+    }
+
+    /// Return the times `(t0, t1)` the [`Ray`] enters and leaves the box,
+    /// clamped between `ray.tmin` and `ray.tmax`,\
+    /// or [`None`] if the ray misses the box in that range.
+    pub fn ray_range(&self, ray: Ray) -> Option<(f32, f32)> {
+        let origin = [ray.origin.x, ray.origin.y, ray.origin.z];
+        let dir = [ray.dir.x, ray.dir.y, ray.dir.z];
+        let min = [self.min.x, self.min.y, self.min.z];
+        let max = [self.max.x, self.max.y, self.max.z];
+        let (mut t0, mut t1) = (ray.tmin, ray.tmax);
+        for axis in 0..3 {
+            let inv_dir = 1.0 / dir[axis];
+            let mut t_near = (min[axis] - origin[axis]) * inv_dir;
+            let mut t_far = (max[axis] - origin[axis]) * inv_dir;
+            if t_near > t_far {
+                std::mem::swap(&mut t_near, &mut t_far);
+            }
+            // `f32::max` and `f32::min` ignore NaN, produced when the ray
+            // lies exactly on a slab plane.
+            t0 = t0.max(t_near);
+            t1 = t1.min(t_far * (1.0 + SLAB_PADDING));
+            if t0 > t1 {
+                return None;
+            }
+        }
+        Some((t0, t1))
+    }
+}
+
+/// Node of a [`Bvh`] tree.
+#[derive(Clone, Debug)]
+enum BvhNode {
+    /// A leaf, that references `count` primitives starting from `first`
+    /// inside [`Bvh`] `indices`.
+    Leaf {
+        bbox: BoundingBox,
+        first: usize,
+        count: usize,
+    },
+    /// An interior node, with the positions of its two children
+    /// inside [`Bvh`] `nodes`.
+    Interior {
+        bbox: BoundingBox,
+        left: usize,
+        right: usize,
+        axis: usize,
+    },
+}
+
+/// Bounding Volume Hierarchy struct.
+///
+/// A binary tree of [`BoundingBox`] built once over a list of primitives,\
+/// that reduces the cost of a ray intersection query to roughly `log(n)`
+/// where `n` is the number of primitives.
+///
+/// Primitives are identified by a [`usize`] index, the caller is in charge of\
+/// computing the actual intersection for a given index
+/// (see [`ray_intersection`](#method.ray_intersection)).
+#[derive(Clone, Debug, Default)]
+pub struct Bvh {
+    /// Flattened tree, the root (if any) is the first node.
+    nodes: Vec<BvhNode>,
+    /// Primitive indices, sorted such that each leaf references a contiguous slice.
+    indices: Vec<usize>,
+}
+
+impl Bvh {
+    /// Build a [`Bvh`] from a list of `(index, bounding box)` primitives.
+    ///
+    /// The tree is built splitting primitives in two halves along the longest axis
+    /// of their centroids bounds, until a leaf contains at most [`MAX_LEAF_SIZE`] primitives.
+    pub fn new(mut primitives: Vec<(usize, BoundingBox)>) -> Self {
+        let mut bvh = Bvh {
+            nodes: Vec::with_capacity(2 * primitives.len()),
+            indices: Vec::with_capacity(primitives.len()),
+        };
+        if !primitives.is_empty() {
+            bvh.build(&mut primitives);
+        }
+        bvh
+    }
+
+    /// Recursively build a subtree from `primitives`, return its position inside `nodes`.
+    fn build(&mut self, primitives: &mut [(usize, BoundingBox)]) -> usize {
+        let bbox = primitives
+            .iter()
+            .map(|(_, bbox)| *bbox)
+            .reduce(BoundingBox::union)
+            .unwrap();
+        let position = self.nodes.len();
+        if primitives.len() <= MAX_LEAF_SIZE {
+            self.nodes.push(BvhNode::Leaf {
+                bbox,
+                first: self.indices.len(),
+                count: primitives.len(),
+            });
+            self.indices
+                .extend(primitives.iter().map(|(index, _)| *index));
+            return position;
+        }
+        let centroids = primitives
+            .iter()
+            .map(|(_, bbox)| BoundingBox::new(bbox.centroid(), bbox.centroid()))
+            .reduce(BoundingBox::union)
+            .unwrap();
+        let axis = centroids.longest_axis();
+        let key = |bbox: &BoundingBox| {
+            let centroid = bbox.centroid();
+            [centroid.x, centroid.y, centroid.z][axis]
+        };
+        primitives.sort_by(|(_, a), (_, b)| key(a).total_cmp(&key(b)));
+        // Push a placeholder, children positions are known only after their build.
+        self.nodes.push(BvhNode::Leaf {
+            bbox,
+            first: 0,
+            count: 0,
+        });
+        let (left_primitives, right_primitives) = primitives.split_at_mut(primitives.len() / 2);
+        let left = self.build(left_primitives);
+        let right = self.build(right_primitives);
+        self.nodes[position] = BvhNode::Interior {
+            bbox,
+            left,
+            right,
+            axis,
+        };
+        position
+    }
+
+    /// Find the closest [`HitRecord`] along `ray`.
+    ///
+    /// `intersect` is called with the index of each primitive
+    /// whose bounding box is hit by the ray,\
+    /// and must return the primitive [`HitRecord`] (if any).
+    pub fn ray_intersection<F>(&self, ray: Ray, mut intersect: F) -> Option<HitRecord>
+    where
+        F: FnMut(usize) -> Option<HitRecord>,
+    {
+        let mut closest: Option<HitRecord> = None;
+        if self.nodes.is_empty() {
+            return closest;
+        }
+        let dir = [ray.dir.x, ray.dir.y, ray.dir.z];
+        let mut stack = vec![0];
+        while let Some(position) = stack.pop() {
+            let tmax = closest.as_ref().map_or(ray.tmax, |hit| hit.t);
+            match self.nodes[position] {
+                BvhNode::Leaf { bbox, first, count } => {
+                    if !bbox.hit(ray, tmax) {
+                        continue;
+                    }
+                    for index in &self.indices[first..first + count] {
+                        if let Some(hit) = intersect(*index) {
+                            if closest
+                                .as_ref()
+                                .is_none_or(|closest_hit| hit.t < closest_hit.t)
+                            {
+                                closest = Some(hit);
+                            }
+                        }
+                    }
+                },
+                BvhNode::Interior {
+                    bbox,
+                    left,
+                    right,
+                    axis,
+                } => {
+                    if !bbox.hit(ray, tmax) {
+                        continue;
+                    }
+                    // Visit first the child closer to the ray origin,
+                    // so the farther one is more likely to be pruned.
+                    if dir[axis] < 0.0 {
+                        stack.push(left);
+                        stack.push(right);
+                    } else {
+                        stack.push(right);
+                        stack.push(left);
+                    }
+                },
+            }
+        }
+        closest
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        misc::IsClose,
+        transformation::{rotation_z, scaling, translation},
+        vector::Vector,
+    };
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_bounding_box() {
+        let bbox = BoundingBox::new(Point::from((1., -1., 2.)), Point::from((-1., 1., -2.)));
+        assert_eq!(bbox.min, Point::from((-1., -1., -2.)));
+        assert_eq!(bbox.max, Point::from((1., 1., 2.)));
+        assert_eq!(bbox.longest_axis(), 2);
+        assert!(bbox.centroid().is_close(Point::default()));
+
+        let other = BoundingBox::new(Point::from((0., 0., 0.)), Point::from((3., 0.5, 1.)));
+        let union = bbox.union(other);
+        assert_eq!(union.min, Point::from((-1., -1., -2.)));
+        assert_eq!(union.max, Point::from((3., 1., 2.)));
+
+        let transformed = bbox.transform(
+            translation(Vector::from((5., 0., 0.)))
+                * rotation_z(PI / 2.)
+                * scaling(Vector::from((2., 1., 1.))),
+        );
+        assert!(transformed.min.is_close(Point::from((4., -2., -2.))));
+        assert!(transformed.max.is_close(Point::from((6., 2., 2.))));
+    }
+
+    #[test]
+    fn test_bounding_box_hit() {
+        let bbox = BoundingBox::new(Point::from((1., -1., -1.)), Point::from((3., 1., 1.)));
+        let ray = Ray::default();
+        assert!(bbox.hit(ray, ray.tmax));
+        assert!(!bbox.hit(ray, 0.5));
+        assert!(!bbox.hit(
+            Ray {
+                dir: Vector::from((-1., 0., 0.)),
+                ..Default::default()
+            },
+            f32::INFINITY
+        ));
+        assert!(!bbox.hit(
+            Ray {
+                origin: Point::from((0., 2., 0.)),
+                ..Default::default()
+            },
+            f32::INFINITY
+        ));
+        assert!(bbox.hit(
+            Ray {
+                origin: Point::from((0., 1., 0.)),
+                ..Default::default()
+            },
+            f32::INFINITY
+        ));
+    }
+}
@@ -7,15 +7,46 @@
 //! That implement [`FireRay`] trait.
 //!
 //! And [`Camera`] enum that wrap them.
-use crate::{point::Point, ray::Ray, transformation::Transformation, vector::Vector};
+use crate::{
+    point::Point,
+    random::Pcg,
+    ray::{Ray, RayDifferentials},
+    transformation::Transformation,
+    vector::Vector,
+};
+use std::f32::consts::PI;
 
 /// Trait for fire a [`Ray`] through a [`camera`](.).
 ///
 /// You should redefine it in derived classes.\
 /// Fire a ray that goes through the screen at the position `(u, v)`.\
 /// The exact meaning of these coordinates depend on the projection used by the camera.
+///
+/// **Note:** [`Pcg`] parameter will be used only by cameras with a lens
+/// (see [`PerspectiveCamera::thin_lens`]).
 pub trait FireRay {
-    fn fire_ray(&self, u: f32, v: f32) -> Ray;
+    fn fire_ray(&self, u: f32, v: f32, pcg: &mut Pcg) -> Ray;
+
+    /// Fire the same ray of [`fire_ray`](#tymethod.fire_ray), with its [`RayDifferentials`]
+    /// towards the screen positions `(u + du, v)` and `(u, v + dv)`.
+    ///
+    /// Offset rays are fired with copies of the [`Pcg`] state,
+    /// so with a lens they depart from the same lens point of the main ray.
+    fn fire_ray_differential(&self, u: f32, v: f32, du: f32, dv: f32, pcg: &mut Pcg) -> Ray {
+        let (mut rx_pcg, mut ry_pcg) = (*pcg, *pcg);
+        let ray = self.fire_ray(u, v, pcg);
+        let rx = self.fire_ray(u + du, v, &mut rx_pcg);
+        let ry = self.fire_ray(u, v + dv, &mut ry_pcg);
+        Ray {
+            differentials: Some(RayDifferentials {
+                rx_origin: rx.origin,
+                rx_dir: rx.dir,
+                ry_origin: ry.origin,
+                ry_dir: ry.dir,
+            }),
+            ..ray
+        }
+    }
 }
 
 /// A camera implementing an orthogonal 3D -> 2D projection.
@@ -65,7 +96,7 @@ impl FireRay for OrthogonalCamera {
     ///     +------------------------------+
     ///  (0, 0)                          (1, 0)
     /// ```
-    fn fire_ray(&self, u: f32, v: f32) -> Ray {
+    fn fire_ray(&self, u: f32, v: f32, _pcg: &mut Pcg) -> Ray {
         self.tranformation
             * Ray {
                 origin: Point::from((-1.0, (1.0 - 2.0 * u) * self.aspect_ratio, 2.0 * v - 1.0)),
@@ -77,13 +108,18 @@ impl FireRay for OrthogonalCamera {
 
 /// A camera implementing a perspective 3D -> 2D projection.
 ///
-/// This class implements an observer seeing the world through a perspective projection.
+/// This class implements an observer seeing the world through a perspective projection.\
+/// The observer is a pinhole, unless a thin lens is used to get depth of field.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct PerspectiveCamera {
     /// Screen distance.
     distance: f32,
     /// Aspect ratio.
     aspect_ratio: f32,
+    /// Lens diameter, zero for a pinhole camera.
+    aperture: f32,
+    /// Distance from the lens of the plane in focus.
+    focal_distance: f32,
     /// [`Transformation`] to apply to [`Ray`].
     transformation: Transformation,
 }
@@ -101,9 +137,31 @@ impl PerspectiveCamera {
     ///
     /// The `transformation` parameter is an instance of the [`Transformation`].
     pub fn new(distance: f32, aspect_ratio: f32, transformation: Transformation) -> Self {
+        Self::thin_lens(distance, aspect_ratio, 0.0, distance, transformation)
+    }
+
+    /// Create a new perspective camera with a thin lens.
+    ///
+    /// The parameters `distance`, `aspect_ratio` and `transformation`
+    /// have the same meaning as in [`new`](#method.new).
+    ///
+    /// The parameter `aperture` is the diameter of the lens, centered on the observer:\
+    /// the larger it is, the more blurred are objects out of focus.
+    ///
+    /// The parameter `focal_distance` tells how much far from the lens
+    /// is the plane that is rendered in focus.
+    pub fn thin_lens(
+        distance: f32,
+        aspect_ratio: f32,
+        aperture: f32,
+        focal_distance: f32,
+        transformation: Transformation,
+    ) -> Self {
         Self {
             distance,
             aspect_ratio,
+            aperture,
+            focal_distance,
             transformation,
         }
     }
@@ -129,15 +187,34 @@ impl FireRay for PerspectiveCamera {
     ///     +------------------------------+
     ///  (0, 0)                          (1, 0)
     /// ```
-    fn fire_ray(&self, u: f32, v: f32) -> Ray {
+    ///
+    /// With a thin lens, the ray origin is sampled on the lens disk using the [`Pcg`],\
+    /// and the ray is aimed at the point on the focal plane
+    /// where the pinhole ray would have crossed it.
+    fn fire_ray(&self, u: f32, v: f32, pcg: &mut Pcg) -> Ray {
+        let origin = Point::from((-self.distance, 0.0, 0.0));
+        let dir = Vector::from((
+            self.distance,
+            (1.0 - 2.0 * u) * self.aspect_ratio,
+            2.0 * v - 1.0,
+        ));
+        if self.aperture <= 0.0 {
+            return self.transformation
+                * Ray {
+                    origin,
+                    dir,
+                    ..Default::default()
+                };
+        }
+        let focus_point = origin + dir * (self.focal_distance / self.distance);
+        // Uniform sampling on the lens disk, orthogonal to the optical (x) axis.
+        let r = 0.5 * self.aperture * pcg.random_float().sqrt();
+        let phi = 2.0 * PI * pcg.random_float();
+        let lens_point = origin + Vector::from((0.0, r * f32::cos(phi), r * f32::sin(phi)));
         self.transformation
             * Ray {
-                origin: Point::from((-self.distance, 0.0, 0.0)),
-                dir: Vector::from((
-                    self.distance,
-                    (1.0 - 2.0 * u) * self.aspect_ratio,
-                    2.0 * v - 1.0,
-                )),
+                origin: lens_point,
+                dir: focus_point - lens_point,
                 ..Default::default()
             }
     }
@@ -152,10 +229,10 @@ pub enum Camera {
 
 impl FireRay for Camera {
     /// Shoot a [`Ray`] through the camera's screen as the variant that [`Camera`] contain will do.
-    fn fire_ray(&self, u: f32, v: f32) -> Ray {
+    fn fire_ray(&self, u: f32, v: f32, pcg: &mut Pcg) -> Ray {
         match self {
-            Camera::Orthogonal(orthogonal) => orthogonal.fire_ray(u, v),
-            Camera::Perspective(perspective) => perspective.fire_ray(u, v),
+            Camera::Orthogonal(orthogonal) => orthogonal.fire_ray(u, v, pcg),
+            Camera::Perspective(perspective) => perspective.fire_ray(u, v, pcg),
         }
     }
 }
@@ -163,15 +240,16 @@ impl FireRay for Camera {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::misc::IsClose;
+    use crate::{misc::IsClose, transformation::translation};
 
     #[test]
     fn test_orthogonal_camera() {
+        let mut pcg = Pcg::default();
         let cam = Camera::Orthogonal(OrthogonalCamera::new(2.0, Transformation::default()));
-        let ray1 = cam.fire_ray(0.0, 0.0);
-        let ray2 = cam.fire_ray(1.0, 0.0);
-        let ray3 = cam.fire_ray(0.0, 1.0);
-        let ray4 = cam.fire_ray(1.0, 1.0);
+        let ray1 = cam.fire_ray(0.0, 0.0, &mut pcg);
+        let ray2 = cam.fire_ray(1.0, 0.0, &mut pcg);
+        let ray3 = cam.fire_ray(0.0, 1.0, &mut pcg);
+        let ray4 = cam.fire_ray(1.0, 1.0, &mut pcg);
 
         assert!((ray1.dir * ray2.dir).squared_norm().is_close(0.0));
         assert!((ray1.dir * ray3.dir).squared_norm().is_close(0.0));
@@ -185,11 +263,12 @@ mod test {
 
     #[test]
     fn test_perspective_camera() {
+        let mut pcg = Pcg::default();
         let cam = Camera::Perspective(PerspectiveCamera::new(1.0, 2.0, Transformation::default()));
-        let ray1 = cam.fire_ray(0.0, 0.0);
-        let ray2 = cam.fire_ray(1.0, 0.0);
-        let ray3 = cam.fire_ray(0.0, 1.0);
-        let ray4 = cam.fire_ray(1.0, 1.0);
+        let ray1 = cam.fire_ray(0.0, 0.0, &mut pcg);
+        let ray2 = cam.fire_ray(1.0, 0.0, &mut pcg);
+        let ray3 = cam.fire_ray(0.0, 1.0, &mut pcg);
+        let ray4 = cam.fire_ray(1.0, 1.0, &mut pcg);
 
         assert!(ray1.origin.is_close(ray2.origin));
         assert!(ray1.origin.is_close(ray3.origin));
@@ -200,4 +279,55 @@ mod test {
         assert!(ray3.at(1.0).is_close(Point::from((0.0, 2.0, 1.0))));
         assert!(ray4.at(1.0).is_close(Point::from((0.0, -2.0, 1.0))));
     }
+
+    #[test]
+    fn test_thin_lens_camera() {
+        let mut pcg = Pcg::default();
+        let pinhole = PerspectiveCamera::new(1.0, 2.0, Transformation::default());
+        let cam = Camera::Perspective(PerspectiveCamera::thin_lens(
+            1.0,
+            2.0,
+            0.5,
+            3.0,
+            Transformation::default(),
+        ));
+        for (u, v) in [(0.0, 0.0), (0.3, 0.8), (1.0, 1.0)] {
+            let pinhole_ray = pinhole.fire_ray(u, v, &mut pcg);
+            let focus_point = pinhole_ray.at(3.0);
+            for _ in 0..10 {
+                let ray = cam.fire_ray(u, v, &mut pcg);
+                // Origin lies on the lens disk.
+                assert!(ray.origin.x.is_close(-1.0));
+                assert!(ray.origin.y.powi(2) + ray.origin.z.powi(2) <= 0.25 * 0.25 + 1e-6);
+                // Every ray crosses the focal plane where the pinhole ray does.
+                assert!(ray.at(1.0).is_close(focus_point));
+            }
+        }
+    }
+
+    #[test]
+    fn test_ray_differentials() {
+        let mut pcg = Pcg::default();
+        let tr = translation(Vector::from((1.0, 2.0, 3.0)));
+        for cam in [
+            Camera::Orthogonal(OrthogonalCamera::new(2.0, tr)),
+            Camera::Perspective(PerspectiveCamera::new(1.0, 2.0, tr)),
+        ] {
+            let ray = cam.fire_ray_differential(0.3, 0.6, 0.1, -0.2, &mut pcg);
+            let (rx, ry) = (
+                cam.fire_ray(0.4, 0.6, &mut pcg),
+                cam.fire_ray(0.3, 0.4, &mut pcg),
+            );
+            let diff = ray.differentials.unwrap();
+            assert!(ray.is_close(cam.fire_ray(0.3, 0.6, &mut pcg)));
+            assert!(diff.rx_origin.is_close(rx.origin) && diff.rx_dir.is_close(rx.dir));
+            assert!(diff.ry_origin.is_close(ry.origin) && diff.ry_dir.is_close(ry.dir));
+        }
+
+        // With a thin lens, offset rays depart from the lens point of the main ray.
+        let cam = PerspectiveCamera::thin_lens(1.0, 2.0, 0.5, 3.0, Transformation::default());
+        let ray = cam.fire_ray_differential(0.3, 0.6, 0.1, -0.2, &mut pcg);
+        let diff = ray.differentials.unwrap();
+        assert!(diff.rx_origin.is_close(ray.origin) && diff.ry_origin.is_close(ray.origin));
+    }
 }
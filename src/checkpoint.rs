@@ -0,0 +1,134 @@
+//! Checkpoint module.
+//!
+//! Provides [`read_checkpoint`] and [`write_checkpoint`] functions,
+//! used to stop and resume a progressive render.
+//!
+//! A checkpoint is made of two files:
+//!  * the pfm image, holding the running average of the passes rendered so far;
+//!  * a samples sidecar (same path with `samples` extension),
+//!    holding the number of samples per pixel averaged inside the image
+//!    and a checksum of the image, so that a mismatched pair is rejected.
+use crate::{
+    error::{CheckpointErr, HdrImageErr},
+    hdrimage::HdrImage,
+    misc::ByteOrder,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Return the path of the samples sidecar of `hdr_file`.
+pub fn samples_file(hdr_file: &Path) -> PathBuf {
+    hdr_file.with_extension("samples")
+}
+
+/// Return the 64 bit [FNV-1a](https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function)
+/// hash of the pfm image at `hdr_file`.
+fn image_checksum(hdr_file: &Path) -> Result<u64, CheckpointErr> {
+    let bytes = fs::read(hdr_file)
+        .map_err(|e| CheckpointErr::PfmFileReadFailure(HdrImageErr::PfmFileReadFailure(e)))?;
+    Ok(bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    }))
+}
+
+/// Read a checkpoint from `hdr_file` and its samples sidecar.
+///
+/// Return the image and the number of samples per pixel inside a [`std::result::Result`].\
+/// If an error occurs the result contains an [`CheckpointErr`] error variant,
+/// [`CheckpointErr::InvalidCheckpoint`] when the sidecar doesn't belong to the image.
+pub fn read_checkpoint(hdr_file: &Path) -> Result<(HdrImage, u32), CheckpointErr> {
+    let hdr_img = HdrImage::read_pfm_file(hdr_file).map_err(CheckpointErr::PfmFileReadFailure)?;
+    let sidecar = fs::read_to_string(samples_file(hdr_file))
+        .map_err(CheckpointErr::SamplesFileReadFailure)?;
+    let mut fields = sidecar.split_whitespace();
+    let samples = u32::from_str(fields.next().unwrap_or_default())
+        .map_err(CheckpointErr::SamplesIntParseFailure)?;
+    let checksum = fields
+        .next()
+        .and_then(|field| u64::from_str_radix(field, 16).ok());
+    if checksum != Some(image_checksum(hdr_file)?) {
+        return Err(CheckpointErr::InvalidCheckpoint(String::from(
+            "samples file doesn't match the checkpoint image",
+        )));
+    }
+    Ok((hdr_img, samples))
+}
+
+/// Write a checkpoint to `hdr_file` and its samples sidecar.
+///
+/// Both files are first written aside and then renamed,
+/// so that an interrupted write never corrupts a previous checkpoint;\
+/// if interrupted between the two renames, the sidecar checksum
+/// no longer matches the image and the pair is rejected on resume.\
+/// If an error occurs the result contains an [`CheckpointErr`] error variant.
+pub fn write_checkpoint(
+    hdr_img: &HdrImage,
+    samples: u32,
+    hdr_file: &Path,
+) -> Result<(), CheckpointErr> {
+    let samples_file = samples_file(hdr_file);
+    let (hdr_tmp, samples_tmp) = (
+        hdr_file.with_extension("pfm.tmp"),
+        samples_file.with_extension("samples.tmp"),
+    );
+    hdr_img
+        .write_pfm_file(&hdr_tmp, ByteOrder::LittleEndian)
+        .map_err(CheckpointErr::PfmFileWriteFailure)?;
+    let checksum = image_checksum(&hdr_tmp)?;
+    fs::write(&samples_tmp, format!("{samples} {checksum:016x}\n"))
+        .map_err(CheckpointErr::SamplesFileWriteFailure)?;
+    fs::rename(&hdr_tmp, hdr_file)
+        .map_err(|e| CheckpointErr::PfmFileWriteFailure(HdrImageErr::PfmFileWriteFailure(e)))?;
+    fs::rename(&samples_tmp, &samples_file).map_err(CheckpointErr::SamplesFileWriteFailure)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::Color;
+
+    #[test]
+    fn test_checkpoint() {
+        let mut hdr_img = HdrImage::new(3, 2);
+        hdr_img
+            .set_pixel(1, 1, Color::from((0.1, 2.0, 30.0)))
+            .unwrap();
+        let hdr_file = std::env::temp_dir().join("rustracer_checkpoint.pfm");
+        assert!(write_checkpoint(&hdr_img, 8, &hdr_file).is_ok());
+        assert!(matches!(
+            read_checkpoint(&hdr_file),
+            Ok((img, samples)) if img == hdr_img && samples == 8
+        ));
+
+        // A sidecar left behind by a previous checkpoint is rejected.
+        let sidecar = fs::read_to_string(samples_file(&hdr_file)).unwrap();
+        hdr_img
+            .set_pixel(0, 0, Color::from((1.0, 1.0, 1.0)))
+            .unwrap();
+        assert!(write_checkpoint(&hdr_img, 16, &hdr_file).is_ok());
+        fs::write(samples_file(&hdr_file), sidecar).unwrap();
+        assert!(matches!(
+            read_checkpoint(&hdr_file),
+            Err(CheckpointErr::InvalidCheckpoint(_))
+        ));
+
+        fs::write(samples_file(&hdr_file), "eight\n").unwrap();
+        assert!(matches!(
+            read_checkpoint(&hdr_file),
+            Err(CheckpointErr::SamplesIntParseFailure(_))
+        ));
+        fs::remove_file(samples_file(&hdr_file)).unwrap();
+        assert!(matches!(
+            read_checkpoint(&hdr_file),
+            Err(CheckpointErr::SamplesFileReadFailure(_))
+        ));
+        fs::remove_file(&hdr_file).unwrap();
+        assert!(matches!(
+            read_checkpoint(Path::new("/invalid/path.pfm")),
+            Err(CheckpointErr::PfmFileReadFailure(_))
+        ));
+    }
+}
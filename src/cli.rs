@@ -3,6 +3,7 @@
 //! Provides [`build_cli`] function with all cli
 //! desired subcommands and flags, using [`clap`](https://github.com/clap-rs/clap)
 //! library.
+use crate::hdrimage::ldr_formats;
 use clap::{builder, Arg, ArgAction, Command};
 
 /// Default normalization factor.
@@ -13,6 +14,22 @@ const FACTOR: &str = "1.0";
 ///
 /// When no arguments are provided to `--gamma` flag
 const GAMMA: &str = "1.0";
+/// Default tone mapping operator.
+///
+/// When no arguments are provided to `--tonemap` flag
+const TONEMAP: &str = "reinhard";
+/// Default bit depth of png and tiff images.
+///
+/// When no arguments are provided to `--bit-depth` flag
+const BIT_DEPTH: &str = "8";
+/// Default dithering of 8 bit images.
+///
+/// When no arguments are provided to `--dither` flag
+const DITHER: &str = "none";
+/// Default jpeg quality.
+///
+/// When no arguments are provided to `--quality` flag
+const QUALITY: &str = "90";
 /// Default image width.
 ///
 /// When no arguments are provided to `--width` flag
@@ -49,10 +66,31 @@ const INIT_SEQ: &str = "54";
 ///
 /// When no arguments are provided to `--anti-aliasing` flag
 const ANTI_ALIASING: &str = "1";
+/// Default number of progressive passes.
+///
+/// When no arguments are provided to `--passes` flag
+const PASSES: &str = "1";
+/// Default samples precision of exr output.
+///
+/// When no arguments are provided to `--exr-precision` flag
+const EXR_PRECISION: &str = "float";
+/// Default compression of exr output.
+///
+/// When no arguments are provided to `--exr-compression` flag
+const EXR_COMPRESSION: &str = "zip";
+/// Default metric checked against threshold.
+///
+/// When no arguments are provided to `--metric` flag
+const METRIC: &str = "rmse";
 
 /// Build a [`clap::Command`](https://docs.rs/clap/latest/clap/type.Command.html)
 /// for [`rustracer`](..) crate.
 pub fn build_cli() -> Command {
+    // LDR formats depend on enabled cargo features.
+    let ldr: Vec<&str> = ldr_formats()
+        .into_iter()
+        .map(|format| format.extensions_str()[0])
+        .collect();
     Command::new(env!("CARGO_PKG_NAME"))
         .version(env!("CARGO_PKG_VERSION"))
         .about(env!("CARGO_PKG_DESCRIPTION"))
@@ -63,18 +101,27 @@ pub fn build_cli() -> Command {
         .subcommand(
             Command::new("convert")
                 .arg_required_else_help(true)
-                .about("Convert HDR (pfm) image to LDR (ff|png) image")
+                .about(format!(
+                    "Convert HDR (pfm|exr|hdr) image to LDR ({}) image",
+                    ldr.join("|")
+                ))
                 .arg(
                     Arg::new("HDR")
                         .required(true)
-                        .help("Input pfm image")
-                        .long_help("Input pfm file path"),
+                        .help("Input image [possible formats: pfm, exr, hdr]")
+                        .long_help("Input hdr file path [possible formats: pfm, exr, hdr]"),
                 )
                 .arg(
                     Arg::new("LDR")
                         .required(true)
-                        .help("Output image [possible formats: ff, png]")
-                        .long_help("Output file path [possible formats: ff, png]"),
+                        .help(format!(
+                            "Output image [possible formats: {}]",
+                            ldr.join(", ")
+                        ))
+                        .long_help(format!(
+                            "Output file path [possible formats: {}]",
+                            ldr.join(", ")
+                        )),
                 )
                 .arg(
                     Arg::new("verbose")
@@ -104,6 +151,80 @@ pub fn build_cli() -> Command {
                         .num_args(1)
                         .help("Gamma parameter")
                         .long_help("Gamma transfer function parameter"),
+                )
+                .arg(
+                    Arg::new("tonemap")
+                        .long("tonemap")
+                        .value_name("TONEMAP")
+                        .default_value(TONEMAP)
+                        .num_args(1)
+                        .value_parser(builder::PossibleValuesParser::new([
+                            "reinhard",
+                            "extended-reinhard",
+                            "aces",
+                            "hable",
+                            "clip",
+                        ]))
+                        .help("Tone mapping operator")
+                        .long_help(
+                            "Tone mapping operator applied after luminosity normalization: \
+                             [reinhard, extended-reinhard, aces, hable, clip]",
+                        ),
+                )
+                .arg(
+                    Arg::new("white-point")
+                        .long("white-point")
+                        .value_name("WHITE_POINT")
+                        .num_args(1)
+                        .help("White point for extended-reinhard")
+                        .long_help(
+                            "Normalized luminosity mapped to pure white by extended-reinhard \
+                             tone mapping (default: maximum luminosity of the image)",
+                        ),
+                )
+                .arg(
+                    Arg::new("srgb")
+                        .long("srgb")
+                        .num_args(0)
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("gamma")
+                        .help("Use sRGB transfer function")
+                        .long_help(
+                            "Use the exact piecewise sRGB transfer function instead of gamma",
+                        ),
+                )
+                .arg(
+                    Arg::new("bit-depth")
+                        .long("bit-depth")
+                        .value_name("BIT_DEPTH")
+                        .default_value(BIT_DEPTH)
+                        .num_args(1)
+                        .value_parser(builder::PossibleValuesParser::new(["8", "16"]))
+                        .help("Png and tiff bit depth")
+                        .long_help("Bit depth of png and tiff output file: [8, 16]"),
+                )
+                .arg(
+                    Arg::new("dither")
+                        .long("dither")
+                        .value_name("DITHER")
+                        .default_value(DITHER)
+                        .num_args(1)
+                        .value_parser(builder::PossibleValuesParser::new([
+                            "none",
+                            "ordered",
+                            "blue-noise",
+                        ]))
+                        .help("Dithering of 8 bit image")
+                        .long_help("Dithering of 8 bit output file: [none, ordered, blue-noise]"),
+                )
+                .arg(
+                    Arg::new("quality")
+                        .long("quality")
+                        .value_name("QUALITY")
+                        .default_value(QUALITY)
+                        .num_args(1)
+                        .help("Jpeg quality")
+                        .long_help("Quality of jpeg output file, clamped into [1, 100]"),
                 ),
         )
         .subcommand(
@@ -113,8 +234,14 @@ pub fn build_cli() -> Command {
                 .arg(
                     Arg::new("OUTPUT")
                         .required(true)
-                        .help("Output image [possible formats: ff, png]")
-                        .long_help("Output ldr image file path [possible formats: ff, png]"),
+                        .help(format!(
+                            "Output image [possible formats: {}]",
+                            ldr.join(", ")
+                        ))
+                        .long_help(format!(
+                            "Output ldr image file path [possible formats: {}]",
+                            ldr.join(", ")
+                        )),
                 )
                 .arg(
                     Arg::new("verbose")
@@ -131,7 +258,7 @@ pub fn build_cli() -> Command {
                         .num_args(0)
                         .action(ArgAction::SetTrue)
                         .help("Output also hdr image")
-                        .long_help("Output also pfm file in combination with (ff|png) file"),
+                        .long_help("Output also pfm file in combination with ldr file"),
                 )
                 .arg(
                     Arg::new("orthogonal")
@@ -188,6 +315,80 @@ pub fn build_cli() -> Command {
                         .help("Gamma parameter")
                         .long_help("Gamma transfer function parameter"),
                 )
+                .arg(
+                    Arg::new("tonemap")
+                        .long("tonemap")
+                        .value_name("TONEMAP")
+                        .default_value(TONEMAP)
+                        .num_args(1)
+                        .value_parser(builder::PossibleValuesParser::new([
+                            "reinhard",
+                            "extended-reinhard",
+                            "aces",
+                            "hable",
+                            "clip",
+                        ]))
+                        .help("Tone mapping operator")
+                        .long_help(
+                            "Tone mapping operator applied after luminosity normalization: \
+                             [reinhard, extended-reinhard, aces, hable, clip]",
+                        ),
+                )
+                .arg(
+                    Arg::new("white-point")
+                        .long("white-point")
+                        .value_name("WHITE_POINT")
+                        .num_args(1)
+                        .help("White point for extended-reinhard")
+                        .long_help(
+                            "Normalized luminosity mapped to pure white by extended-reinhard \
+                             tone mapping (default: maximum luminosity of the image)",
+                        ),
+                )
+                .arg(
+                    Arg::new("srgb")
+                        .long("srgb")
+                        .num_args(0)
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("gamma")
+                        .help("Use sRGB transfer function")
+                        .long_help(
+                            "Use the exact piecewise sRGB transfer function instead of gamma",
+                        ),
+                )
+                .arg(
+                    Arg::new("bit-depth")
+                        .long("bit-depth")
+                        .value_name("BIT_DEPTH")
+                        .default_value(BIT_DEPTH)
+                        .num_args(1)
+                        .value_parser(builder::PossibleValuesParser::new(["8", "16"]))
+                        .help("Png and tiff bit depth")
+                        .long_help("Bit depth of png and tiff output file: [8, 16]"),
+                )
+                .arg(
+                    Arg::new("dither")
+                        .long("dither")
+                        .value_name("DITHER")
+                        .default_value(DITHER)
+                        .num_args(1)
+                        .value_parser(builder::PossibleValuesParser::new([
+                            "none",
+                            "ordered",
+                            "blue-noise",
+                        ]))
+                        .help("Dithering of 8 bit image")
+                        .long_help("Dithering of 8 bit output file: [none, ordered, blue-noise]"),
+                )
+                .arg(
+                    Arg::new("quality")
+                        .long("quality")
+                        .value_name("QUALITY")
+                        .default_value(QUALITY)
+                        .num_args(1)
+                        .help("Jpeg quality")
+                        .long_help("Quality of jpeg output file, clamped into [1, 100]"),
+                )
                 .arg(
                     Arg::new("algorithm")
                         .short('a')
@@ -199,10 +400,12 @@ pub fn build_cli() -> Command {
                             "onoff",
                             "flat",
                             "pathtracer",
+                            "pointlight",
                         ]))
                         .help("Rendering algorithm")
                         .long_help(
-                            "Algorithm to use for render the scene: [onoff, flat, pathtracer]",
+                            "Algorithm to use for render the scene: \
+                             [onoff, flat, pathtracer, pointlight]",
                         ),
                 )
                 .arg(
@@ -227,6 +430,17 @@ pub fn build_cli() -> Command {
                         .help("Maximum depth")
                         .long_help("Maximum allowed ray depth"),
                 )
+                .arg(
+                    Arg::new("light-sampling")
+                        .long("light-sampling")
+                        .num_args(0)
+                        .action(ArgAction::SetTrue)
+                        .help("Sample emissive shapes explicitly")
+                        .long_help(
+                            "Sample emissive spheres at each diffuse impact (next-event \
+                             estimation) with multiple importance sampling, only with pathtracer",
+                        ),
+                )
                 .arg(
                     Arg::new("init-state")
                         .long("init-state")
@@ -276,8 +490,14 @@ pub fn build_cli() -> Command {
                 .arg(
                     Arg::new("OUTPUT")
                         .required(true)
-                        .help("Output image [possible formats: ff, png]")
-                        .long_help("Output ldr image file path [possible formats: ff, png]"),
+                        .help(format!(
+                            "Output image [possible formats: {}]",
+                            ldr.join(", ")
+                        ))
+                        .long_help(format!(
+                            "Output ldr image file path [possible formats: {}]",
+                            ldr.join(", ")
+                        )),
                 )
                 .arg(
                     Arg::new("verbose")
@@ -293,7 +513,49 @@ pub fn build_cli() -> Command {
                         .long("output-pfm")
                         .num_args(0)
                         .help("Output also hdr image")
-                        .long_help("Output also pfm file in combination with (ff|png) file"),
+                        .long_help("Output also pfm file in combination with ldr file"),
+                )
+                .arg(
+                    Arg::new("output-hdr")
+                        .long("output-hdr")
+                        .value_name("OUTPUT_HDR")
+                        .num_args(1)
+                        .help("Output also hdr image [possible formats: pfm, exr, hdr]")
+                        .long_help(
+                            "Output also hdr file path in combination with ldr file \
+                             [possible formats: pfm, exr, hdr]",
+                        ),
+                )
+                .arg(
+                    Arg::new("exr-precision")
+                        .long("exr-precision")
+                        .value_name("EXR_PRECISION")
+                        .default_value(EXR_PRECISION)
+                        .num_args(1)
+                        .value_parser(builder::PossibleValuesParser::new(["half", "float"]))
+                        .requires("output-hdr")
+                        .help("Exr samples precision")
+                        .long_help("Samples precision of the exr output file: [half, float]"),
+                )
+                .arg(
+                    Arg::new("exr-compression")
+                        .long("exr-compression")
+                        .value_name("EXR_COMPRESSION")
+                        .default_value(EXR_COMPRESSION)
+                        .num_args(1)
+                        .value_parser(builder::PossibleValuesParser::new(["none", "zip", "piz"]))
+                        .requires("output-hdr")
+                        .help("Exr compression")
+                        .long_help("Lossless compression of the exr output file: [none, zip, piz]"),
+                )
+                .arg(
+                    Arg::new("exr-alpha")
+                        .long("exr-alpha")
+                        .num_args(0)
+                        .action(ArgAction::SetTrue)
+                        .requires("output-hdr")
+                        .help("Exr alpha channel")
+                        .long_help("Write also an opaque alpha channel to the exr output file"),
                 )
                 .arg(
                     Arg::new("width")
@@ -342,6 +604,80 @@ pub fn build_cli() -> Command {
                         .help("Gamma parameter")
                         .long_help("Gamma transfer function parameter"),
                 )
+                .arg(
+                    Arg::new("tonemap")
+                        .long("tonemap")
+                        .value_name("TONEMAP")
+                        .default_value(TONEMAP)
+                        .num_args(1)
+                        .value_parser(builder::PossibleValuesParser::new([
+                            "reinhard",
+                            "extended-reinhard",
+                            "aces",
+                            "hable",
+                            "clip",
+                        ]))
+                        .help("Tone mapping operator")
+                        .long_help(
+                            "Tone mapping operator applied after luminosity normalization: \
+                             [reinhard, extended-reinhard, aces, hable, clip]",
+                        ),
+                )
+                .arg(
+                    Arg::new("white-point")
+                        .long("white-point")
+                        .value_name("WHITE_POINT")
+                        .num_args(1)
+                        .help("White point for extended-reinhard")
+                        .long_help(
+                            "Normalized luminosity mapped to pure white by extended-reinhard \
+                             tone mapping (default: maximum luminosity of the image)",
+                        ),
+                )
+                .arg(
+                    Arg::new("srgb")
+                        .long("srgb")
+                        .num_args(0)
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("gamma")
+                        .help("Use sRGB transfer function")
+                        .long_help(
+                            "Use the exact piecewise sRGB transfer function instead of gamma",
+                        ),
+                )
+                .arg(
+                    Arg::new("bit-depth")
+                        .long("bit-depth")
+                        .value_name("BIT_DEPTH")
+                        .default_value(BIT_DEPTH)
+                        .num_args(1)
+                        .value_parser(builder::PossibleValuesParser::new(["8", "16"]))
+                        .help("Png and tiff bit depth")
+                        .long_help("Bit depth of png and tiff output file: [8, 16]"),
+                )
+                .arg(
+                    Arg::new("dither")
+                        .long("dither")
+                        .value_name("DITHER")
+                        .default_value(DITHER)
+                        .num_args(1)
+                        .value_parser(builder::PossibleValuesParser::new([
+                            "none",
+                            "ordered",
+                            "blue-noise",
+                        ]))
+                        .help("Dithering of 8 bit image")
+                        .long_help("Dithering of 8 bit output file: [none, ordered, blue-noise]"),
+                )
+                .arg(
+                    Arg::new("quality")
+                        .long("quality")
+                        .value_name("QUALITY")
+                        .default_value(QUALITY)
+                        .num_args(1)
+                        .help("Jpeg quality")
+                        .long_help("Quality of jpeg output file, clamped into [1, 100]"),
+                )
                 .arg(
                     Arg::new("algorithm")
                         .short('a')
@@ -353,10 +689,12 @@ pub fn build_cli() -> Command {
                             "onoff",
                             "flat",
                             "pathtracer",
+                            "pointlight",
                         ]))
                         .help("Rendering algorithm")
                         .long_help(
-                            "Algorithm to use for render the scene: [onoff, flat, pathtracer]",
+                            "Algorithm to use for render the scene: \
+                             [onoff, flat, pathtracer, pointlight]",
                         ),
                 )
                 .arg(
@@ -381,6 +719,17 @@ pub fn build_cli() -> Command {
                         .help("Maximum depth")
                         .long_help("Maximum allowed ray depth"),
                 )
+                .arg(
+                    Arg::new("light-sampling")
+                        .long("light-sampling")
+                        .num_args(0)
+                        .action(ArgAction::SetTrue)
+                        .help("Sample emissive shapes explicitly")
+                        .long_help(
+                            "Sample emissive spheres at each diffuse impact (next-event \
+                             estimation) with multiple importance sampling, only with pathtracer",
+                        ),
+                )
                 .arg(
                     Arg::new("init-state")
                         .long("init-state")
@@ -415,6 +764,107 @@ pub fn build_cli() -> Command {
                             "Anti-aliasing level, corresponds to the square-root of the number of \
                              samples per pixel",
                         ),
+                )
+                .arg(
+                    Arg::new("passes")
+                        .long("passes")
+                        .value_name("PASSES")
+                        .default_value(PASSES)
+                        .num_args(1)
+                        .value_parser(builder::RangedU64ValueParser::<u32>::new().range(1..))
+                        .requires("output-pfm")
+                        .help("Number of progressive passes")
+                        .long_help(
+                            "Render progressively in passes of anti-aliasing samples per pixel, \
+                             writing a checkpoint (pfm file and samples file) after each pass",
+                        ),
+                )
+                .arg(
+                    Arg::new("resume")
+                        .long("resume")
+                        .num_args(0)
+                        .action(ArgAction::SetTrue)
+                        .requires("output-pfm")
+                        .help("Resume a progressive render")
+                        .long_help(
+                            "Resume a progressive render from its last checkpoint \
+                             (pfm file and samples file)",
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("compare")
+                .arg_required_else_help(true)
+                .about("Compare two images with RMSE, PSNR and SSIM")
+                .arg(
+                    Arg::new("REFERENCE")
+                        .required(true)
+                        .help("Reference image [possible formats: pfm, exr, hdr, ff, png, ...]")
+                        .long_help(format!(
+                            "Reference image file path (e.g. golden render) \
+                             [possible formats: pfm, exr, hdr, {}]",
+                            ldr.join(", ")
+                        )),
+                )
+                .arg(
+                    Arg::new("TEST")
+                        .required(true)
+                        .help("Test image [possible formats: pfm, exr, hdr, ff, png, ...]")
+                        .long_help(format!(
+                            "Test image file path, same shape of reference image \
+                             [possible formats: pfm, exr, hdr, {}]",
+                            ldr.join(", ")
+                        )),
+                )
+                .arg(
+                    Arg::new("verbose")
+                        .short('v')
+                        .long("verbose")
+                        .num_args(0)
+                        .action(ArgAction::SetTrue)
+                        .help("Print stdout information")
+                        .long_help("Print stdout information"),
+                )
+                .arg(
+                    Arg::new("diff")
+                        .long("diff")
+                        .value_name("DIFF")
+                        .num_args(1)
+                        .help("Output false color difference image")
+                        .long_help(format!(
+                            "Output false color image of per-pixel maximum absolute error \
+                             [possible formats: {}]",
+                            ldr.join(", ")
+                        )),
+                )
+                .arg(
+                    Arg::new("metric")
+                        .long("metric")
+                        .value_name("METRIC")
+                        .default_value(METRIC)
+                        .num_args(1)
+                        .value_parser(builder::PossibleValuesParser::new([
+                            "rmse",
+                            "max-error",
+                            "psnr",
+                            "ssim",
+                        ]))
+                        .help("Metric checked against threshold")
+                        .long_help(
+                            "Metric of the worst channel checked against threshold: \
+                             [rmse, max-error, psnr, ssim]",
+                        ),
+                )
+                .arg(
+                    Arg::new("threshold")
+                        .long("threshold")
+                        .value_name("THRESHOLD")
+                        .num_args(1)
+                        .help("Exit with error past threshold")
+                        .long_help(
+                            "Exit with error when metric is past threshold \
+                             (above it for rmse and max-error, below it for psnr and ssim)",
+                        ),
                 ),
         )
         .subcommand(
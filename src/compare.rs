@@ -0,0 +1,298 @@
+//! Image Comparison module.
+//!
+//! Provides [`Comparison`](struct@Comparison) struct,
+//! with per-channel error metrics between a reference and a test [`HdrImage`].
+use crate::color::Color;
+use crate::hdrimage::HdrImage;
+
+/// Half side of the gaussian window used by [`ssim`].
+const SSIM_RADIUS: i64 = 5;
+/// Standard deviation (in pixels) of the gaussian window used by [`ssim`].
+const SSIM_SIGMA: f64 = 1.5;
+/// SSIM luminance stabilization constant, relative to the peak value.
+const SSIM_K1: f64 = 0.01;
+/// SSIM contrast stabilization constant, relative to the peak value.
+const SSIM_K2: f64 = 0.03;
+/// Color stops of the false color map used by [`difference_image`],
+/// evenly spaced from no error (black) to maximum error (light yellow).
+const FALSE_COLOR_STOPS: [(f32, f32, f32); 5] = [
+    (0.0, 0.0, 0.0),
+    (0.34, 0.06, 0.43),
+    (0.73, 0.21, 0.33),
+    (0.98, 0.55, 0.04),
+    (0.99, 1.0, 0.64),
+];
+
+/// Variants of metrics used to gate a [`Comparison`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Metric {
+    /// Root mean square error, lower is better.
+    Rmse,
+    /// Maximum absolute error, lower is better.
+    MaxError,
+    /// Peak signal to noise ratio (in dB), higher is better.
+    Psnr,
+    /// Mean structural similarity index, higher is better.
+    Ssim,
+}
+
+impl Metric {
+    /// Return `true` if `value` of this metric is not past `threshold`.
+    pub fn passes(&self, value: f32, threshold: f32) -> bool {
+        match self {
+            Metric::Rmse | Metric::MaxError => value <= threshold,
+            Metric::Psnr | Metric::Ssim => value >= threshold,
+        }
+    }
+}
+
+/// Per-channel error metrics between a reference and a test [`HdrImage`].
+///
+/// Each metric is stored as a [`Color`], one value for each RGB channel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Comparison {
+    /// Root mean square error.
+    pub rmse: Color,
+    /// Maximum absolute error.
+    pub max_error: Color,
+    /// Peak signal to noise ratio (in dB), infinite for identical channels.
+    pub psnr: Color,
+    /// Mean structural similarity index, one for identical channels.
+    pub ssim: Color,
+}
+
+impl Comparison {
+    /// Compare `test` image against `reference` image.
+    ///
+    /// The peak value used by PSNR and SSIM is the maximum
+    /// color component of the `reference` image (one if it is black),
+    /// that is one for a saturated LDR image.
+    ///
+    /// **Note:** images must have the same shape.
+    pub fn new(reference: &HdrImage, test: &HdrImage) -> Self {
+        assert_eq!(reference.shape(), test.shape());
+        let (width, height) = reference.shape();
+        let (reference, test) = (channels(reference), channels(test));
+        let peak = reference
+            .iter()
+            .flatten()
+            .fold(0.0, |peak: f64, el| peak.max(*el));
+        let peak = if peak > 0.0 { peak } else { 1.0 };
+        let mut metrics = [[0.0; 3]; 4];
+        for (c, (a, b)) in reference.iter().zip(test.iter()).enumerate() {
+            let mse = a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum::<f64>()
+                / a.len().max(1) as f64;
+            metrics[0][c] = mse.sqrt() as f32;
+            metrics[1][c] =
+                a.iter()
+                    .zip(b)
+                    .fold(0.0, |max: f64, (a, b)| max.max((a - b).abs())) as f32;
+            metrics[2][c] = (10.0 * f64::log10(peak * peak / mse)) as f32;
+            metrics[3][c] = ssim(a, b, width as usize, height as usize, peak) as f32;
+        }
+        let [rmse, max_error, psnr, ssim] = metrics.map(|m| Color::from((m[0], m[1], m[2])));
+        Self {
+            rmse,
+            max_error,
+            psnr,
+            ssim,
+        }
+    }
+
+    /// Return the worst channel value of a [`Metric`].
+    pub fn worst(&self, metric: Metric) -> f32 {
+        match metric {
+            Metric::Rmse => self.rmse.into_iter().fold(0.0, f32::max),
+            Metric::MaxError => self.max_error.into_iter().fold(0.0, f32::max),
+            Metric::Psnr => self.psnr.into_iter().fold(f32::INFINITY, f32::min),
+            Metric::Ssim => self.ssim.into_iter().fold(f32::INFINITY, f32::min),
+        }
+    }
+}
+
+/// Return a false color [`HdrImage`] of the per-pixel error between
+/// `test` and `reference` images.
+///
+/// The error of each pixel is the maximum absolute error among its channels,
+/// mapped from black (no error) to light yellow (maximum error of the image).
+///
+/// **Note:** images must have the same shape.
+pub fn difference_image(reference: &HdrImage, test: &HdrImage) -> HdrImage {
+    assert_eq!(reference.shape(), test.shape());
+    let (width, height) = reference.shape();
+    let mut errors = Vec::with_capacity((width * height) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let (a, b) = (
+                reference.get_pixel(x, y).unwrap(),
+                test.get_pixel(x, y).unwrap(),
+            );
+            errors.push(
+                a.into_iter()
+                    .zip(b)
+                    .fold(0.0, |max: f32, (a, b)| max.max((a - b).abs())),
+            );
+        }
+    }
+    let max_error = errors.iter().fold(0.0, |max: f32, el| max.max(*el));
+    let scale = if max_error > 0.0 {
+        1.0 / max_error
+    } else {
+        0.0
+    };
+    let mut diff_img = HdrImage::new(width, height);
+    diff_img
+        .set_pixels(
+            errors
+                .into_iter()
+                .map(|error| false_color(error * scale))
+                .collect(),
+        )
+        .unwrap();
+    diff_img
+}
+
+/// Map `t` in `[0, 1]` to a [`Color`], interpolating linearly [`FALSE_COLOR_STOPS`].
+fn false_color(t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0) * (FALSE_COLOR_STOPS.len() - 1) as f32;
+    let i = (t as usize).min(FALSE_COLOR_STOPS.len() - 2);
+    let (c0, c1) = (
+        Color::from(FALSE_COLOR_STOPS[i]),
+        Color::from(FALSE_COLOR_STOPS[i + 1]),
+    );
+    c0 * (1.0 - (t - i as f32)) + c1 * (t - i as f32)
+}
+
+/// Split an [`HdrImage`] into its three RGB channels, row by row.
+fn channels(image: &HdrImage) -> [Vec<f64>; 3] {
+    let (width, height) = image.shape();
+    let mut channels = [vec![], vec![], vec![]];
+    for y in 0..height {
+        for x in 0..width {
+            for (channel, el) in channels.iter_mut().zip(image.get_pixel(x, y).unwrap()) {
+                channel.push(el as f64);
+            }
+        }
+    }
+    channels
+}
+
+/// Blur a `width x height` channel with a truncated gaussian window,
+/// weights are normalized again near the borders.
+fn blur(channel: &[f64], width: usize, height: usize) -> Vec<f64> {
+    let weights: Vec<f64> = (-SSIM_RADIUS..=SSIM_RADIUS)
+        .map(|d| f64::exp(-((d * d) as f64) / (2.0 * SSIM_SIGMA * SSIM_SIGMA)))
+        .collect();
+    let convolve = |get: &dyn Fn(usize) -> f64, pos: usize, len: usize| {
+        let (mut sum, mut norm) = (0.0, 0.0);
+        for (d, weight) in (-SSIM_RADIUS..=SSIM_RADIUS).zip(weights.iter()) {
+            let p = pos as i64 + d;
+            if (0..len as i64).contains(&p) {
+                sum += weight * get(p as usize);
+                norm += weight;
+            }
+        }
+        sum / norm
+    };
+    let mut horizontal = vec![0.0; channel.len()];
+    for y in 0..height {
+        for x in 0..width {
+            horizontal[y * width + x] = convolve(&|p| channel[y * width + p], x, width);
+        }
+    }
+    let mut blurred = vec![0.0; channel.len()];
+    for y in 0..height {
+        for x in 0..width {
+            blurred[y * width + x] = convolve(&|p| horizontal[p * width + x], y, height);
+        }
+    }
+    blurred
+}
+
+/// Return the mean structural similarity index between two `width x height` channels.
+///
+/// See [Wang et al. (2004)](https://doi.org/10.1109/TIP.2003.819861),
+/// with local statistics weighted by an 11x11 gaussian window (`sigma = 1.5`).
+fn ssim(a: &[f64], b: &[f64], width: usize, height: usize, peak: f64) -> f64 {
+    let product =
+        |a: &[f64], b: &[f64]| -> Vec<f64> { a.iter().zip(b).map(|(a, b)| a * b).collect() };
+    let (mu_a, mu_b) = (blur(a, width, height), blur(b, width, height));
+    let aa = blur(&product(a, a), width, height);
+    let bb = blur(&product(b, b), width, height);
+    let ab = blur(&product(a, b), width, height);
+    let (c1, c2) = ((SSIM_K1 * peak).powi(2), (SSIM_K2 * peak).powi(2));
+    let sum: f64 = (0..a.len())
+        .map(|i| {
+            let (mu_a, mu_b) = (mu_a[i], mu_b[i]);
+            let var_a = aa[i] - mu_a * mu_a;
+            let var_b = bb[i] - mu_b * mu_b;
+            let cov = ab[i] - mu_a * mu_b;
+            ((2.0 * mu_a * mu_b + c1) * (2.0 * cov + c2))
+                / ((mu_a * mu_a + mu_b * mu_b + c1) * (var_a + var_b + c2))
+        })
+        .sum();
+    sum / a.len().max(1) as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::misc::IsClose;
+
+    #[test]
+    fn test_comparison() {
+        let mut reference = HdrImage::new(16, 12);
+        for y in 0..12 {
+            for x in 0..16 {
+                reference
+                    .set_pixel(x, y, Color::from((x as f32 / 16.0, y as f32 / 12.0, 0.5)))
+                    .unwrap();
+            }
+        }
+        let same = Comparison::new(&reference, &reference);
+        assert_eq!(same.rmse, Color::default());
+        assert_eq!(same.max_error, Color::default());
+        assert!(same.psnr.into_iter().all(|psnr| psnr == f32::INFINITY));
+        assert!(same.ssim.is_close(Color::from((1.0, 1.0, 1.0))));
+
+        let mut test = reference.clone();
+        test.set_pixel(3, 4, Color::from((3.0 / 16.0, 4.0 / 12.0 + 0.5, 0.5)))
+            .unwrap();
+        let comparison = Comparison::new(&reference, &test);
+        assert!(comparison.rmse.r.is_close(0.0));
+        assert!(comparison.rmse.g.is_close(0.5 / f32::sqrt(192.0)));
+        assert!(comparison.max_error.is_close(Color::from((0.0, 0.5, 0.0))));
+        // Peak value is the maximum reference component, 15/16.
+        assert!(comparison
+            .psnr
+            .g
+            .is_close(10.0 * f32::log10((15.0_f32 / 16.0).powi(2) * 192.0 / 0.25)));
+        assert!(comparison.ssim.g < 1.0 && comparison.ssim.r.is_close(1.0));
+
+        assert!(comparison.worst(Metric::MaxError).is_close(0.5));
+        assert!(comparison.worst(Metric::Ssim) < 1.0);
+        assert!(Metric::Rmse.passes(comparison.worst(Metric::Rmse), 0.05));
+        assert!(!Metric::Rmse.passes(comparison.worst(Metric::Rmse), 0.01));
+        assert!(Metric::Psnr.passes(comparison.worst(Metric::Psnr), 20.0));
+        assert!(!Metric::Psnr.passes(comparison.worst(Metric::Psnr), 40.0));
+    }
+
+    #[test]
+    fn test_difference_image() {
+        let reference = HdrImage::new(4, 2);
+        let mut test = reference.clone();
+        test.set_pixel(1, 0, Color::from((0.0, 2.0, 0.0))).unwrap();
+        test.set_pixel(2, 1, Color::from((1.0, 0.0, 0.0))).unwrap();
+        let diff_img = difference_image(&reference, &test);
+        assert_eq!(diff_img.shape(), (4, 2));
+        assert_eq!(diff_img.get_pixel(0, 0).unwrap(), Color::default());
+        assert!(diff_img
+            .get_pixel(1, 0)
+            .unwrap()
+            .is_close(Color::from((0.99, 1.0, 0.64))));
+        assert!(diff_img
+            .get_pixel(2, 1)
+            .unwrap()
+            .is_close(Color::from((0.73, 0.21, 0.33))));
+    }
+}
@@ -0,0 +1,175 @@
+//! Dithering module.
+//!
+//! Provides [`Dither`](enum@Dither) enum,
+//! used to hide banding when quantizing colors to 8 bit.
+use crate::random::Pcg;
+use std::sync::OnceLock;
+
+/// Side of the 8x8 Bayer matrix.
+const BAYER_SIZE: usize = 8;
+/// Side of the tiled blue noise mask.
+const BLUE_NOISE_SIZE: usize = 64;
+/// Standard deviation (in pixels) of the gaussian filter used
+/// by void-and-cluster to measure clusters and voids.
+const BLUE_NOISE_SIGMA: f32 = 1.5;
+
+/// Variants of dithering threshold masks.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Dither {
+    /// No dithering, quantized values are truncated.
+    #[default]
+    None,
+    /// Ordered dithering with an 8x8 Bayer matrix.
+    Ordered,
+    /// Dithering with a 64x64 blue noise mask,
+    /// generated with void-and-cluster method.
+    BlueNoise,
+}
+
+impl Dither {
+    /// Return the threshold in `[0, 1)` to add to pixel `(x, y)` before truncation.
+    ///
+    /// Masks are tiled over the whole image.
+    pub fn threshold(&self, x: u32, y: u32) -> f32 {
+        match self {
+            Dither::None => 0.0,
+            Dither::Ordered => {
+                let (x, y) = (x as usize % BAYER_SIZE, y as usize % BAYER_SIZE);
+                (bayer(x, y) as f32 + 0.5) / (BAYER_SIZE * BAYER_SIZE) as f32
+            },
+            Dither::BlueNoise => {
+                let (x, y) = (x as usize % BLUE_NOISE_SIZE, y as usize % BLUE_NOISE_SIZE);
+                blue_noise()[y * BLUE_NOISE_SIZE + x]
+            },
+        }
+    }
+}
+
+/// Return the rank of `(x, y)` inside the 8x8 Bayer matrix,
+/// interleaving the bits of `x ^ y` and `y`.
+fn bayer(x: usize, y: usize) -> usize {
+    let xor = x ^ y;
+    (0..3).fold(0, |rank, bit| {
+        rank | ((xor >> bit) & 1) << (2 * (2 - bit) + 1) | ((y >> bit) & 1) << (2 * (2 - bit))
+    })
+}
+
+/// Return the blue noise thresholds mask, computed once at first use.
+fn blue_noise() -> &'static [f32] {
+    static MASK: OnceLock<Vec<f32>> = OnceLock::new();
+    MASK.get_or_init(void_and_cluster)
+}
+
+/// Generate a blue noise thresholds mask with
+/// [void-and-cluster](https://doi.org/10.1117/12.152707) method (Ulichney, 1993).
+///
+/// Starting from a relaxed random pattern, pixels are ranked removing
+/// the tightest clusters and then filling the largest voids,
+/// where clusters and voids are measured by a toroidal gaussian filter.\
+/// Ranks are finally mapped to thresholds in `[0, 1)`.
+fn void_and_cluster() -> Vec<f32> {
+    let n = BLUE_NOISE_SIZE * BLUE_NOISE_SIZE;
+    // Gaussian filter as a function of the toroidal offset between two pixels.
+    let kernel: Vec<f32> = (0..n)
+        .map(|offset| {
+            let wrap = |d: usize| d.min(BLUE_NOISE_SIZE - d) as f32;
+            let (dx, dy) = (
+                wrap(offset % BLUE_NOISE_SIZE),
+                wrap(offset / BLUE_NOISE_SIZE),
+            );
+            f32::exp(-(dx * dx + dy * dy) / (2.0 * BLUE_NOISE_SIGMA * BLUE_NOISE_SIGMA))
+        })
+        .collect();
+    let toggle = |pattern: &mut [bool], energy: &mut [f32], index: usize| {
+        pattern[index] = !pattern[index];
+        let sign = if pattern[index] { 1.0 } else { -1.0 };
+        let (x, y) = (index % BLUE_NOISE_SIZE, index / BLUE_NOISE_SIZE);
+        for (other, e) in energy.iter_mut().enumerate() {
+            let dx = (other % BLUE_NOISE_SIZE + BLUE_NOISE_SIZE - x) % BLUE_NOISE_SIZE;
+            let dy = (other / BLUE_NOISE_SIZE + BLUE_NOISE_SIZE - y) % BLUE_NOISE_SIZE;
+            *e += sign * kernel[dy * BLUE_NOISE_SIZE + dx];
+        }
+    };
+    let tightest_cluster = |pattern: &[bool], energy: &[f32]| {
+        (0..n)
+            .filter(|index| pattern[*index])
+            .max_by(|a, b| energy[*a].total_cmp(&energy[*b]))
+            .unwrap()
+    };
+    let largest_void = |pattern: &[bool], energy: &[f32]| {
+        (0..n)
+            .filter(|index| !pattern[*index])
+            .min_by(|a, b| energy[*a].total_cmp(&energy[*b]))
+            .unwrap()
+    };
+
+    // Initial random pattern, relaxed moving tightest clusters into largest voids.
+    let mut pcg = Pcg::default();
+    let (mut pattern, mut energy) = (vec![false; n], vec![0.0; n]);
+    let ones = n / 10;
+    while pattern.iter().filter(|one| **one).count() < ones {
+        let index = pcg.random() as usize % n;
+        if !pattern[index] {
+            toggle(&mut pattern, &mut energy, index);
+        }
+    }
+    loop {
+        let cluster = tightest_cluster(&pattern, &energy);
+        toggle(&mut pattern, &mut energy, cluster);
+        let void = largest_void(&pattern, &energy);
+        toggle(&mut pattern, &mut energy, void);
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut rank = vec![0; n];
+    // Rank initial pattern pixels, removing tightest clusters.
+    let (mut removed, mut removed_energy) = (pattern.clone(), energy.clone());
+    for r in (0..ones).rev() {
+        let cluster = tightest_cluster(&removed, &removed_energy);
+        toggle(&mut removed, &mut removed_energy, cluster);
+        rank[cluster] = r;
+    }
+    // Rank remaining pixels, filling largest voids.
+    for r in ones..n {
+        let void = largest_void(&pattern, &energy);
+        toggle(&mut pattern, &mut energy, void);
+        rank[void] = r;
+    }
+    rank.into_iter()
+        .map(|r| (r as f32 + 0.5) / n as f32)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dither() {
+        // Every mask is a permutation of evenly spaced thresholds.
+        for (dither, size) in [
+            (Dither::Ordered, BAYER_SIZE),
+            (Dither::BlueNoise, BLUE_NOISE_SIZE),
+        ] {
+            let n = size * size;
+            let mut ranks: Vec<usize> = (0..size as u32)
+                .flat_map(|y| (0..size as u32).map(move |x| (x, y)))
+                .map(|(x, y)| (dither.threshold(x, y) * n as f32) as usize)
+                .collect();
+            ranks.sort();
+            assert_eq!(ranks, (0..n).collect::<Vec<usize>>());
+            assert_eq!(
+                dither.threshold(3, 5),
+                dither.threshold(3 + size as u32, 5 + 2 * size as u32)
+            );
+        }
+        assert_eq!(Dither::None.threshold(3, 5), 0.0);
+        assert_eq!(bayer(0, 0), 0);
+        assert_eq!(bayer(1, 1), 16);
+        assert_eq!(bayer(1, 0), 32);
+        assert_eq!(bayer(0, 1), 48);
+        assert_eq!(bayer(2, 0), 8);
+    }
+}
@@ -28,16 +28,88 @@ pub enum HdrImageErr {
     #[error("impossible to parse {1} as float from pfm file\n\tsource: {s}",
         s = format!("{}", .0).to_lowercase())]
     PfmFloatParseFailure(#[source] std::num::ParseFloatError, String),
+    #[error("impossible to read from ldr file\n\tsource: {}",
+        format!("{}", .0).to_lowercase())]
+    LdrFileReadFailure(#[source] image::ImageError),
     #[error("impossible to write to ldr file\n\tsource: {}",
         format!("{}", .0).to_lowercase())]
     LdrFileWriteFailure(#[source] image::ImageError),
-    #[error("unsupported {0:?} ldr file format, only \"ff\" or \"png\" supported")]
+    #[error("impossible to write to png file\n\tsource: {}",
+        format!("{}", .0).to_lowercase())]
+    PngFileWriteFailure(#[source] png::EncodingError),
+    #[error("unsupported {0:?} ldr file format, only {formats} supported",
+        formats = crate::hdrimage::ldr_formats()
+            .iter()
+            .map(|format| format!("{:?}", format.extensions_str()[0]))
+            .collect::<Vec<String>>()
+            .join(", "))]
     UnsupportedLdrFileFormat(String),
+    #[error("impossible to read from exr file\n\tsource: {}",
+        format!("{}", .0).to_lowercase())]
+    ExrFileReadFailure(#[source] exr::error::Error),
+    #[error("impossible to write to exr file\n\tsource: {}",
+        format!("{}", .0).to_lowercase())]
+    ExrFileWriteFailure(#[source] exr::error::Error),
+    #[error("invalid rgbe file format: {0:?}")]
+    InvalidRgbeFileFormat(String),
+    #[error("impossible to read from rgbe file\n\tsource: {}",
+        format!("{}", .0).to_lowercase())]
+    RgbeFileReadFailure(#[source] std::io::Error),
+    #[error("impossible to write to rgbe file\n\tsource: {}",
+        format!("{}", .0).to_lowercase())]
+    RgbeFileWriteFailure(#[source] std::io::Error),
+    #[error("impossible to parse {1} as integer from rgbe file\n\tsource: {s}",
+        s = format!("{}", .0).to_lowercase())]
+    RgbeIntParseFailure(#[source] std::num::ParseIntError, String),
+    #[error("impossible to parse {1} as float from rgbe file\n\tsource: {s}",
+        s = format!("{}", .0).to_lowercase())]
+    RgbeFloatParseFailure(#[source] std::num::ParseFloatError, String),
+    #[error("unsupported {0:?} hdr file format, only \"pfm\", \"exr\" or \"hdr\" supported")]
+    UnsupportedHdrFileFormat(String),
+}
+
+/// Error enum for [`Mesh`](../mesh) module.
+#[derive(Error, Debug)]
+pub enum MeshErr {
+    #[error("invalid obj file format: {0:?}")]
+    InvalidObjFileFormat(String),
+    #[error("impossible to read from obj file\n\tsource: {}",
+        format!("{}", .0).to_lowercase())]
+    ObjFileReadFailure(#[source] std::io::Error),
+    #[error("impossible to parse {1} as integer from obj file\n\tsource: {s}",
+        s = format!("{}", .0).to_lowercase())]
+    ObjIntParseFailure(#[source] std::num::ParseIntError, String),
+    #[error("impossible to parse {1} as float from obj file\n\tsource: {s}",
+        s = format!("{}", .0).to_lowercase())]
+    ObjFloatParseFailure(#[source] std::num::ParseFloatError, String),
+}
+
+/// Error enum for [`Checkpoint`](../checkpoint) module.
+#[derive(Error, Debug)]
+pub enum CheckpointErr {
+    #[error("impossible to read checkpoint image\n\tsource: {0}")]
+    PfmFileReadFailure(#[source] HdrImageErr),
+    #[error("impossible to write checkpoint image\n\tsource: {0}")]
+    PfmFileWriteFailure(#[source] HdrImageErr),
+    #[error("impossible to read from samples file\n\tsource: {}",
+        format!("{}", .0).to_lowercase())]
+    SamplesFileReadFailure(#[source] std::io::Error),
+    #[error("impossible to write to samples file\n\tsource: {}",
+        format!("{}", .0).to_lowercase())]
+    SamplesFileWriteFailure(#[source] std::io::Error),
+    #[error("impossible to parse samples count as integer\n\tsource: {}",
+        format!("{}", .0).to_lowercase())]
+    SamplesIntParseFailure(#[source] std::num::ParseIntError),
+    #[error("invalid checkpoint: {0}")]
+    InvalidCheckpoint(String),
 }
 
 /// Error enum for [`convert`](../fn.convert.html) function inside [`main`](../fn.main.html).
 #[derive(Error, Debug)]
 pub enum ConvertErr {
+    #[error("{msg}\n\tsource: {0}",
+        msg = format!("{:?} flag invalid value, expected integer number", .1).bold())]
+    IntParseFailure(#[source] std::num::ParseIntError, String),
     #[error("{msg}\n\tsource: {0}",
         msg = format!("{:?} flag invalid value, expected floating-point number", .1).bold())]
     FloatParseFailure(#[source] std::num::ParseFloatError, String),
@@ -84,6 +156,13 @@ pub enum SceneErr {
         msg: String,
         src: HdrImageErr,
     },
+    #[error("{} {}\n\tsource: {}",
+        format!(":{}:{}", loc.line_num, loc.col_num).yellow(), msg, src)]
+    ObjFileReadFailure {
+        loc: SourceLocation,
+        msg: String,
+        src: MeshErr,
+    },
     #[error("{0}")]
     UnexpectedMatch(String),
     #[error("{} {}",
@@ -92,6 +171,9 @@ pub enum SceneErr {
     #[error("{} {}",
         format!(":{}:{}", loc.line_num, loc.col_num).yellow(), msg)]
     InvalidCamera { loc: SourceLocation, msg: String },
+    #[error("{} {}",
+        format!(":{}:{}", loc.line_num, loc.col_num).yellow(), msg)]
+    InvalidValue { loc: SourceLocation, msg: String },
     #[error("{sep} impossible to read from scene file\n\tsource: {0}", sep = "::".yellow())]
     SceneFileReadFailure(#[source] std::io::Error),
 }
@@ -115,6 +197,22 @@ pub enum RenderErr {
                                            .to_str().unwrap().yellow(),
             src = .0))]
     SceneError(#[source] SceneErr, String),
+    #[error("{msg}\n\tsource: {0}", msg = "render checkpoint error".bold())]
+    CheckpointError(#[source] CheckpointErr),
+}
+
+/// Error enum for [`compare`](../fn.compare.html) function inside [`main`](../fn.main.html).
+#[derive(Error, Debug)]
+pub enum CompareErr {
+    #[error("{msg}\n\tsource: {0}",
+        msg = format!("{:?} flag invalid value, expected floating-point number", .1).bold())]
+    FloatParseFailure(#[source] std::num::ParseFloatError, String),
+    #[error("{msg}\n\tsource: {0}", msg = "image compare input/output error".bold())]
+    IoError(#[source] HdrImageErr),
+    #[error("{}", format!("images shape mismatch: {:?} and {:?}", .0, .1).bold())]
+    ShapeMismatch((u32, u32), (u32, u32)),
+    #[error("{}", format!("{} {} past threshold {}", .0, .1, .2).bold())]
+    ThresholdExceeded(String, f32, f32),
 }
 
 /// Error enum for [`completion`](../fn.completion.html) function inside [`main`](../fn.main.html).
@@ -3,7 +3,11 @@
 //! Provides [`HdrImage`](struct@HdrImage) struct.
 
 use byteorder::{ReadBytesExt, WriteBytesExt};
-use image::{DynamicImage, ImageFormat, Rgb, Rgba};
+use exr::prelude::{
+    f16, read_first_rgba_layer_from_file, Compression, Encoding, Image, SpecificChannels, Vec2,
+    WritableImage,
+};
+use image::{DynamicImage, ImageFormat, Rgba};
 use std::{
     fs::File,
     io::{BufRead, BufReader, BufWriter, Write},
@@ -12,7 +16,13 @@ use std::{
     vec::Vec,
 };
 
-use crate::{color::Color, error::HdrImageErr, misc::ByteOrder};
+use crate::{
+    color::Color,
+    dither::Dither,
+    error::HdrImageErr,
+    misc::ByteOrder,
+    tonemap::{ToneMap, ToneMapping},
+};
 
 const DELTA: f32 = 1e-10;
 
@@ -95,6 +105,25 @@ impl HdrImage {
         }
     }
 
+    /// Blend pixels matrix, of the correct size, into the current one as a running average,
+    /// where `pixels` is the `n`-th (one-based) contribution.
+    ///
+    /// Otherwise return [`HdrImageErr::InvalidPixelsSize`] variant.
+    pub fn average_pixels(&mut self, pixels: Vec<Color>, n: u32) -> Result<(), HdrImageErr> {
+        if self.pixels.len() == pixels.len() {
+            let (old_weight, new_weight) = ((n - 1) as f32 / n as f32, 1.0 / n as f32);
+            for (old, new) in self.pixels.iter_mut().zip(pixels) {
+                *old = *old * old_weight + new * new_weight;
+            }
+            Ok(())
+        } else {
+            Err(HdrImageErr::InvalidPixelsSize(
+                pixels.len() as u32,
+                self.pixels.len() as u32,
+            ))
+        }
+    }
+
     /// Read a pfm image from `buf_reader` with [`std::io::BufRead`] trait implementation.
     ///
     /// The expected input buffer must respect pfm format:
@@ -232,6 +261,249 @@ impl HdrImage {
         self.write_pfm_image(&mut writer, endianness)
     }
 
+    /// Read an OpenEXR image from `path`,
+    /// using [`exr`](https://github.com/johannesvollmer/exrs) library.
+    ///
+    /// Only the first valid layer, at its largest resolution level, is read.\
+    /// `R`, `G` and `B` channels, stored as half or float samples, are required,
+    /// while the optional `A` channel is discarded.
+    ///
+    ///  Return a [`HdrImage`] object containing the image inside a [`std::result::Result`].\
+    ///  If an error occurs the result contains an [`HdrImageErr`] error variant.
+    pub fn read_exr_file(path: &Path) -> Result<Self, HdrImageErr> {
+        let image = read_first_rgba_layer_from_file(
+            path,
+            |resolution, _| HdrImage::new(resolution.width() as u32, resolution.height() as u32),
+            |hdr_img: &mut HdrImage, position, (r, g, b, _a): (f32, f32, f32, f32)| {
+                let offset = hdr_img.pixel_offset(position.x() as u32, position.y() as u32);
+                hdr_img.pixels[offset] = Color::from((r, g, b));
+            },
+        )
+        .map_err(HdrImageErr::ExrFileReadFailure)?;
+        Ok(image.layer_data.channel_data.pixels)
+    }
+
+    /// Write an OpenEXR image to `path`,
+    /// using [`exr`](https://github.com/johannesvollmer/exrs) library.
+    ///
+    /// The struct [`format`](struct@ExrFormat) specifies samples precision,
+    /// channels and compression to be used in the file.
+    ///
+    /// If an error occurs the result contains an [`HdrImageErr`] error variant.
+    pub fn write_exr_file(&self, path: &Path, format: ExrFormat) -> Result<(), HdrImageErr> {
+        let size = (self.width as usize, self.height as usize);
+        let encoding = Encoding {
+            compression: match format.compression {
+                ExrCompression::Uncompressed => Compression::Uncompressed,
+                ExrCompression::Zip => Compression::ZIP16,
+                ExrCompression::Piz => Compression::PIZ,
+            },
+            ..Encoding::UNCOMPRESSED
+        };
+        let pixel = |position: Vec2<usize>| self.pixels[position.y() * size.0 + position.x()];
+        let half = |pixel: Color| {
+            (
+                f16::from_f32(pixel.r),
+                f16::from_f32(pixel.g),
+                f16::from_f32(pixel.b),
+            )
+        };
+        match (format.precision, format.alpha) {
+            (ExrPrecision::Half, false) => Image::from_encoded_channels(
+                size,
+                encoding,
+                SpecificChannels::rgb(|position| half(pixel(position))),
+            )
+            .write()
+            .to_file(path),
+            (ExrPrecision::Half, true) => Image::from_encoded_channels(
+                size,
+                encoding,
+                SpecificChannels::rgba(|position| {
+                    let (r, g, b) = half(pixel(position));
+                    (r, g, b, f16::ONE)
+                }),
+            )
+            .write()
+            .to_file(path),
+            (ExrPrecision::Float, false) => Image::from_encoded_channels(
+                size,
+                encoding,
+                SpecificChannels::rgb(|position| {
+                    let pixel = pixel(position);
+                    (pixel.r, pixel.g, pixel.b)
+                }),
+            )
+            .write()
+            .to_file(path),
+            (ExrPrecision::Float, true) => Image::from_encoded_channels(
+                size,
+                encoding,
+                SpecificChannels::rgba(|position| {
+                    let pixel = pixel(position);
+                    (pixel.r, pixel.g, pixel.b, 1.0_f32)
+                }),
+            )
+            .write()
+            .to_file(path),
+        }
+        .map_err(HdrImageErr::ExrFileWriteFailure)
+    }
+
+    /// Read a Radiance rgbe image from `buf_reader` with [`std::io::BufRead`] trait implementation.
+    ///
+    /// The expected input buffer must respect rgbe format:
+    /// ```text
+    /// #?RADIANCE                 # ASCII text, '\n' as line separator
+    /// FORMAT=32-bit_rle_rgbe     # optional header variables, one per line
+    ///                            # empty line, end of header variables
+    /// -Y height +X width         # ASCII text, '\n' as line separator
+    /// scanlines                  # binary content (RGBE x #pixels)
+    /// ```
+    /// Little description:
+    /// * `#?RADIANCE` - the magic (also `#?RGBE` is accepted)
+    /// * `FORMAT` - pixels encoding, only `32-bit_rle_rgbe` supported,
+    ///   while `EXPOSURE` values are divided out from pixels and any other variable is ignored
+    /// * `-Y height +X width` - image shape and orientation, scanlines from top (`-Y`)
+    ///   or from bottom (`+Y`) of the image
+    /// * `scanlines` - one byte per color component plus a shared exponent,
+    ///   each scanline could be run length encoded ([`read_rgbe_scanline`])
+    ///
+    /// Possible read failures, in precedence order:
+    /// 1. Invalid magic (no `#?RADIANCE` or `#?RGBE`)
+    /// 2. Unsupported pixels format or invalid exposure
+    /// 3. Lack of end of header variables
+    /// 4. Invalid image shape or orientation ([`parse_rgbe_shape`])
+    /// 5. Invalid scanlines (unexpected EOF or malformed run length encoding)
+    ///
+    ///  Return a [`HdrImage`] object containing the image inside a [`std::result::Result`].\
+    ///  If an error occurs the result contains an [`HdrImageErr`] error variant.
+    fn read_rgbe_image<R: BufRead>(buf_reader: &mut R) -> Result<Self, HdrImageErr> {
+        let mut line = String::new();
+        buf_reader
+            .read_line(&mut line)
+            .map_err(HdrImageErr::RgbeFileReadFailure)?;
+        if !matches!(line.trim_end(), "#?RADIANCE" | "#?RGBE") {
+            return Err(HdrImageErr::InvalidRgbeFileFormat(String::from(
+                "wrong magic inside header",
+            )));
+        }
+        let mut exposure = 1.0;
+        loop {
+            line.clear();
+            let bytes = buf_reader
+                .read_line(&mut line)
+                .map_err(HdrImageErr::RgbeFileReadFailure)?;
+            let variable = line.trim_end();
+            if bytes == 0 {
+                return Err(HdrImageErr::InvalidRgbeFileFormat(String::from(
+                    "expected empty line after header variables",
+                )));
+            } else if variable.is_empty() {
+                break;
+            } else if let Some(format) = variable.strip_prefix("FORMAT=") {
+                if format != "32-bit_rle_rgbe" {
+                    return Err(HdrImageErr::InvalidRgbeFileFormat(format!(
+                        "unsupported pixels format {format}"
+                    )));
+                }
+            } else if let Some(value) = variable.strip_prefix("EXPOSURE=") {
+                exposure *= f32::from_str(value.trim())
+                    .map_err(|e| HdrImageErr::RgbeFloatParseFailure(e, String::from("exposure")))?;
+            }
+        }
+        line.clear();
+        buf_reader
+            .read_line(&mut line)
+            .map_err(HdrImageErr::RgbeFileReadFailure)?;
+        let (width, height, bottom_up) = parse_rgbe_shape(line.trim_end())?;
+        let mut hdr_img = HdrImage::new(width, height);
+        let mut scanline = vec![[0_u8; 4]; width as usize];
+        for row in 0..height {
+            read_rgbe_scanline(buf_reader, &mut scanline)?;
+            let y = if bottom_up { height - 1 - row } else { row };
+            for (x, rgbe) in scanline.iter().enumerate() {
+                let offset = hdr_img.pixel_offset(x as u32, y);
+                hdr_img.pixels[offset] = rgbe_to_color(*rgbe) * (1.0 / exposure);
+            }
+        }
+        Ok(hdr_img)
+    }
+
+    /// Read a Radiance rgbe image from `path`, wrapper function around
+    /// [`read_rgbe_image`](#method.read_rgbe_image).
+    ///
+    ///  Return a [`HdrImage`] object containing the image inside a [`std::result::Result`].\
+    ///  If an error occurs the result contains an [`HdrImageErr`] error variant.
+    pub fn read_rgbe_file(path: &Path) -> Result<Self, HdrImageErr> {
+        let file = File::open(path).map_err(HdrImageErr::RgbeFileReadFailure)?;
+        let mut buf_reader = BufReader::new(file);
+        HdrImage::read_rgbe_image(&mut buf_reader)
+    }
+
+    /// Write a Radiance rgbe image to `stream` with [`std::io::Write`] trait implementation.
+    ///
+    /// Scanlines are written from top to bottom, run length encoded
+    /// when the image width allows it ([`write_rgbe_scanline`]).
+    ///
+    /// If an error occurs the result contains an [`HdrImageErr`] error variant.
+    fn write_rgbe_image<W: Write>(&self, stream: &mut W) -> Result<(), HdrImageErr> {
+        let header = format!(
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+            self.height, self.width
+        );
+        stream
+            .write_all(header.as_bytes())
+            .map_err(HdrImageErr::RgbeFileWriteFailure)?;
+        for row in self.pixels.chunks(self.width.max(1) as usize) {
+            let scanline: Vec<[u8; 4]> = row.iter().map(|pixel| color_to_rgbe(*pixel)).collect();
+            write_rgbe_scanline(stream, &scanline).map_err(HdrImageErr::RgbeFileWriteFailure)?;
+        }
+        Ok(())
+    }
+
+    /// Write a Radiance rgbe image to `path`, wrapper function around
+    /// [`write_rgbe_image`](#method.write_rgbe_image).
+    ///
+    /// If an error occurs the result contains an [`HdrImageErr`] error variant.
+    pub fn write_rgbe_file(&self, path: &Path) -> Result<(), HdrImageErr> {
+        let file = File::create(path).map_err(HdrImageErr::RgbeFileWriteFailure)?;
+        let mut writer = BufWriter::new(file);
+        self.write_rgbe_image(&mut writer)?;
+        writer.flush().map_err(HdrImageErr::RgbeFileWriteFailure)
+    }
+
+    /// Read an HDR image from `path`.
+    ///
+    /// **Note:** the input format is auto-detected from the file name extension,\
+    /// `.exr` files are read with [`read_exr_file`](#method.read_exr_file),
+    /// `.hdr` files with [`read_rgbe_file`](#method.read_rgbe_file),
+    /// any other file with [`read_pfm_file`](#method.read_pfm_file).
+    pub fn read_hdr_file(path: &Path) -> Result<Self, HdrImageErr> {
+        match hdr_extension(path).as_str() {
+            "exr" => HdrImage::read_exr_file(path),
+            "hdr" => HdrImage::read_rgbe_file(path),
+            _ => HdrImage::read_pfm_file(path),
+        }
+    }
+
+    /// Write an HDR image to `path`.
+    ///
+    /// **Note:** the output format is auto-detected from the file name extension,\
+    /// only three HDR image format are supported `.pfm` (little endian),
+    /// `.exr` (with the given [`exr_format`](struct@ExrFormat))
+    /// and `.hdr` (Radiance rgbe).
+    ///
+    /// In case of errors, `std::result::Result` is an [`HdrImageErr`] error variant.
+    pub fn write_hdr_file(&self, path: &Path, exr_format: ExrFormat) -> Result<(), HdrImageErr> {
+        match hdr_extension(path).as_str() {
+            "pfm" => self.write_pfm_file(path, ByteOrder::LittleEndian),
+            "exr" => self.write_exr_file(path, exr_format),
+            "hdr" => self.write_rgbe_file(path),
+            ext => Err(HdrImageErr::UnsupportedHdrFileFormat(String::from(ext))),
+        }
+    }
+
     /// Return the average luminosity of the image.
     ///
     /// The [`DELTA`] constant is used to prevent  numerical problems
@@ -258,23 +530,147 @@ impl HdrImage {
         }
     }
 
+    /// Return the maximum luminosity of the image.
+    pub fn max_luminosity(&self) -> f32 {
+        self.pixels
+            .iter()
+            .map(|pixel| pixel.luminosity())
+            .fold(0.0, f32::max)
+    }
+
     /// Adjust the color levels of the brightest pixels in the image.
-    pub fn clamp_image(&mut self) {
+    ///
+    /// Different variants of [`tone_map`](enum@ToneMap) enum can be chosen,
+    /// [`ToneMap::default`] is a Reinhard curve.
+    pub fn clamp_image(&mut self, tone_map: ToneMap) {
         for pixel in self.pixels.iter_mut() {
-            pixel.r = clamp(pixel.r);
-            pixel.g = clamp(pixel.g);
-            pixel.b = clamp(pixel.b);
+            *pixel = tone_map.tone_map_color(*pixel);
+        }
+    }
+
+    /// Return the mip pyramid of the image, from the image itself down to a `1x1` level.
+    ///
+    /// Each level halves the shape of the previous one (rounding up),
+    /// averaging blocks of `2x2` pixels (edge pixels are repeated for odd shapes).\
+    /// An empty image is its own pyramid.
+    pub fn mip_pyramid(&self) -> Vec<HdrImage> {
+        let mut levels = vec![self.clone()];
+        while let Some(level) = levels.last().filter(|level| level.pixels.len() > 1) {
+            let (width, height) = (level.width.div_ceil(2), level.height.div_ceil(2));
+            let texel = |x: u32, y: u32| {
+                level.pixels[level.pixel_offset(x.min(level.width - 1), y.min(level.height - 1))]
+            };
+            let pixels = (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| {
+                    (texel(2 * x, 2 * y)
+                        + texel(2 * x + 1, 2 * y)
+                        + texel(2 * x, 2 * y + 1)
+                        + texel(2 * x + 1, 2 * y + 1))
+                        * 0.25
+                })
+                .collect();
+            levels.push(HdrImage {
+                width,
+                height,
+                pixels,
+            });
+        }
+        levels
+    }
+
+    /// Read an LDR image from `path`.
+    ///
+    /// **Note:** the input format is auto-detected from the file name extension,
+    /// same LDR image formats of [`write_ldr_file`](#method.write_ldr_file) are supported.\
+    /// Color components are mapped into `[0, 1]` as they are,
+    /// without inverting the transfer function used to encode them.
+    ///
+    /// In case of errors, `std::result::Result` is an [`HdrImageErr`] error variant.
+    pub fn read_ldr_file(path: &Path) -> Result<Self, HdrImageErr> {
+        let image_format = ImageFormat::from_path(path).map_err(HdrImageErr::LdrFileReadFailure)?;
+        if !ldr_formats().contains(&image_format) {
+            return Err(HdrImageErr::UnsupportedLdrFileFormat(String::from(
+                path.extension().unwrap().to_str().unwrap_or(""),
+            )));
+        }
+        let ldr_img = image::open(path)
+            .map_err(HdrImageErr::LdrFileReadFailure)?
+            .into_rgb32f();
+        let mut hdr_img = HdrImage::new(ldr_img.width(), ldr_img.height());
+        hdr_img.pixels = ldr_img
+            .pixels()
+            .map(|pixel| Color::from((pixel[0], pixel[1], pixel[2])))
+            .collect();
+        Ok(hdr_img)
+    }
+
+    /// Return the 8 bit RGB components of the image, row by row,
+    /// encoded and dithered as specified by [`format`](struct@LdrFormat).
+    fn ldr_pixels_8(&self, format: LdrFormat) -> Vec<u8> {
+        let mut data = Vec::with_capacity(3 * self.pixels.len());
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let threshold = format.dither.threshold(x, y);
+                let pixel = format.transfer.encode_color(self.get_pixel(x, y).unwrap());
+                data.extend(pixel.into_iter().map(|el| (255.0 * el + threshold) as u8));
+            }
         }
+        data
+    }
+
+    /// Return the 16 bit RGB components of the image, row by row,
+    /// encoded as specified by [`format`](struct@LdrFormat).
+    fn ldr_pixels_16(&self, format: LdrFormat) -> Vec<u16> {
+        self.pixels
+            .iter()
+            .flat_map(|pixel| format.transfer.encode_color(*pixel).into_iter())
+            .map(|el| (65535.0 * el) as u16)
+            .collect()
+    }
+
+    /// Save the image as an 8 bit RGB `image_format` file,
+    /// using [`image`](https://github.com/image-rs/image) library encoders.
+    #[cfg(any(feature = "tiff", feature = "bmp", feature = "tga", feature = "webp"))]
+    fn save_rgb8(
+        &self,
+        path: &Path,
+        image_format: ImageFormat,
+        format: LdrFormat,
+    ) -> Result<(), HdrImageErr> {
+        image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(
+            self.width,
+            self.height,
+            self.ldr_pixels_8(format),
+        )
+        .unwrap()
+        .save_with_format(path, image_format)
+        .map_err(HdrImageErr::LdrFileWriteFailure)
     }
 
     /// Save the image in a Low Dynamic Range (LDR) format,
-    /// using [`image`](https://github.com/image-rs/image) library.
+    /// using [`image`](https://github.com/image-rs/image)
+    /// and [`png`](https://github.com/image-rs/image-png) libraries.
     ///
-    /// `gamma` is transfer function parameter.
+    /// The struct [`format`](struct@LdrFormat) specifies transfer function,
+    /// bit depth, dithering and jpeg quality to be used in the file.
     ///
-    /// **Note:** the output format is auto-detected from the file name extension,\
-    /// only two LDR image format are supported `.ff` ([`farbfeld`](https://tools.suckless.org/farbfeld/))
-    /// and `.png` ([`PNG`](https://en.wikipedia.org/wiki/Portable_Network_Graphics)).
+    /// **Note:** the output format is auto-detected from the file name extension,
+    /// supported LDR image formats are:
+    /// * `.ff` ([`farbfeld`](https://tools.suckless.org/farbfeld/)), always 16 bit;
+    /// * `.png` ([`PNG`](https://en.wikipedia.org/wiki/Portable_Network_Graphics)), 8 or 16 bit
+    ///   with an `sRGB` or a `gAMA` chunk describing its transfer function
+    ///   (none for the identity);
+    /// * `.jpg` ([`JPEG`](https://en.wikipedia.org/wiki/JPEG)), 8 bit,
+    ///   with `jpeg` cargo feature;
+    /// * `.tiff` ([`TIFF`](https://en.wikipedia.org/wiki/TIFF)), 8 or 16 bit,
+    ///   with `tiff` cargo feature;
+    /// * `.bmp` ([`BMP`](https://en.wikipedia.org/wiki/BMP_file_format)), 8 bit,
+    ///   with `bmp` cargo feature;
+    /// * `.tga` ([`TGA`](https://en.wikipedia.org/wiki/Truevision_TGA)), 8 bit,
+    ///   with `tga` cargo feature;
+    /// * `.webp` ([`WebP`](https://en.wikipedia.org/wiki/WebP)), 8 bit lossless,
+    ///   with `webp` cargo feature.
     ///
     /// **Note:** before calling this function, you should apply a
     /// tone-mapping algorithm to the image and \
@@ -283,50 +679,101 @@ impl HdrImage {
     /// and [`clamp_image`](#method.clamp_image) to do this.
     ///
     /// In case of errors, `std::result::Result` is an [`HdrImageErr`] error variant.
-    pub fn write_ldr_file(&self, path: &Path, gamma: f32) -> Result<(), HdrImageErr> {
-        let format = ImageFormat::from_path(path).map_err(HdrImageErr::LdrFileWriteFailure)?;
-        match format {
+    pub fn write_ldr_file(&self, path: &Path, format: LdrFormat) -> Result<(), HdrImageErr> {
+        let image_format =
+            ImageFormat::from_path(path).map_err(HdrImageErr::LdrFileWriteFailure)?;
+        match image_format {
             ImageFormat::Farbfeld => {
                 let mut ldr_img = DynamicImage::new_rgb16(self.width, self.height).into_rgba16();
                 for y in 0..self.height {
                     for x in 0..self.width {
-                        let pixel = self.get_pixel(x, y).unwrap();
+                        let pixel = format.transfer.encode_color(self.get_pixel(x, y).unwrap());
                         ldr_img.put_pixel(
                             x,
                             y,
                             Rgba([
-                                (65535.0 * f32::powf(pixel.r, 1.0 / gamma)) as u16,
-                                (65535.0 * f32::powf(pixel.g, 1.0 / gamma)) as u16,
-                                (65535.0 * f32::powf(pixel.b, 1.0 / gamma)) as u16,
+                                (65535.0 * pixel.r) as u16,
+                                (65535.0 * pixel.g) as u16,
+                                (65535.0 * pixel.b) as u16,
                                 65535_u16,
                             ]),
                         )
                     }
                 }
                 ldr_img
-                    .save_with_format(&path, format)
+                    .save_with_format(path, image_format)
                     .map_err(HdrImageErr::LdrFileWriteFailure)
             },
             ImageFormat::Png => {
-                let mut ldr_img = DynamicImage::new_rgb8(self.width, self.height).into_rgb8();
-                for y in 0..self.height {
-                    for x in 0..self.width {
-                        let pixel = self.get_pixel(x, y).unwrap();
-                        ldr_img.put_pixel(
-                            x,
-                            y,
-                            Rgb([
-                                (255.0 * f32::powf(pixel.r, 1.0 / gamma)) as u8,
-                                (255.0 * f32::powf(pixel.g, 1.0 / gamma)) as u8,
-                                (255.0 * f32::powf(pixel.b, 1.0 / gamma)) as u8,
-                            ]),
-                        )
-                    }
+                let file = File::create(path).map_err(|err| {
+                    HdrImageErr::LdrFileWriteFailure(image::ImageError::IoError(err))
+                })?;
+                let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+                encoder.set_color(png::ColorType::Rgb);
+                match format.transfer {
+                    TransferFunction::Srgb => {
+                        encoder.set_srgb(png::SrgbRenderingIntent::Perceptual)
+                    },
+                    TransferFunction::Gamma(gamma) if gamma != 1.0 => {
+                        encoder.set_source_gamma(png::ScaledFloat::new(1.0 / gamma))
+                    },
+                    // The identity transfer function needs no chunk.
+                    TransferFunction::Gamma(_) => (),
                 }
-                ldr_img
-                    .save_with_format(&path, format)
+                let data = match format.bit_depth {
+                    BitDepth::Eight => {
+                        encoder.set_depth(png::BitDepth::Eight);
+                        self.ldr_pixels_8(format)
+                    },
+                    BitDepth::Sixteen => {
+                        encoder.set_depth(png::BitDepth::Sixteen);
+                        self.ldr_pixels_16(format)
+                            .into_iter()
+                            .flat_map(u16::to_be_bytes)
+                            .collect()
+                    },
+                };
+                let mut writer = encoder
+                    .write_header()
+                    .map_err(HdrImageErr::PngFileWriteFailure)?;
+                writer
+                    .write_image_data(&data)
+                    .map_err(HdrImageErr::PngFileWriteFailure)?;
+                writer.finish().map_err(HdrImageErr::PngFileWriteFailure)
+            },
+            #[cfg(feature = "jpeg")]
+            ImageFormat::Jpeg => {
+                let file = File::create(path).map_err(|err| {
+                    HdrImageErr::LdrFileWriteFailure(image::ImageError::IoError(err))
+                })?;
+                let mut writer = BufWriter::new(file);
+                image::codecs::jpeg::JpegEncoder::new_with_quality(&mut writer, format.quality)
+                    .encode(
+                        &self.ldr_pixels_8(format),
+                        self.width,
+                        self.height,
+                        image::ColorType::Rgb8,
+                    )
                     .map_err(HdrImageErr::LdrFileWriteFailure)
             },
+            #[cfg(feature = "tiff")]
+            ImageFormat::Tiff => match format.bit_depth {
+                BitDepth::Eight => self.save_rgb8(path, image_format, format),
+                BitDepth::Sixteen => image::ImageBuffer::<image::Rgb<u16>, _>::from_raw(
+                    self.width,
+                    self.height,
+                    self.ldr_pixels_16(format),
+                )
+                .unwrap()
+                .save_with_format(path, image_format)
+                .map_err(HdrImageErr::LdrFileWriteFailure),
+            },
+            #[cfg(feature = "bmp")]
+            ImageFormat::Bmp => self.save_rgb8(path, image_format, format),
+            #[cfg(feature = "tga")]
+            ImageFormat::Tga => self.save_rgb8(path, image_format, format),
+            #[cfg(feature = "webp")]
+            ImageFormat::WebP => self.save_rgb8(path, image_format, format),
             _ => Err(HdrImageErr::UnsupportedLdrFileFormat(String::from(
                 path.extension().unwrap().to_str().unwrap_or(""),
             ))),
@@ -334,6 +781,24 @@ impl HdrImage {
     }
 }
 
+/// Return the LDR image formats supported by
+/// [`write_ldr_file`](../hdrimage/struct.HdrImage.html#method.write_ldr_file),
+/// as function of enabled cargo features.
+pub fn ldr_formats() -> Vec<ImageFormat> {
+    [
+        (true, ImageFormat::Farbfeld),
+        (true, ImageFormat::Png),
+        (cfg!(feature = "jpeg"), ImageFormat::Jpeg),
+        (cfg!(feature = "tiff"), ImageFormat::Tiff),
+        (cfg!(feature = "bmp"), ImageFormat::Bmp),
+        (cfg!(feature = "tga"), ImageFormat::Tga),
+        (cfg!(feature = "webp"), ImageFormat::WebP),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, format)| enabled.then_some(format))
+    .collect()
+}
+
 /// Boolean check end of line.
 ///
 /// Return an [`HdrImageErr::InvalidPfmFileFormat`]
@@ -388,6 +853,204 @@ fn parse_endianness(line: &str) -> Result<ByteOrder, HdrImageErr> {
     }
 }
 
+/// Parse rgbe image shape and orientation from string.
+///
+/// Only the standard orientations `-Y height +X width` and `+Y height +X width`
+/// are supported, `+Y` means scanlines are stored from bottom to top.
+///
+/// Return `(width, height, bottom_up)` wrapped around a [`std::result::Result`].\
+/// If parse fails the [`std::result::Result`] will be an [`HdrImageErr`] error variant.
+fn parse_rgbe_shape(line: &str) -> Result<(u32, u32, bool), HdrImageErr> {
+    let shape: Vec<&str> = line.split(' ').filter(|s| s != &"").collect();
+    if shape.len() != 4 || !matches!(shape[0], "-Y" | "+Y") || shape[2] != "+X" {
+        return Err(HdrImageErr::InvalidRgbeFileFormat(String::from(
+            "wrong image shape or orientation inside header",
+        )));
+    }
+    let height = u32::from_str(shape[1])
+        .map_err(|e| HdrImageErr::RgbeIntParseFailure(e, String::from("image height")))?;
+    let width = u32::from_str(shape[3])
+        .map_err(|e| HdrImageErr::RgbeIntParseFailure(e, String::from("image width")))?;
+    Ok((width, height, shape[0] == "+Y"))
+}
+
+/// Read one rgbe scanline from `buf_reader`, filling all `scanline` pixels.
+///
+/// Both scanline encodings are decoded:
+/// * new run length encoding, starting with `2 2` bytes followed by the scanline width,
+///   where the four components are stored one after the other as a sequence of runs
+///   (count byte `> 128` followed by the repeated byte)
+///   or literals (count byte `<= 128` followed by count bytes);
+/// * flat pixels, where the old run length encoding (a `1 1 1` pixel)
+///   repeats the previous pixel.
+///
+/// If scanline is malformed the [`std::result::Result`] will be an [`HdrImageErr`] error variant.
+fn read_rgbe_scanline<R: BufRead>(
+    buf_reader: &mut R,
+    scanline: &mut [[u8; 4]],
+) -> Result<(), HdrImageErr> {
+    let width = scanline.len();
+    let mut rgbe = [0_u8; 4];
+    if width == 0 {
+        return Ok(());
+    }
+    buf_reader
+        .read_exact(&mut rgbe)
+        .map_err(HdrImageErr::RgbeFileReadFailure)?;
+    if (8..0x8000).contains(&width) && rgbe[0] == 2 && rgbe[1] == 2 && rgbe[2] & 0x80 == 0 {
+        if ((rgbe[2] as usize) << 8 | rgbe[3] as usize) != width {
+            return Err(HdrImageErr::InvalidRgbeFileFormat(String::from(
+                "wrong scanline width",
+            )));
+        }
+        for component in 0..4 {
+            let mut x = 0;
+            while x < width {
+                let count = buf_reader
+                    .read_u8()
+                    .map_err(HdrImageErr::RgbeFileReadFailure)?;
+                let (run, count) = if count > 128 {
+                    (true, (count - 128) as usize)
+                } else {
+                    (false, count as usize)
+                };
+                if count == 0 || x + count > width {
+                    return Err(HdrImageErr::InvalidRgbeFileFormat(String::from(
+                        "wrong run length inside scanline",
+                    )));
+                }
+                if run {
+                    let value = buf_reader
+                        .read_u8()
+                        .map_err(HdrImageErr::RgbeFileReadFailure)?;
+                    scanline[x..x + count]
+                        .iter_mut()
+                        .for_each(|pixel| pixel[component] = value);
+                } else {
+                    for pixel in scanline[x..x + count].iter_mut() {
+                        pixel[component] = buf_reader
+                            .read_u8()
+                            .map_err(HdrImageErr::RgbeFileReadFailure)?;
+                    }
+                }
+                x += count;
+            }
+        }
+    } else {
+        let (mut x, mut shift) = (0, 0);
+        loop {
+            if rgbe[0] == 1 && rgbe[1] == 1 && rgbe[2] == 1 {
+                // Consecutive repeats hold higher bytes of the count.
+                if rgbe[3] == 0 || shift >= usize::BITS {
+                    return Err(HdrImageErr::InvalidRgbeFileFormat(String::from(
+                        "wrong run length inside scanline",
+                    )));
+                }
+                let count = (rgbe[3] as usize) << shift;
+                if x == 0 || x + count > width {
+                    return Err(HdrImageErr::InvalidRgbeFileFormat(String::from(
+                        "wrong run length inside scanline",
+                    )));
+                }
+                let previous = scanline[x - 1];
+                scanline[x..x + count].fill(previous);
+                x += count;
+                shift += 8;
+            } else {
+                scanline[x] = rgbe;
+                x += 1;
+                shift = 0;
+            }
+            if x == width {
+                break;
+            }
+            buf_reader
+                .read_exact(&mut rgbe)
+                .map_err(HdrImageErr::RgbeFileReadFailure)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write one rgbe scanline to `stream` with [`std::io::Write`] trait implementation.
+///
+/// Scanlines with a width in `[8, 32767]` are written with the new run length encoding,
+/// where only runs of at least four bytes are encoded as runs.\
+/// Otherwise pixels are written flat.
+fn write_rgbe_scanline<W: Write>(stream: &mut W, scanline: &[[u8; 4]]) -> std::io::Result<()> {
+    const MIN_RUN: usize = 4;
+    let width = scanline.len();
+    if !(8..0x8000).contains(&width) {
+        return scanline.iter().try_for_each(|rgbe| stream.write_all(rgbe));
+    }
+    stream.write_all(&[2, 2, (width >> 8) as u8, width as u8])?;
+    for component in 0..4 {
+        let data: Vec<u8> = scanline.iter().map(|rgbe| rgbe[component]).collect();
+        let mut x = 0;
+        while x < width {
+            // Look for the next run long enough to be worth encoding.
+            let (mut run_start, mut run_count) = (x, 0);
+            while run_count < MIN_RUN && run_start < width {
+                run_start += run_count;
+                run_count = 1;
+                while run_start + run_count < width
+                    && run_count < 127
+                    && data[run_start] == data[run_start + run_count]
+                {
+                    run_count += 1;
+                }
+            }
+            while x < run_start {
+                let count = (run_start - x).min(128);
+                stream.write_all(&[count as u8])?;
+                stream.write_all(&data[x..x + count])?;
+                x += count;
+            }
+            if run_count >= MIN_RUN {
+                stream.write_all(&[128 + run_count as u8, data[run_start]])?;
+                x += run_count;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Convert an rgbe pixel, with shared exponent, to [`Color`].
+fn rgbe_to_color(rgbe: [u8; 4]) -> Color {
+    if rgbe[3] == 0 {
+        return Color::default();
+    }
+    // Mantissas are centered inside their quantization interval.
+    let factor = f32::powi(2.0, rgbe[3] as i32 - (128 + 8));
+    Color::from((
+        (rgbe[0] as f32 + 0.5) * factor,
+        (rgbe[1] as f32 + 0.5) * factor,
+        (rgbe[2] as f32 + 0.5) * factor,
+    ))
+}
+
+/// Convert a [`Color`] to an rgbe pixel, with shared exponent.
+///
+/// Negative components are clamped to zero,
+/// too dim colors are stored as black.
+fn color_to_rgbe(color: Color) -> [u8; 4] {
+    let max = color.r.max(color.g).max(color.b);
+    if max < 1e-32 {
+        return [0; 4];
+    }
+    let mut exponent = max.log2().floor() as i32 + 1;
+    if max * f32::powi(2.0, -exponent) >= 1.0 {
+        exponent += 1;
+    }
+    let scale = f32::powi(2.0, 8 - exponent);
+    [
+        (color.r.max(0.0) * scale) as u8,
+        (color.g.max(0.0) * scale) as u8,
+        (color.b.max(0.0) * scale) as u8,
+        (exponent + 128).clamp(0, 255) as u8,
+    ]
+}
+
 /// Write [`f32`] value to stream. \
 ///
 /// With `stream` implemented [`std::io::Write`] trait and `value` encoded as function of
@@ -405,6 +1068,125 @@ fn write_float<W: Write>(
     }
 }
 
+/// Return the lowercase extension of `path`, empty if missing.
+fn hdr_extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+/// Samples precision of an OpenEXR image.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ExrPrecision {
+    /// 16 bit float samples.
+    Half,
+    /// 32 bit float samples.
+    #[default]
+    Float,
+}
+
+/// Lossless compression of an OpenEXR image.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ExrCompression {
+    Uncompressed,
+    /// Deflate compression of blocks of 16 scan lines.
+    #[default]
+    Zip,
+    /// Wavelet compression, good for noisy images.
+    Piz,
+}
+
+/// Layout of an OpenEXR image written by
+/// [`write_exr_file`](../hdrimage/struct.HdrImage.html#method.write_exr_file).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ExrFormat {
+    /// Samples precision.
+    pub precision: ExrPrecision,
+    /// Compression method.
+    pub compression: ExrCompression,
+    /// If `true` also an opaque `A` channel is written beside `R`, `G` and `B`.
+    pub alpha: bool,
+}
+
+/// Variants of transfer functions (OETF),
+/// used to encode linear colors inside LDR images.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransferFunction {
+    /// Pure power law `x^(1/gamma)`.
+    Gamma(f32),
+    /// Exact piecewise sRGB curve, linear near black.
+    Srgb,
+}
+
+impl Default for TransferFunction {
+    /// Default transfer function is the identity, `Gamma(1.0)`.
+    fn default() -> Self {
+        TransferFunction::Gamma(1.0)
+    }
+}
+
+impl TransferFunction {
+    /// Encode a linear component `x` in `[0, 1]`.
+    fn encode(&self, x: f32) -> f32 {
+        match self {
+            TransferFunction::Gamma(gamma) => f32::powf(x, 1.0 / gamma),
+            TransferFunction::Srgb => {
+                if x <= 0.0031308 {
+                    12.92 * x
+                } else {
+                    1.055 * f32::powf(x, 1.0 / 2.4) - 0.055
+                }
+            },
+        }
+    }
+
+    /// Encode each linear component of a [`Color`].
+    fn encode_color(&self, color: Color) -> Color {
+        Color::from((
+            self.encode(color.r),
+            self.encode(color.g),
+            self.encode(color.b),
+        ))
+    }
+}
+
+/// Bit depth of png and tiff images.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BitDepth {
+    #[default]
+    Eight,
+    Sixteen,
+}
+
+/// Layout of an LDR image written by
+/// [`write_ldr_file`](../hdrimage/struct.HdrImage.html#method.write_ldr_file).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LdrFormat {
+    /// Transfer function applied to linear colors.
+    pub transfer: TransferFunction,
+    /// Bit depth of png and tiff images,
+    /// farbfeld images are always 16 bit and other formats always 8 bit.
+    pub bit_depth: BitDepth,
+    /// Dithering of 8 bit images.
+    pub dither: Dither,
+    /// Quality of jpeg images, in `[1, 100]`.
+    pub quality: u8,
+}
+
+impl Default for LdrFormat {
+    /// Default layout is an 8 bit image with identity transfer function,
+    /// no dithering and jpeg quality `90`.
+    fn default() -> Self {
+        Self {
+            transfer: TransferFunction::default(),
+            bit_depth: BitDepth::default(),
+            dither: Dither::default(),
+            quality: 90,
+        }
+    }
+}
+
 /// Luminosity enum.
 pub enum Luminosity {
     /// Variant for using
@@ -416,16 +1198,11 @@ pub enum Luminosity {
     FloatValue(f32),
 }
 
-/// Adjust the color levels of the brightest pixels in the image.
-fn clamp(x: f32) -> f32 {
-    x / (1.0 + x)
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::{color::WHITE, misc::IsClose};
-    use std::io::Cursor;
+    use std::{io::Cursor, path::PathBuf};
 
     #[test]
     fn test_image_creation() {
@@ -480,7 +1257,19 @@ mod test {
         assert!(hdr_img.get_pixel(2, 2).unwrap().is_close(WHITE));
         assert!(matches!(
             hdr_img.set_pixels(vec![Color::default(); 11]),
-            Err(HdrImageErr::InvalidPixelsSize(a, b)) if a == 11 && b == 9))
+            Err(HdrImageErr::InvalidPixelsSize(a, b)) if a == 11 && b == 9));
+
+        assert!(matches!(hdr_img.average_pixels(vec![color2; 9], 1), Ok(())));
+        assert!(hdr_img.get_pixel(0, 0).unwrap().is_close(color2));
+        assert!(matches!(hdr_img.average_pixels(vec![color1; 9], 2), Ok(())));
+        assert!(matches!(hdr_img.average_pixels(vec![color1; 9], 3), Ok(())));
+        assert!(hdr_img
+            .get_pixel(1, 1)
+            .unwrap()
+            .is_close((color2 + color1 + color1) * (1. / 3.)));
+        assert!(matches!(
+            hdr_img.average_pixels(vec![Color::default(); 4], 4),
+            Err(HdrImageErr::InvalidPixelsSize(a, b)) if a == 4 && b == 9))
     }
 
     #[test]
@@ -775,6 +1564,173 @@ mod test {
         ))
     }
 
+    #[test]
+    fn test_write_exr_file() {
+        let mut hdr_img = HdrImage::new(3, 2);
+        hdr_img
+            .set_pixels(vec![
+                Color::from((1.0e1, 2.0e1, 3.0e1)),
+                Color::from((4.0e1, 5.0e1, 6.0e1)),
+                Color::from((7.0e1, 8.0e1, 9.0e1)),
+                Color::from((1.0e2, 2.0e2, 3.0e2)),
+                Color::from((4.0e2, 5.0e2, 6.0e2)),
+                Color::from((7.0e2, 8.0e2, 9.0e2)),
+            ])
+            .unwrap();
+
+        let reference_file = Path::new("/tmp/reference.exr");
+        for precision in [ExrPrecision::Half, ExrPrecision::Float] {
+            for compression in [
+                ExrCompression::Uncompressed,
+                ExrCompression::Zip,
+                ExrCompression::Piz,
+            ] {
+                for alpha in [false, true] {
+                    let format = ExrFormat {
+                        precision,
+                        compression,
+                        alpha,
+                    };
+                    assert!(matches!(
+                        hdr_img.write_exr_file(reference_file, format),
+                        Ok(())
+                    ));
+                    let hdr_img_result = HdrImage::read_exr_file(reference_file);
+                    assert!(matches!(hdr_img_result, Ok(ref img) if img == &hdr_img));
+                }
+            }
+        }
+        assert!(matches!(
+            hdr_img.write_exr_file(Path::new("/invalid/path.exr"), ExrFormat::default()),
+            Err(HdrImageErr::ExrFileWriteFailure(_))
+        ));
+        assert!(matches!(
+            HdrImage::read_exr_file(Path::new("/tmp/invalid.exr")),
+            Err(HdrImageErr::ExrFileReadFailure(_))
+        ));
+
+        let reference_file = Path::new("/tmp/reference_hdr.EXR");
+        assert!(matches!(
+            hdr_img.write_hdr_file(reference_file, ExrFormat::default()),
+            Ok(())
+        ));
+        assert!(matches!(HdrImage::read_hdr_file(reference_file), Ok(ref img) if img == &hdr_img));
+        let reference_file = Path::new("/tmp/reference_hdr.pfm");
+        assert!(matches!(
+            hdr_img.write_hdr_file(reference_file, ExrFormat::default()),
+            Ok(())
+        ));
+        assert!(matches!(HdrImage::read_hdr_file(reference_file), Ok(ref img) if img == &hdr_img));
+        assert!(matches!(
+            hdr_img.write_hdr_file(Path::new("/tmp/reference_hdr.tga"), ExrFormat::default()),
+            Err(HdrImageErr::UnsupportedHdrFileFormat(format)) if format.as_str() == "tga"
+        ));
+    }
+
+    #[test]
+    fn test_parse_rgbe_shape() {
+        assert!(matches!(parse_rgbe_shape("-Y 2 +X 3"), Ok((3, 2, false))));
+        assert!(matches!(parse_rgbe_shape("+Y 2 +X 3"), Ok((3, 2, true))));
+        assert!(matches!(
+            parse_rgbe_shape("+X 3 -Y 2"),
+            Err(HdrImageErr::InvalidRgbeFileFormat(_))
+        ));
+        assert!(matches!(
+            parse_rgbe_shape("-Y 2 +X"),
+            Err(HdrImageErr::InvalidRgbeFileFormat(_))
+        ));
+        assert!(matches!(
+            parse_rgbe_shape("-Y 2 +X -3"),
+            Err(HdrImageErr::RgbeIntParseFailure(_, msg)) if msg == "image width"
+        ));
+    }
+
+    #[test]
+    fn test_read_rgbe_image() {
+        // Flat scanline with old run length encoding.
+        let mut bytes = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\nEXPOSURE=0.5\n\n-Y 1 +X 3\n".to_vec();
+        bytes.extend([128, 64, 0, 129, 1, 1, 1, 2]);
+        let hdr_img = HdrImage::read_rgbe_image(&mut Cursor::new(bytes)).unwrap();
+        let expected = Color::from((128.5, 64.5, 0.5)) * (2.0 / 128.0);
+        assert_eq!(hdr_img.shape(), (3, 1));
+        for x in 0..3 {
+            assert!(hdr_img.get_pixel(x, 0).unwrap().is_close(expected));
+        }
+
+        // New run length encoding, scanlines stored from bottom to top.
+        let mut bytes = b"#?RGBE\n\n+Y 2 +X 8\n".to_vec();
+        for exponent in [129, 130] {
+            bytes.extend([2, 2, 0, 8]);
+            bytes.extend([136, 128, 136, 64, 4, 1, 2, 3, 4, 132, 0, 136, exponent]);
+        }
+        let hdr_img = HdrImage::read_rgbe_image(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(hdr_img.shape(), (8, 2));
+        for (y, factor) in [(1, 1.0 / 128.0), (0, 1.0 / 64.0)] {
+            for x in 0..8 {
+                let b = if x < 4 { x as f32 + 1.5 } else { 0.5 };
+                assert!(hdr_img
+                    .get_pixel(x, y)
+                    .unwrap()
+                    .is_close(Color::from((128.5, 64.5, b)) * factor));
+            }
+        }
+
+        let mut zero_repeats = b"#?RADIANCE\n\n-Y 1 +X 2\n\x80\x80\x80\x80".to_vec();
+        for _ in 0..10 {
+            zero_repeats.extend([1, 1, 1, 0]);
+        }
+        let invalid_rgbe: [&[u8]; 6] = [
+            b"#?PF\n\n-Y 1 +X 1\n\x80\x80\x80\x80",
+            b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n-Y 1 +X 1\n\x80\x80\x80\x80",
+            b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n",
+            b"#?RADIANCE\n\n-Y 1 -X 1\n\x80\x80\x80\x80",
+            b"#?RADIANCE\n\n-Y 1 +X 8\n\x02\x02\x00\x08\x89\x80",
+            &zero_repeats,
+        ];
+        for bytes in invalid_rgbe {
+            assert!(matches!(
+                HdrImage::read_rgbe_image(&mut Cursor::new(bytes)),
+                Err(HdrImageErr::InvalidRgbeFileFormat(_))
+            ));
+        }
+        let mut truncated = Cursor::new(b"#?RADIANCE\n\n-Y 1 +X 2\n\x80\x80\x80\x80");
+        assert!(matches!(
+            HdrImage::read_rgbe_image(&mut truncated),
+            Err(HdrImageErr::RgbeFileReadFailure(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_rgbe_file() {
+        for width in [3, 40] {
+            let mut hdr_img = HdrImage::new(width, 2);
+            for x in 0..width {
+                hdr_img
+                    .set_pixel(x, 0, Color::from((1.0e2, 2.0e-1, x as f32)))
+                    .unwrap();
+                hdr_img.set_pixel(x, 1, WHITE * ((x / 5) as f32)).unwrap();
+            }
+            let reference_file = Path::new("/tmp/reference.hdr");
+            assert!(matches!(hdr_img.write_rgbe_file(reference_file), Ok(())));
+            let hdr_img_result = HdrImage::read_hdr_file(reference_file).unwrap();
+            assert_eq!(hdr_img_result.shape(), hdr_img.shape());
+            for (read, written) in hdr_img_result.pixels.iter().zip(hdr_img.pixels.iter()) {
+                let max = written.r.max(written.g).max(written.b);
+                for (read, written) in read.into_iter().zip(*written) {
+                    assert!((read - written).abs() <= max / 128.0);
+                }
+            }
+        }
+        assert!(matches!(
+            HdrImage::new(1, 1).write_rgbe_file(Path::new("/invalid/path.hdr")),
+            Err(HdrImageErr::RgbeFileWriteFailure(_))
+        ));
+        assert!(matches!(
+            HdrImage::read_rgbe_file(Path::new("/tmp/invalid.hdr")),
+            Err(HdrImageErr::RgbeFileReadFailure(_))
+        ));
+    }
+
     #[test]
     fn test_average_luminosity() {
         let mut hdr_img = HdrImage::new(2, 1);
@@ -786,7 +1742,8 @@ mod test {
             .set_pixel(1, 0, Color::from((500.0, 1000.0, 1500.0)))
             .unwrap();
 
-        assert!(hdr_img.average_luminosity().is_close(100.0))
+        assert!(hdr_img.average_luminosity().is_close(100.0));
+        assert!(hdr_img.max_luminosity().is_close(1000.0))
     }
 
     #[test]
@@ -822,7 +1779,7 @@ mod test {
             .set_pixel(1, 0, Color::from((500.0, 1000.0, 1500.0)))
             .unwrap();
 
-        hdr_img.clamp_image();
+        hdr_img.clamp_image(ToneMap::default());
         for pixel in hdr_img.pixels.iter() {
             for el in pixel.into_iter() {
                 assert!((0.0..1.0).contains(&el));
@@ -830,6 +1787,38 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_mip_pyramid() {
+        let mut hdr_img = HdrImage::new(5, 2);
+        hdr_img
+            .set_pixels((0..10).map(|i| Color::from((i as f32, 0.0, 1.0))).collect())
+            .unwrap();
+
+        let levels = hdr_img.mip_pyramid();
+        assert_eq!(
+            levels.iter().map(|level| level.shape()).collect::<Vec<_>>(),
+            vec![(5, 2), (3, 1), (2, 1), (1, 1)]
+        );
+        assert_eq!(levels[0], hdr_img);
+        assert!(levels[1]
+            .get_pixel(0, 0)
+            .unwrap()
+            .is_close(Color::from((3.0, 0.0, 1.0))));
+        assert!(levels[1]
+            .get_pixel(2, 0)
+            .unwrap()
+            .is_close(Color::from((6.5, 0.0, 1.0))));
+        assert!(levels[3]
+            .get_pixel(0, 0)
+            .unwrap()
+            .is_close(Color::from((5.25, 0.0, 1.0))));
+
+        for shape in [(0, 4), (3, 0), (1, 1)] {
+            let hdr_img = HdrImage::new(shape.0, shape.1);
+            assert_eq!(hdr_img.mip_pyramid(), vec![hdr_img]);
+        }
+    }
+
     #[test]
     fn test_write_ldr_file() {
         let reference_bytes_le = vec![
@@ -843,27 +1832,177 @@ mod test {
         let mut stream = Cursor::new(reference_bytes_le);
         let mut hdr_img = HdrImage::read_pfm_image(&mut stream).unwrap();
         hdr_img.normalize_image(1000.0, Luminosity::AverageLuminosity);
-        hdr_img.clamp_image();
+        hdr_img.clamp_image(ToneMap::default());
 
         let invalid_format = Path::new("/tmp/reference_le.mkv");
-        let unsupported_format = Path::new("/tmp/reference_le.jpeg");
+        let unsupported_format = Path::new("/tmp/reference_le.gif");
         let invalid_path = Path::new("/invalid_path/reference_le.png");
         let reference_png = Path::new("/tmp/reference_le.png");
         let reference_ff = Path::new("/tmp/reference_le.ff");
 
         assert!(matches!(
-            hdr_img.write_ldr_file(invalid_format, 1.0),
+            hdr_img.write_ldr_file(invalid_format, LdrFormat::default()),
             Err(HdrImageErr::LdrFileWriteFailure(_))
         ));
         assert!(matches!(
-            hdr_img.write_ldr_file(unsupported_format, 1.0),
-            Err(HdrImageErr::UnsupportedLdrFileFormat(format)) if format.as_str() == "jpeg"
+            hdr_img.write_ldr_file(unsupported_format, LdrFormat::default()),
+            Err(HdrImageErr::UnsupportedLdrFileFormat(format)) if format.as_str() == "gif"
         ));
         assert!(matches!(
-            hdr_img.write_ldr_file(invalid_path, 1.0),
+            hdr_img.write_ldr_file(invalid_path, LdrFormat::default()),
             Err(HdrImageErr::LdrFileWriteFailure(_))
         ));
-        assert!(matches!(hdr_img.write_ldr_file(reference_png, 1.0), Ok(())));
-        assert!(matches!(hdr_img.write_ldr_file(reference_ff, 1.0), Ok(())))
+        assert!(matches!(
+            hdr_img.write_ldr_file(reference_png, LdrFormat::default()),
+            Ok(())
+        ));
+        assert!(matches!(
+            hdr_img.write_ldr_file(reference_ff, LdrFormat::default()),
+            Ok(())
+        ))
+    }
+
+    #[test]
+    fn test_write_ldr_format() {
+        assert!(TransferFunction::Srgb.encode(0.0).is_close(0.0));
+        assert!(TransferFunction::Srgb.encode(0.002).is_close(0.02584));
+        assert!(TransferFunction::Srgb.encode(0.5).is_close(0.735_356_7));
+        assert!(TransferFunction::Srgb.encode(1.0).is_close(1.0));
+        assert!(TransferFunction::Gamma(2.0).encode(0.25).is_close(0.5));
+
+        let mut hdr_img = HdrImage::new(16, 16);
+        hdr_img
+            .set_pixels(vec![Color::from((0.5, 0.25, 0.0)); 256])
+            .unwrap();
+        let reference_png = Path::new("/tmp/reference_format.png");
+        let read_png = |path: &Path| {
+            let decoder = png::Decoder::new(File::open(path).unwrap());
+            let mut reader = decoder.read_info().unwrap();
+            let mut buf = vec![0; reader.output_buffer_size()];
+            reader.next_frame(&mut buf).unwrap();
+            let info = reader.info();
+            (info.bit_depth, info.srgb, info.source_gamma, buf)
+        };
+
+        hdr_img
+            .write_ldr_file(
+                reference_png,
+                LdrFormat {
+                    transfer: TransferFunction::Gamma(2.0),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let (bit_depth, srgb, gamma, buf) = read_png(reference_png);
+        assert_eq!(bit_depth, png::BitDepth::Eight);
+        assert!(srgb.is_none());
+        assert_eq!(gamma, Some(png::ScaledFloat::new(0.5)));
+        assert_eq!(buf[..3], [180, 127, 0]);
+
+        hdr_img
+            .write_ldr_file(
+                reference_png,
+                LdrFormat {
+                    transfer: TransferFunction::Srgb,
+                    bit_depth: BitDepth::Sixteen,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let (bit_depth, srgb, _, buf) = read_png(reference_png);
+        assert_eq!(bit_depth, png::BitDepth::Sixteen);
+        assert_eq!(srgb, Some(png::SrgbRenderingIntent::Perceptual));
+        assert_eq!(u16::from_be_bytes([buf[0], buf[1]]), 48191);
+
+        hdr_img
+            .write_ldr_file(reference_png, LdrFormat::default())
+            .unwrap();
+        let (_, srgb, gamma, buf) = read_png(reference_png);
+        assert!(srgb.is_none() && gamma.is_none());
+        assert_eq!(buf[..3], [127, 63, 0]);
+
+        // Dithering preserves on average the quantized value.
+        for dither in [Dither::Ordered, Dither::BlueNoise] {
+            hdr_img
+                .write_ldr_file(
+                    reference_png,
+                    LdrFormat {
+                        dither,
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            let (_, _, _, buf) = read_png(reference_png);
+            let red: Vec<u8> = buf.iter().step_by(3).copied().collect();
+            assert!(red.iter().all(|r| *r == 127 || *r == 128));
+            let mean = red.iter().map(|r| *r as f32).sum::<f32>() / red.len() as f32;
+            assert!((mean - 127.5).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn test_write_ldr_formats() {
+        let mut hdr_img = HdrImage::new(16, 8);
+        hdr_img
+            .set_pixels(vec![Color::from((0.5, 0.25, 0.0)); 128])
+            .unwrap();
+        for (extension, bit_depth, color_type) in [
+            ("jpg", BitDepth::Eight, image::ColorType::Rgb8),
+            ("tiff", BitDepth::Eight, image::ColorType::Rgb8),
+            ("tiff", BitDepth::Sixteen, image::ColorType::Rgb16),
+            ("bmp", BitDepth::Eight, image::ColorType::Rgb8),
+            ("tga", BitDepth::Eight, image::ColorType::Rgb8),
+            // Lossless WebP is always decoded with an alpha channel.
+            ("webp", BitDepth::Eight, image::ColorType::Rgba8),
+        ] {
+            let reference_file = PathBuf::from(format!("/tmp/reference_formats.{extension}"));
+            let result = hdr_img.write_ldr_file(
+                &reference_file,
+                LdrFormat {
+                    bit_depth,
+                    ..Default::default()
+                },
+            );
+            if !ldr_formats().contains(&ImageFormat::from_path(&reference_file).unwrap()) {
+                assert!(matches!(
+                    result,
+                    Err(HdrImageErr::UnsupportedLdrFileFormat(format)) if format == extension
+                ));
+                continue;
+            }
+            assert!(matches!(result, Ok(())));
+            let ldr_img = image::open(&reference_file).unwrap();
+            assert_eq!((ldr_img.width(), ldr_img.height()), (16, 8));
+            assert_eq!(ldr_img.color(), color_type);
+            let pixel = ldr_img.to_rgb8().get_pixel(3, 5).0;
+            assert!(pixel[0].abs_diff(127) <= 2 && pixel[1].abs_diff(63) <= 2);
+        }
+    }
+
+    #[test]
+    fn test_read_ldr_file() {
+        let mut hdr_img = HdrImage::new(4, 2);
+        hdr_img
+            .set_pixels(vec![Color::from((1.0, 0.5, 0.0)); 8])
+            .unwrap();
+        hdr_img
+            .set_pixel(3, 1, Color::from((0.0, 0.25, 1.0)))
+            .unwrap();
+        let reference_file = Path::new("/tmp/reference_read.png");
+        hdr_img
+            .write_ldr_file(reference_file, LdrFormat::default())
+            .unwrap();
+
+        let ldr_img = HdrImage::read_ldr_file(reference_file).unwrap();
+        assert_eq!(ldr_img.shape(), (4, 2));
+        for (read, written) in ldr_img.pixels.into_iter().zip(hdr_img.pixels) {
+            for (r, w) in read.into_iter().zip(written) {
+                assert!((r - w).abs() <= 1.0 / 255.0);
+            }
+        }
+        assert!(matches!(
+            HdrImage::read_ldr_file(Path::new("/tmp/reference_read.gif")),
+            Err(HdrImageErr::UnsupportedLdrFileFormat(format)) if format == "gif"
+        ));
     }
 }
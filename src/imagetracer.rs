@@ -19,19 +19,6 @@ pub struct ImageTracer<'a> {
     camera: Camera,
 }
 
-/// Appo struct for [`all_rays`](../imagetracer/struct.ImageTracer.html#method.all_rays) that will
-/// store for each pixel in [`image`](../imagetracer/struct.ImageTracer.html#fields):
-///   * a [`Vec`] of [`Ray`]s, the length of this vector\ will be greater than one when
-///     `antialiasing_level!=1`;
-///   * a [`u64`] random integer generated from [`Pcg`], that will be used\ to init an independent
-///     [`Pcg`] when rendering equation solution (avoiding artefacts).
-struct Rays {
-    /// [`Pcg`] `init_seq` for each pixel.
-    seq: u64,
-    /// Rays for each pixel.
-    rays: Vec<Ray>,
-}
-
 impl<'a> ImageTracer<'a> {
     /// Initialize an ImageTracer object.
     ///
@@ -49,38 +36,17 @@ impl<'a> ImageTracer<'a> {
     /// The values of `u_pixel` and `v_pixel` are floating-point numbers in the range `[0, 1]`.\
     /// They specify where the ray should cross the pixel; passing `0.5` to both means that\
     /// the ray will pass through the pixel's center.
-    fn fire_ray(&self, col: u32, row: u32, u_pixel: f32, v_pixel: f32) -> Ray {
-        let u = (col as f32 + u_pixel) / self.image.shape().0 as f32;
-        let v = 1. - (row as f32 + v_pixel) / self.image.shape().1 as f32;
-        self.camera.fire_ray(u, v)
-    }
-
-    /// Generate a [`Vec`] of [`Rays`].
     ///
-    /// Appo method for parallelized [`fire_all_rays`](#method.fire_all_rays).
-    fn all_rays(&self, init_state: u64, init_seq: u64, antialiasing_level: u32) -> Vec<Rays> {
-        let mut all_rays = Vec::new();
-        let mut pcg = Pcg::new(init_state, init_seq);
-        for row in 0..self.image.shape().1 {
-            for col in 0..self.image.shape().0 {
-                let mut rays = Vec::new();
-                for sub_row in 0..antialiasing_level {
-                    for sub_col in 0..antialiasing_level {
-                        rays.push(self.fire_ray(
-                            col,
-                            row,
-                            (sub_row as f32 + pcg.random_float()) / (antialiasing_level as f32),
-                            (sub_col as f32 + pcg.random_float()) / (antialiasing_level as f32),
-                        ));
-                    }
-                }
-                all_rays.push(Rays {
-                    rays,
-                    seq: pcg.random() as u64,
-                })
-            }
-        }
-        all_rays
+    /// The [`Pcg`] is forwarded to the camera, that may need it (e.g. a thin lens).
+    ///
+    /// The ray carries its [`RayDifferentials`](../ray/struct.RayDifferentials.html)
+    /// towards the next column and the next row.
+    fn fire_ray(&self, col: u32, row: u32, u_pixel: f32, v_pixel: f32, pcg: &mut Pcg) -> Ray {
+        let (width, height) = self.image.shape();
+        let u = (col as f32 + u_pixel) / width as f32;
+        let v = 1. - (row as f32 + v_pixel) / height as f32;
+        self.camera
+            .fire_ray_differential(u, v, 1. / width as f32, -1. / height as f32, pcg)
     }
 
     /// Shoot several light rays crossing each of the pixels in the image.
@@ -93,15 +59,17 @@ impl<'a> ImageTracer<'a> {
     /// where N is the anti-aliasing level, and a [`Ray`] is thrown for each sub-pixel;\
     /// the color of the pixel in this case is obtained as the mean color of the N*N samples.
     ///
-    /// This function is **parallelized** for each pixel that compose
+    /// This function is **parallelized** for each row that compose
     /// [`image`](#fields) pixels matrix,\
-    /// thanks to high-level API [`rayon::iter::IntoParallelRefIterator::par_iter`].\
+    /// thanks to high-level API [`rayon::iter::IntoParallelIterator::into_par_iter`].\
     /// So for each available thread an independent
-    /// pixel rendering equation resolution is computed,\
-    /// using particular [`Renderer`] that implement [`Solve`] trait.
+    /// row rendering equation resolution is computed,\
+    /// using particular [`Renderer`] that implement [`Solve`] trait.\
+    /// Rays are fired lazily, pixel by pixel, so memory usage does not grow
+    /// with the anti-aliasing level.
     ///
-    /// **Note:** to avoid artefacts each [`Pcg`] used by each thread is created from
-    /// a different sequence, thanks to [`all_rays`](#method.all_rays) method.
+    /// **Note:** to avoid artefacts each [`Pcg`] used by each pixel is created from
+    /// a different sequence, see [`render_row`](#method.render_row) method.
     pub fn fire_all_rays(
         &mut self,
         renderer: &Renderer,
@@ -109,19 +77,106 @@ impl<'a> ImageTracer<'a> {
         init_seq: u64,
         antialiasing_level: u32,
     ) {
-        let pixels: Vec<Color> = self
-            .all_rays(init_state, init_seq, antialiasing_level)
-            .par_iter()
-            .map(|ray| {
+        let pixels = self.render_pixels(renderer, init_state, init_seq, antialiasing_level);
+        self.image.set_pixels(pixels).unwrap_or(())
+    }
+
+    /// Shoot a pass of light rays, as [`fire_all_rays`](#method.fire_all_rays) does,
+    /// and blend it into the image as a running average.
+    ///
+    /// `pass` is the zero-based index of the pass, the image is expected to hold
+    /// the average of the previous passes.\
+    /// Each pass uses its own random sequence (`init_seq + pass`), so that a progressive render
+    /// can be stopped and resumed, always giving the same result.\
+    /// The first pass is the same as calling [`fire_all_rays`](#method.fire_all_rays).
+    pub fn fire_pass(
+        &mut self,
+        renderer: &Renderer,
+        init_state: u64,
+        init_seq: u64,
+        antialiasing_level: u32,
+        pass: u32,
+    ) {
+        let pixels = self.render_pixels(
+            renderer,
+            init_state,
+            init_seq.wrapping_add(pass as u64),
+            antialiasing_level,
+        );
+        self.image.average_pixels(pixels, pass + 1).unwrap_or(())
+    }
+
+    /// Return the traced image.
+    pub fn image(&self) -> &HdrImage {
+        self.image
+    }
+
+    /// Compute the [`Color`] of each pixel.
+    ///
+    /// Appo method for [`fire_all_rays`](#method.fire_all_rays)
+    /// and [`fire_pass`](#method.fire_pass), rows are rendered in parallel
+    /// by [`render_row`](#method.render_row).
+    fn render_pixels(
+        &self,
+        renderer: &Renderer,
+        init_state: u64,
+        init_seq: u64,
+        antialiasing_level: u32,
+    ) -> Vec<Color> {
+        (0..self.image.shape().1)
+            .into_par_iter()
+            .map(|row| self.render_row(renderer, init_state, init_seq, antialiasing_level, row))
+            .flatten_iter()
+            .collect()
+    }
+
+    /// Compute the [`Color`] of each pixel of a `row`, firing its rays lazily.
+    ///
+    /// A single [`Pcg`] stream, seeded by `init_state` and `init_seq`, is virtually
+    /// walked through the whole image, row by row:\
+    /// for each pixel it gives the sub-pixel positions of its rays and
+    /// a [`u64`] random integer,\
+    /// used to init an independent [`Pcg`] when firing rays and solving rendering equation
+    /// (avoiding artefacts).\
+    /// The stream is jumped to the first pixel of the row, so the result is deterministic
+    /// and independent of the order (and the number of threads) rows are rendered.
+    fn render_row(
+        &self,
+        renderer: &Renderer,
+        init_state: u64,
+        init_seq: u64,
+        antialiasing_level: u32,
+        row: u32,
+    ) -> Vec<Color> {
+        let width = self.image.shape().0;
+        let samples = antialiasing_level.pow(2);
+        // Each pixel draws two floats per sample and the independent sequence.
+        let steps_per_pixel = 2 * samples as u64 + 1;
+        let mut pcg = Pcg::new(init_state, init_seq);
+        pcg.advance(row as u64 * width as u64 * steps_per_pixel);
+        let mut uvs = Vec::with_capacity(samples as usize);
+        (0..width)
+            .map(|col| {
+                uvs.clear();
+                for sub_row in 0..antialiasing_level {
+                    for sub_col in 0..antialiasing_level {
+                        uvs.push((
+                            (sub_row as f32 + pcg.random_float()) / (antialiasing_level as f32),
+                            (sub_col as f32 + pcg.random_float()) / (antialiasing_level as f32),
+                        ));
+                    }
+                }
+                let mut pixel_pcg = Pcg::new(init_state, pcg.random() as u64);
                 let mut color = Color::default();
-                let mut pcg = Pcg::new(init_state, ray.seq);
-                for ray in ray.rays.iter() {
-                    color = color + renderer.solve(*ray, &mut pcg);
+                for (u_pixel, v_pixel) in uvs.iter() {
+                    let mut ray = self.fire_ray(col, row, *u_pixel, *v_pixel, &mut pixel_pcg);
+                    // Each sample covers a sub-pixel of the anti-aliasing grid.
+                    ray.scale_differentials(1. / antialiasing_level as f32);
+                    color = color + renderer.solve(ray, &mut pixel_pcg);
                 }
-                color * (1. / antialiasing_level.pow(2) as f32)
+                color * (1. / samples as f32)
             })
-            .collect();
-        self.image.set_pixels(pixels).unwrap_or(())
+            .collect()
     }
 }
 
@@ -129,8 +184,16 @@ impl<'a> ImageTracer<'a> {
 mod test {
     use super::*;
     use crate::{
-        camera::PerspectiveCamera, color::Color, misc::IsClose, point::Point,
-        render::DummyRenderer, transformation::Transformation,
+        camera::PerspectiveCamera,
+        color::{Color, BLACK},
+        material::{Material, Pigment, UniformPigment},
+        misc::IsClose,
+        point::Point,
+        render::{DummyRenderer, PathTracer},
+        shape::Sphere,
+        transformation::{translation, Transformation},
+        vector::Vector,
+        world::World,
     };
 
     #[test]
@@ -140,8 +203,9 @@ mod test {
             Camera::Perspective(PerspectiveCamera::new(1.0, 2.0, Transformation::default()));
         let tracer = ImageTracer::new(&mut image, camera);
 
-        let ray1 = tracer.fire_ray(0, 0, 2.5, 1.5);
-        let ray2 = tracer.fire_ray(2, 1, 0.5, 0.5);
+        let mut pcg = Pcg::default();
+        let ray1 = tracer.fire_ray(0, 0, 2.5, 1.5, &mut pcg);
+        let ray2 = tracer.fire_ray(2, 1, 0.5, 0.5, &mut pcg);
         assert!(ray1.is_close(ray2));
     }
 
@@ -172,13 +236,63 @@ mod test {
             camera,
         };
 
-        let top_left_ray = tracer.fire_ray(0, 0, 0., 0.);
+        let mut pcg = Pcg::default();
+        let top_left_ray = tracer.fire_ray(0, 0, 0., 0., &mut pcg);
         println!("{}", top_left_ray.at(1.));
         assert!(top_left_ray.at(1.).is_close(Point::from((0., 2., 1.))));
 
-        let bottom_right_ray = tracer.fire_ray(3, 1, 1., 1.);
+        let bottom_right_ray = tracer.fire_ray(3, 1, 1., 1., &mut pcg);
         assert!(bottom_right_ray
             .at(1.)
             .is_close(Point::from((0., -2., -1.))));
     }
+
+    #[test]
+    fn test_fire_pass() {
+        let mut world = World::default();
+        world.add(Box::new(Sphere::new(
+            Transformation::default(),
+            Material {
+                emitted_radiance: Pigment::Uniform(UniformPigment {
+                    color: Color::from((0.5, 0.5, 0.5)),
+                }),
+                ..Default::default()
+            },
+        )));
+        world.add(Box::new(Sphere::new(
+            translation(Vector::from((0., 0., -2.))),
+            Material::default(),
+        )));
+        let renderer = Renderer::PathTracer(PathTracer::new(&world, BLACK, 2, 3, 1, false));
+        let camera = || {
+            Camera::Perspective(PerspectiveCamera::new(
+                1.0,
+                2.0,
+                translation(Vector::from((-2., 0., 0.))),
+            ))
+        };
+
+        // The first pass is a plain render.
+        let mut image = HdrImage::new(4, 2);
+        ImageTracer::new(&mut image, camera()).fire_all_rays(&renderer, 42, 54, 2);
+        let mut first_pass = HdrImage::new(4, 2);
+        ImageTracer::new(&mut first_pass, camera()).fire_pass(&renderer, 42, 54, 2, 0);
+        assert_eq!(image, first_pass);
+
+        // An interrupted render resumed from the second pass is the same as an uninterrupted one.
+        let mut uninterrupted = HdrImage::new(4, 2);
+        let mut tracer = ImageTracer::new(&mut uninterrupted, camera());
+        for pass in 0..3 {
+            tracer.fire_pass(&renderer, 42, 54, 2, pass);
+        }
+        let mut checkpoint = HdrImage::new(4, 2);
+        let mut tracer = ImageTracer::new(&mut checkpoint, camera());
+        for pass in 0..2 {
+            tracer.fire_pass(&renderer, 42, 54, 2, pass);
+        }
+        let mut resumed = tracer.image().clone();
+        ImageTracer::new(&mut resumed, camera()).fire_pass(&renderer, 42, 54, 2, 2);
+        assert_eq!(resumed, uninterrupted);
+        assert_ne!(resumed, checkpoint);
+    }
 }
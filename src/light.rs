@@ -0,0 +1,678 @@
+//! Light Sources module.
+//!
+//! Provides [`PointLight`](struct@PointLight) struct,
+//! [`AreaLight`](enum@AreaLight) enum and [`Environment`](enum@Environment) enum.
+use crate::{
+    color::{Color, BLACK},
+    hdrimage::HdrImage,
+    normal::{create_onb_from_z, Normal},
+    point::Point,
+    sky::Sky,
+    transformation::Transformation,
+    vector::Vector,
+};
+use std::f32::consts::PI;
+
+/// A point light source.
+///
+/// Used by [`PointLightRenderer`](../render/struct.PointLightRenderer.html).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PointLight {
+    /// Position of the light source in world coordinates.
+    pub position: Point,
+    /// [`Color`] of the light source.
+    pub color: Color,
+    /// Radius of the source, used to compute the solid angle subtended by the light.\
+    /// If zero, the light intensity does not decrease with distance.
+    pub linear_radius: f32,
+}
+
+impl PointLight {
+    /// Provides a constructor for [`PointLight`](struct@PointLight).
+    pub fn new(position: Point, color: Color, linear_radius: f32) -> Self {
+        Self {
+            position,
+            color,
+            linear_radius,
+        }
+    }
+}
+
+/// A trait for sampling directions towards an area light source.
+///
+/// Used by [`PathTracer`](../render/struct.PathTracer.html) next-event estimation.
+pub trait SampleLight {
+    /// Sample a direction from `origin` towards the light using two [`f32`]
+    /// generated by [`Pcg`](../random/struct.Pcg.html) random generator.
+    ///
+    /// Return the normalized direction and its probability density (per unit solid angle).
+    fn sample(&self, origin: Point, pcg: (f32, f32)) -> (Vector, f32);
+    /// Return the probability density (per unit solid angle) of sampling `dir`
+    /// from `origin` with [`sample`](#tymethod.sample), zero if `dir` misses the light.
+    fn pdf(&self, origin: Point, dir: Vector) -> f32;
+}
+
+/// The geometry of an emissive [`Sphere`](../shape/struct.Sphere.html).
+///
+/// Only spheres with a uniform scaling can be sampled,
+/// ellipsoids are not supported.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SphereLight {
+    /// Center of the sphere in world coordinates.
+    pub center: Point,
+    /// Radius of the sphere in world coordinates.
+    pub radius: f32,
+}
+
+impl SphereLight {
+    /// Provides a constructor for [`SphereLight`](struct@SphereLight).
+    pub fn new(center: Point, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    /// Return the cosine of the half-aperture of the cone subtended
+    /// by the sphere as seen from `origin`, together with `1 - cos`.\
+    /// [`None`] if `origin` lies inside the sphere.
+    fn cone(&self, origin: Point) -> Option<(f32, f32)> {
+        let sin_theta_max_sq = self.radius * self.radius / (self.center - origin).squared_norm();
+        if sin_theta_max_sq >= 1.0 {
+            return None;
+        }
+        let cos_theta_max = (1.0 - sin_theta_max_sq).sqrt();
+        // Avoid cancellation when the sphere is small or far away.
+        Some((cos_theta_max, sin_theta_max_sq / (1.0 + cos_theta_max)))
+    }
+
+    /// Return the solid angle probability density of a point `p`
+    /// uniformly sampled on the sphere surface, as seen from `origin`.
+    fn area_pdf(&self, origin: Point, p: Point) -> f32 {
+        let to_p = p - origin;
+        let distance_sq = to_p.squared_norm();
+        let normal = (p - self.center) * (1.0 / self.radius);
+        let cos_light = normal.dot(to_p).abs() / distance_sq.sqrt();
+        distance_sq / (cos_light * 4.0 * PI * self.radius * self.radius)
+    }
+}
+
+impl SampleLight for SphereLight {
+    /// Sample uniformly the cone subtended by the sphere if `origin` lies outside,\
+    /// otherwise sample uniformly the sphere surface.
+    fn sample(&self, origin: Point, pcg: (f32, f32)) -> (Vector, f32) {
+        match self.cone(origin) {
+            Some((_, one_minus_cos_theta_max)) => (
+                sample_cone(
+                    (self.center - origin).normalize(),
+                    one_minus_cos_theta_max,
+                    pcg,
+                ),
+                1.0 / (2.0 * PI * one_minus_cos_theta_max),
+            ),
+            None => {
+                let phi = 2.0 * PI * pcg.1;
+                let z = 1.0 - 2.0 * pcg.0;
+                let r = (1.0 - z * z).max(0.0).sqrt();
+                let p = self.center
+                    + Vector::from((r * f32::cos(phi), r * f32::sin(phi), z)) * self.radius;
+                ((p - origin).normalize(), self.area_pdf(origin, p))
+            },
+        }
+    }
+
+    fn pdf(&self, origin: Point, dir: Vector) -> f32 {
+        let dir = dir.normalize();
+        let to_center = self.center - origin;
+        match self.cone(origin) {
+            Some((cos_theta_max, one_minus_cos_theta_max)) => {
+                if dir.dot(to_center.normalize()) >= cos_theta_max {
+                    1.0 / (2.0 * PI * one_minus_cos_theta_max)
+                } else {
+                    0.0
+                }
+            },
+            None => {
+                // From inside, the ray always exits the sphere at the farthest root.
+                let b = dir.dot(to_center);
+                let c = to_center.squared_norm() - self.radius * self.radius;
+                let t = b + (b * b - c).max(0.0).sqrt();
+                self.area_pdf(origin, origin + dir * t)
+            },
+        }
+    }
+}
+
+/// Sample uniformly a direction inside the cone around the normalized `axis`,
+/// with half-aperture of cosine `1 - one_minus_cos_theta_max`.
+fn sample_cone(axis: Vector, one_minus_cos_theta_max: f32, pcg: (f32, f32)) -> Vector {
+    let phi = 2.0 * PI * pcg.1;
+    let (e1, e2, e3) = create_onb_from_z(Normal::from((axis.x, axis.y, axis.z)));
+    let cos_theta = 1.0 - pcg.0 * one_minus_cos_theta_max;
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    e1 * (sin_theta * f32::cos(phi)) + e2 * (sin_theta * f32::sin(phi)) + e3 * cos_theta
+}
+
+/// Enum of area light sources,
+/// the geometry of emissive shapes that can be explicitly sampled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AreaLight {
+    Sphere(SphereLight),
+}
+
+impl SampleLight for AreaLight {
+    /// Sample a direction towards a particular [`AreaLight`] variant.
+    fn sample(&self, origin: Point, pcg: (f32, f32)) -> (Vector, f32) {
+        match self {
+            AreaLight::Sphere(sphere) => sphere.sample(origin, pcg),
+        }
+    }
+
+    /// Return the probability density of a particular [`AreaLight`] variant.
+    fn pdf(&self, origin: Point, dir: Vector) -> f32 {
+        match self {
+            AreaLight::Sphere(sphere) => sphere.pdf(origin, dir),
+        }
+    }
+}
+
+/// An environment light source, infinitely far away and surrounding the whole scene.
+///
+/// Its radiance is read from an equirectangular (latitude-longitude) [`HdrImage`],
+/// with the same parametrization of a [`Sphere`](../shape/struct.Sphere.html) surface:\
+/// the first row of the image is the direction `+z` of the environment
+/// and the last row is `-z`.
+///
+/// Directions are sampled proportionally to the luminosity of the texels,
+/// through a marginal cumulative distribution over the rows and a conditional
+/// one over the columns of each row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnvironmentLight {
+    /// Number of columns of the map.
+    width: usize,
+    /// Number of rows of the map.
+    height: usize,
+    /// Radiance of the texels (already scaled by the intensity), row by row.
+    texels: Vec<Color>,
+    /// Transformation from environment to world coordinates, usually a rotation.
+    transformation: Transformation,
+    /// Marginal (unnormalized) cumulative distribution over the rows, `height + 1` values.
+    row_cdf: Vec<f32>,
+    /// Conditional (unnormalized) cumulative distributions over the columns,
+    /// `width + 1` values for each row.
+    col_cdf: Vec<f32>,
+}
+
+impl EnvironmentLight {
+    /// Provides a constructor for [`EnvironmentLight`](struct@EnvironmentLight).
+    ///
+    /// The radiance of `image` is scaled by `intensity`
+    /// and oriented in the world by `transformation`.
+    pub fn new(image: HdrImage, intensity: f32, transformation: Transformation) -> Self {
+        let (width, height) = image.shape();
+        let texels: Vec<Color> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| image.get_pixel(x, y).unwrap() * intensity)
+            .collect();
+        let (width, height) = (width as usize, height as usize);
+        let mut row_cdf = vec![0.0; height + 1];
+        let mut col_cdf = vec![0.0; (width + 1) * height];
+        for y in 0..height {
+            // Texels near the poles subtend a smaller solid angle.
+            let (cos_theta0, cos_theta1) = row_cos_theta(y, height);
+            let solid_angle = 2.0 * PI * (cos_theta0 - cos_theta1) / width as f32;
+            let cdf = &mut col_cdf[y * (width + 1)..(y + 1) * (width + 1)];
+            for x in 0..width {
+                cdf[x + 1] = cdf[x] + texels[y * width + x].luminosity() * solid_angle;
+            }
+            row_cdf[y + 1] = row_cdf[y] + cdf[width];
+        }
+        Self {
+            width,
+            height,
+            texels,
+            transformation,
+            row_cdf,
+            col_cdf,
+        }
+    }
+
+    /// Return the radiance coming from direction `dir` (in world coordinates).
+    pub fn radiance(&self, dir: Vector) -> Color {
+        self.texels[self.texel_index(dir)]
+    }
+
+    /// Return the index of the texel seen in direction `dir` (in world coordinates).
+    fn texel_index(&self, dir: Vector) -> usize {
+        let dir = (self.transformation.inverse() * dir).normalize();
+        let mut u = dir.y.atan2(dir.x) / (2.0 * PI);
+        if u < 0.0 {
+            u += 1.0
+        };
+        let v = dir.z.clamp(-1.0, 1.0).acos() / PI;
+        let x = ((u * self.width as f32) as usize).min(self.width - 1);
+        let y = ((v * self.height as f32) as usize).min(self.height - 1);
+        y * self.width + x
+    }
+
+    /// Return the probability density (per unit solid angle) of the `index`-th texel.
+    ///
+    /// Within a texel directions are sampled uniformly in solid angle,
+    /// so the density is proportional to the texel luminosity.
+    fn texel_pdf(&self, index: usize) -> f32 {
+        let total = self.row_cdf[self.height];
+        if total > 0.0 {
+            self.texels[index].luminosity() / total
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Return the cosines of the polar angles bounding the `y`-th row
+/// of an equirectangular map with `height` rows.
+fn row_cos_theta(y: usize, height: usize) -> (f32, f32) {
+    (
+        f32::cos(PI * y as f32 / height as f32),
+        f32::cos(PI * (y + 1) as f32 / height as f32),
+    )
+}
+
+/// Sample a bin of the (unnormalized) cumulative distribution `cdf` using `random` in `[0, 1)`.
+///
+/// Return the bin index together with the position of the sample
+/// inside the bin, remapped in `[0, 1)`.
+fn sample_cdf(cdf: &[f32], random: f32) -> (usize, f32) {
+    let bins = cdf.len() - 1;
+    let target = random * cdf[bins];
+    let index = cdf
+        .partition_point(|&value| value <= target)
+        .saturating_sub(1)
+        .min(bins - 1);
+    let width = cdf[index + 1] - cdf[index];
+    if width > 0.0 {
+        (
+            index,
+            ((target - cdf[index]) / width).clamp(0.0, 1.0 - f32::EPSILON),
+        )
+    } else {
+        (index, 0.5)
+    }
+}
+
+impl SampleLight for EnvironmentLight {
+    /// Sample a texel proportionally to its luminosity times its solid angle,\
+    /// then a direction uniformly in solid angle inside the texel.
+    fn sample(&self, _origin: Point, pcg: (f32, f32)) -> (Vector, f32) {
+        if self.row_cdf[self.height] <= 0.0 {
+            return (Vector::from((0.0, 0.0, 1.0)), 0.0);
+        }
+        let (y, row_offset) = sample_cdf(&self.row_cdf, pcg.0);
+        let (x, col_offset) = sample_cdf(
+            &self.col_cdf[y * (self.width + 1)..(y + 1) * (self.width + 1)],
+            pcg.1,
+        );
+        let (cos_theta0, cos_theta1) = row_cos_theta(y, self.height);
+        let cos_theta = cos_theta0 + row_offset * (cos_theta1 - cos_theta0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * (x as f32 + col_offset) / self.width as f32;
+        let dir = Vector::from((
+            sin_theta * f32::cos(phi),
+            sin_theta * f32::sin(phi),
+            cos_theta,
+        ));
+        (
+            (self.transformation * dir).normalize(),
+            self.texel_pdf(y * self.width + x),
+        )
+    }
+
+    fn pdf(&self, _origin: Point, dir: Vector) -> f32 {
+        self.texel_pdf(self.texel_index(dir))
+    }
+}
+
+/// A directional light source, the disk of the sun.
+///
+/// Its radiance is uniform inside a cone around its direction,
+/// the apparent angular diameter of the sun.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SunLight {
+    /// Normalized direction towards the sun.
+    pub direction: Vector,
+    /// One minus the cosine of the angular radius of the sun.
+    one_minus_cos_theta_max: f32,
+    /// Radiance of the sun disk.
+    radiance: Color,
+}
+
+impl SunLight {
+    /// Provides a constructor for [`SunLight`](struct@SunLight).
+    ///
+    /// The radiance is derived from the `illuminance` of the sun
+    /// on a surface facing it, so that it doesn't depend on the `angular_diameter` (radians).
+    pub fn new(direction: Vector, angular_diameter: f32, illuminance: Color) -> Self {
+        // Avoid cancellation for such small angles.
+        let one_minus_cos_theta_max = 2.0 * (angular_diameter / 4.0).sin().powi(2);
+        Self {
+            direction: direction.normalize(),
+            one_minus_cos_theta_max,
+            radiance: illuminance * (1.0 / (2.0 * PI * one_minus_cos_theta_max)),
+        }
+    }
+
+    /// Return the solid angle subtended by the sun disk.
+    pub fn solid_angle(&self) -> f32 {
+        2.0 * PI * self.one_minus_cos_theta_max
+    }
+
+    /// Return the illuminance of the sun on a surface facing it.
+    pub fn illuminance(&self) -> Color {
+        self.radiance * self.solid_angle()
+    }
+
+    /// Return the radiance coming from direction `dir`,
+    /// [`BLACK`] outside the sun disk.
+    pub fn radiance(&self, dir: Vector) -> Color {
+        if self.contains(dir) {
+            self.radiance
+        } else {
+            BLACK
+        }
+    }
+
+    /// Return `true` if direction `dir` points inside the sun disk.
+    fn contains(&self, dir: Vector) -> bool {
+        1.0 - dir.normalize().dot(self.direction) <= self.one_minus_cos_theta_max
+    }
+}
+
+impl SampleLight for SunLight {
+    /// Sample uniformly the cone subtended by the sun disk.
+    fn sample(&self, _origin: Point, pcg: (f32, f32)) -> (Vector, f32) {
+        (
+            sample_cone(self.direction, self.one_minus_cos_theta_max, pcg),
+            1.0 / self.solid_angle(),
+        )
+    }
+
+    fn pdf(&self, _origin: Point, dir: Vector) -> f32 {
+        if self.contains(dir) {
+            1.0 / self.solid_angle()
+        } else {
+            0.0
+        }
+    }
+}
+
+/// A daylight environment, the analytic [`Sky`] together with its [`SunLight`].
+///
+/// The sky is sampled through an [`EnvironmentLight`](struct@EnvironmentLight)
+/// baked from its radiance, while the sun disk is sampled directly.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SkyLight {
+    /// The analytic sky model.
+    sky: Sky,
+    /// The sun disk.
+    sun: SunLight,
+    /// Radiance scale factor of both sky and sun.
+    intensity: f32,
+    /// The sky radiance baked into an equirectangular map, used only for sampling.
+    map: EnvironmentLight,
+    /// Probability of sampling the sun instead of the sky.
+    sun_probability: f32,
+}
+
+impl SkyLight {
+    /// Width of the map used to sample the sky, its height is a half.
+    const MAP_WIDTH: u32 = 128;
+
+    /// Provides a constructor for [`SkyLight`](struct@SkyLight).
+    ///
+    /// The sun is in `sun_direction` with an `angular_diameter` (radians),
+    /// the atmosphere has `turbidity`, and radiance is scaled by `intensity`.
+    pub fn new(
+        sun_direction: Vector,
+        turbidity: f32,
+        angular_diameter: f32,
+        intensity: f32,
+    ) -> Self {
+        let sky = Sky::new(sun_direction, turbidity);
+        let sun = SunLight::new(
+            sky.sun_direction(),
+            angular_diameter,
+            sky.sun_illuminance() * intensity,
+        );
+        let (width, height) = (Self::MAP_WIDTH, Self::MAP_WIDTH / 2);
+        let mut image = HdrImage::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                // Same parametrization of `EnvironmentLight`, at the texel center.
+                let theta = PI * (y as f32 + 0.5) / height as f32;
+                let phi = 2.0 * PI * (x as f32 + 0.5) / width as f32;
+                let dir = Vector::from((
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                ));
+                image.set_pixel(x, y, sky.radiance(dir)).unwrap();
+            }
+        }
+        let map = EnvironmentLight::new(image, intensity, Transformation::default());
+        let sun_probability = if sun.illuminance().luminosity() > 0.0 {
+            0.5
+        } else {
+            0.0
+        };
+        Self {
+            sky,
+            sun,
+            intensity,
+            map,
+            sun_probability,
+        }
+    }
+
+    /// Return the [`SunLight`] of this sky.
+    pub fn sun(&self) -> &SunLight {
+        &self.sun
+    }
+
+    /// Return the radiance of sky and sun coming from direction `dir`.
+    pub fn radiance(&self, dir: Vector) -> Color {
+        self.sky.radiance(dir) * self.intensity + self.sun.radiance(dir)
+    }
+}
+
+impl SampleLight for SkyLight {
+    /// Sample either the sun disk or the sky map,\
+    /// the density is the mixture of both densities.
+    fn sample(&self, origin: Point, pcg: (f32, f32)) -> (Vector, f32) {
+        let dir = if pcg.0 < self.sun_probability {
+            self.sun
+                .sample(origin, (pcg.0 / self.sun_probability, pcg.1))
+                .0
+        } else {
+            let offset = (pcg.0 - self.sun_probability) / (1.0 - self.sun_probability);
+            self.map
+                .sample(origin, (offset.min(1.0 - f32::EPSILON), pcg.1))
+                .0
+        };
+        (dir, self.pdf(origin, dir))
+    }
+
+    fn pdf(&self, origin: Point, dir: Vector) -> f32 {
+        self.sun_probability * self.sun.pdf(origin, dir)
+            + (1.0 - self.sun_probability) * self.map.pdf(origin, dir)
+    }
+}
+
+/// Enum of environment light sources,
+/// seen by the rays that miss every shape of the world.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Environment {
+    Map(EnvironmentLight),
+    Sky(SkyLight),
+}
+
+impl Environment {
+    /// Return the radiance coming from direction `dir`
+    /// of a particular [`Environment`] variant.
+    pub fn radiance(&self, dir: Vector) -> Color {
+        match self {
+            Environment::Map(map) => map.radiance(dir),
+            Environment::Sky(sky) => sky.radiance(dir),
+        }
+    }
+
+    /// Return the [`SunLight`] of a particular [`Environment`] variant, if any.
+    pub fn sun(&self) -> Option<&SunLight> {
+        match self {
+            Environment::Map(_) => None,
+            Environment::Sky(sky) => Some(sky.sun()),
+        }
+    }
+}
+
+impl SampleLight for Environment {
+    /// Sample a direction towards a particular [`Environment`] variant.
+    fn sample(&self, origin: Point, pcg: (f32, f32)) -> (Vector, f32) {
+        match self {
+            Environment::Map(map) => map.sample(origin, pcg),
+            Environment::Sky(sky) => sky.sample(origin, pcg),
+        }
+    }
+
+    /// Return the probability density of a particular [`Environment`] variant.
+    fn pdf(&self, origin: Point, dir: Vector) -> f32 {
+        match self {
+            Environment::Map(map) => map.pdf(origin, dir),
+            Environment::Sky(sky) => sky.pdf(origin, dir),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        color::{BLACK, WHITE},
+        misc::IsClose,
+        random::Pcg,
+        transformation::rotation_x,
+    };
+
+    #[test]
+    fn test_sphere_light() {
+        let mut pcg = Pcg::default();
+        // Outside: every sampled direction hits the sphere
+        // and the probability density integrates to one over the cone.
+        let light = SphereLight::new(Point::from((0., 0., 4.)), 1.);
+        let origin = Point::default();
+        for _ in 0..100 {
+            let (dir, pdf) = light.sample(origin, (pcg.random_float(), pcg.random_float()));
+            let to_center = light.center - origin;
+            let b = dir.dot(to_center);
+            assert!(b * b - to_center.squared_norm() + 1. >= -1e-4);
+            assert!(pdf.is_close(light.pdf(origin, dir)));
+        }
+        let cone_solid_angle = 2. * PI * (1. - f32::sqrt(15.) / 4.);
+        assert!((1. / light.pdf(origin, Vector::from((0., 0., 1.)))).is_close(cone_solid_angle));
+        assert_eq!(light.pdf(origin, Vector::from((0., 1., 0.))), 0.);
+
+        // Inside: from the center the density is uniform over the whole sphere.
+        let (dir, pdf) = light.sample(light.center, (0.3, 0.7));
+        assert!((1. / pdf).is_close(4. * PI));
+        assert!(pdf.is_close(light.pdf(light.center, dir)));
+        let origin = Point::from((0., 0.5, 4.));
+        let (dir, pdf) = light.sample(origin, (0.1, 0.2));
+        assert!((pdf / light.pdf(origin, dir) - 1.).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_environment_light() {
+        let mut pcg = Pcg::default();
+        let mut image = HdrImage::new(4, 2);
+        image.set_pixel(1, 0, WHITE * 3.).unwrap();
+        image.set_pixel(2, 0, WHITE).unwrap();
+        image.set_pixel(3, 1, Color::from((0.5, 0., 0.5))).unwrap();
+        let light = EnvironmentLight::new(image, 2., Transformation::default());
+        // Integral of the luminosity over the whole sphere, every texel spans pi/2.
+        let total = (6. + 2. + 0.5) * PI / 2.;
+        let origin = Point::default();
+        for _ in 0..100 {
+            let (dir, pdf) = light.sample(origin, (pcg.random_float(), pcg.random_float()));
+            assert!(pdf.is_close(light.pdf(origin, dir)));
+            // Importance sampling of a gray map is exact sample by sample.
+            assert!((light.radiance(dir).luminosity() / pdf).is_close(total));
+        }
+        assert!(light
+            .radiance(Vector::from((-1., 1., 0.1)))
+            .is_close(WHITE * 6.));
+        assert!(light
+            .radiance(Vector::from((-1., -1., 0.1)))
+            .is_close(WHITE * 2.));
+        assert_eq!(light.pdf(origin, Vector::from((1., 1., 0.1))), 0.);
+
+        // A rotated environment turns upside down.
+        let mut image = HdrImage::new(1, 2);
+        image.set_pixel(0, 0, WHITE).unwrap();
+        let light = EnvironmentLight::new(image, 1., rotation_x(PI));
+        assert!(light.radiance(Vector::from((0., 0., -1.))).is_close(WHITE));
+        assert!(light.radiance(Vector::from((0., 0., 1.))).is_close(BLACK));
+        let (dir, pdf) = light.sample(origin, (0.3, 0.7));
+        assert!(dir.z < 0.);
+        assert!((1. / pdf).is_close(2. * PI));
+
+        // A black environment can't be sampled.
+        let light = EnvironmentLight::new(HdrImage::new(2, 2), 1., Transformation::default());
+        assert_eq!(light.sample(origin, (0.3, 0.7)).1, 0.);
+    }
+
+    #[test]
+    fn test_sun_light() {
+        let mut pcg = Pcg::default();
+        let diameter = f32::to_radians(0.53);
+        let sun = SunLight::new(Vector::from((1., 0., 1.)), diameter, WHITE * 100.);
+        let origin = Point::default();
+        // The solid angle of a small disk is close to its flat area.
+        assert!((sun.solid_angle() / (PI * diameter * diameter / 4.) - 1.).abs() < 1e-3);
+        assert!(sun.illuminance().is_close(WHITE * 100.));
+        for _ in 0..100 {
+            let (dir, pdf) = sun.sample(origin, (pcg.random_float(), pcg.random_float()));
+            assert!(pdf.is_close(sun.pdf(origin, dir)));
+            assert!(sun.radiance(dir).is_close(WHITE * (100. * pdf)));
+        }
+        assert_eq!(sun.pdf(origin, Vector::from((1., 0.1, 1.))), 0.);
+        assert!(sun.radiance(Vector::from((1., 0.1, 1.))).is_close(BLACK));
+    }
+
+    #[test]
+    fn test_sky_light() {
+        let mut pcg = Pcg::default();
+        let sky = SkyLight::new(Vector::from((1., 0., 0.5)), 3., f32::to_radians(0.53), 0.1);
+        let origin = Point::default();
+        let (mut sun_samples, mut sky_samples) = (0, 0);
+        for _ in 0..1000 {
+            let (dir, pdf) = sky.sample(origin, (pcg.random_float(), pcg.random_float()));
+            assert!(pdf.is_close(sky.pdf(origin, dir)));
+            assert!(pdf > 0.);
+            if sky.sun().radiance(dir).luminosity() > 0. {
+                sun_samples += 1;
+            } else {
+                // The sky is never sampled below the horizon.
+                assert!(dir.z > 0.);
+                sky_samples += 1;
+            }
+        }
+        assert!((400..600).contains(&sun_samples));
+        assert_eq!(sun_samples + sky_samples, 1000);
+        // The sky radiance is scaled by intensity, with the sun on top.
+        let environment = Environment::Sky(sky.clone());
+        assert!(environment.sun().is_some());
+        let sun_dir = Vector::from((1., 0., 0.5));
+        assert!(environment
+            .radiance(sun_dir)
+            .is_close(sky.sky.radiance(sun_dir) * 0.1 + sky.sun().radiance(sun_dir)));
+        assert!(environment
+            .radiance(Vector::from((0., 1., -1.)))
+            .is_close(BLACK));
+    }
+}
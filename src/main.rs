@@ -1,20 +1,30 @@
 #![doc = include_str!("../README.md")]
 
+mod bvh;
 mod camera;
+mod checkpoint;
 mod cli;
 mod color;
+mod compare;
+mod dither;
 mod error;
 mod hdrimage;
 mod imagetracer;
+mod light;
 mod material;
+mod mesh;
 mod misc;
+mod noise;
 mod normal;
 mod point;
 mod random;
 mod ray;
 mod render;
 mod scene;
+mod sdf;
 mod shape;
+mod sky;
+mod tonemap;
 mod transformation;
 mod vector;
 mod world;
@@ -34,18 +44,31 @@ use std::{
 
 use crate::{
     camera::{Camera, OrthogonalCamera, PerspectiveCamera},
+    checkpoint::{read_checkpoint, write_checkpoint},
     cli::Cli,
     color::{Color, BLACK, WHITE},
-    error::{CompletionErr, ConvertErr, DemoErr, HdrImageErr, RenderErr},
-    hdrimage::{HdrImage, Luminosity},
+    compare::{difference_image, Comparison, Metric},
+    dither::Dither,
+    error::{
+        CheckpointErr, CompareErr, CompletionErr, ConvertErr, DemoErr, HdrImageErr, RenderErr,
+    },
+    hdrimage::{
+        BitDepth, ExrCompression, ExrFormat, ExrPrecision, HdrImage, LdrFormat, Luminosity,
+        TransferFunction,
+    },
     imagetracer::ImageTracer,
+    light::PointLight,
     material::{
         CheckeredPigment, DiffuseBRDF, Material, Pigment, SpecularBRDF, UniformPigment, BRDF,
     },
     misc::ByteOrder,
-    render::{DummyRenderer, FlatRenderer, OnOffRenderer, PathTracer, Renderer},
+    point::Point,
+    render::{
+        DummyRenderer, FlatRenderer, OnOffRenderer, PathTracer, PointLightRenderer, Renderer,
+    },
     scene::Scene,
     shape::{Plane, Sphere},
+    tonemap::{AcesToneMap, ClipToneMap, ExtendedReinhardToneMap, HableToneMap, ToneMap},
     transformation::{rotation_z, scaling, translation, Transformation},
     vector::Vector,
     world::World,
@@ -64,6 +87,7 @@ fn main() {
         Some("convert") => exit!(convert(cli_m.subcommand_matches("convert").unwrap())),
         Some("demo") => exit!(demo(cli_m.subcommand_matches("demo").unwrap())),
         Some("render") => exit!(render(cli_m.subcommand_matches("render").unwrap())),
+        Some("compare") => exit!(compare(cli_m.subcommand_matches("compare").unwrap())),
         Some("completion") => {
             exit!(completion(cli_m.subcommand_matches("completion").unwrap()))
         },
@@ -82,7 +106,14 @@ fn convert(sub_m: &clap::ArgMatches) -> Result<(), ConvertErr> {
         .map_err(|e| ConvertErr::FloatParseFailure(e, String::from("factor")))?;
     let gamma = f32::from_str(sub_m.get_one::<String>("gamma").unwrap())
         .map_err(|e| ConvertErr::FloatParseFailure(e, String::from("gamma")))?;
-    let mut hdr_img = HdrImage::read_pfm_file(hdr_file).map_err(ConvertErr::IoError)?;
+    let white_point = sub_m
+        .get_one::<String>("white-point")
+        .map(|white_point| f32::from_str(white_point))
+        .transpose()
+        .map_err(|e| ConvertErr::FloatParseFailure(e, String::from("white-point")))?;
+    let quality = u8::from_str(sub_m.get_one::<String>("quality").unwrap())
+        .map_err(|e| ConvertErr::IntParseFailure(e, String::from("quality")))?;
+    let mut hdr_img = HdrImage::read_hdr_file(hdr_file).map_err(ConvertErr::IoError)?;
     check!(ldr_file).map_err(ConvertErr::IoError)?;
     if sub_m.get_flag("verbose") {
         println!(
@@ -92,9 +123,14 @@ fn convert(sub_m: &clap::ArgMatches) -> Result<(), ConvertErr> {
         );
     }
     hdr_img.normalize_image(factor, Luminosity::AverageLuminosity);
-    hdr_img.clamp_image();
+    let tone_map = tone_map(
+        sub_m.get_one::<String>("tonemap").unwrap(),
+        white_point,
+        &hdr_img,
+    );
+    hdr_img.clamp_image(tone_map);
     hdr_img
-        .write_ldr_file(ldr_file, gamma)
+        .write_ldr_file(ldr_file, ldr_format(sub_m, gamma, quality))
         .map_err(ConvertErr::IoError)?;
     if sub_m.get_flag("verbose") {
         println!(
@@ -115,6 +151,13 @@ fn demo(sub_m: &clap::ArgMatches) -> Result<(), DemoErr> {
         .map_err(|e| DemoErr::FloatParseFailure(e, String::from("factor")))?;
     let gamma = f32::from_str(sub_m.get_one::<String>("gamma").unwrap())
         .map_err(|e| DemoErr::FloatParseFailure(e, String::from("gamma")))?;
+    let white_point = sub_m
+        .get_one::<String>("white-point")
+        .map(|white_point| f32::from_str(white_point))
+        .transpose()
+        .map_err(|e| DemoErr::FloatParseFailure(e, String::from("white-point")))?;
+    let quality = u8::from_str(sub_m.get_one::<String>("quality").unwrap())
+        .map_err(|e| DemoErr::IntParseFailure(e, String::from("quality")))?;
     let width = u32::from_str(sub_m.get_one::<String>("width").unwrap())
         .map_err(|e| DemoErr::IntParseFailure(e, String::from("width")))?;
     let height = u32::from_str(sub_m.get_one::<String>("height").unwrap())
@@ -194,6 +237,12 @@ fn demo(sub_m: &clap::ArgMatches) -> Result<(), DemoErr> {
         translation(Vector::from((1.0, 2.5, 0.0))),
         mirror_material,
     )));
+    world.build_bvh();
+    world.add_light(PointLight::new(
+        Point::from((-30.0, 30.0, 30.0)),
+        WHITE,
+        0.0,
+    ));
     let camera_tr = rotation_z(f32::to_radians(angle_deg + 230.0))
         * translation(Vector::from((-3.5, 0.0, 0.7)));
     let mut tracer = ImageTracer::new(
@@ -214,9 +263,19 @@ fn demo(sub_m: &clap::ArgMatches) -> Result<(), DemoErr> {
     let renderer = match algorithm.as_str() {
         "onoff" => Renderer::OnOff(OnOffRenderer::new(&world, BLACK, WHITE)),
         "flat" => Renderer::Flat(FlatRenderer::new(&world, BLACK)),
-        "pathtracer" => {
-            Renderer::PathTracer(PathTracer::new(&world, BLACK, num_of_rays, max_depth, 3))
-        },
+        "pathtracer" => Renderer::PathTracer(PathTracer::new(
+            &world,
+            BLACK,
+            num_of_rays,
+            max_depth,
+            3,
+            sub_m.get_flag("light-sampling"),
+        )),
+        "pointlight" => Renderer::PointLight(PointLightRenderer::new(
+            &world,
+            BLACK,
+            Color::from((0.1, 0.1, 0.1)),
+        )),
         // This branch should not be triggered (dummy behaviour).
         _ => Renderer::Dummy(DummyRenderer),
     };
@@ -235,9 +294,14 @@ fn demo(sub_m: &clap::ArgMatches) -> Result<(), DemoErr> {
         }
     }
     hdr_img.normalize_image(factor, Luminosity::AverageLuminosity);
-    hdr_img.clamp_image();
+    let tone_map = tone_map(
+        sub_m.get_one::<String>("tonemap").unwrap(),
+        white_point,
+        &hdr_img,
+    );
+    hdr_img.clamp_image(tone_map);
     hdr_img
-        .write_ldr_file(ldr_file, gamma)
+        .write_ldr_file(ldr_file, ldr_format(sub_m, gamma, quality))
         .map_err(DemoErr::IoError)?;
     if sub_m.get_flag("verbose") {
         println!(
@@ -259,6 +323,13 @@ fn render(sub_m: &clap::ArgMatches) -> Result<(), Box<RenderErr>> {
         .map_err(|e| RenderErr::FloatParseFailure(e, String::from("factor")))?;
     let gamma = f32::from_str(sub_m.get_one::<String>("gamma").unwrap())
         .map_err(|e| RenderErr::FloatParseFailure(e, String::from("gamma")))?;
+    let white_point = sub_m
+        .get_one::<String>("white-point")
+        .map(|white_point| f32::from_str(white_point))
+        .transpose()
+        .map_err(|e| RenderErr::FloatParseFailure(e, String::from("white-point")))?;
+    let quality = u8::from_str(sub_m.get_one::<String>("quality").unwrap())
+        .map_err(|e| RenderErr::IntParseFailure(e, String::from("quality")))?;
     let width = u32::from_str(sub_m.get_one::<String>("width").unwrap())
         .map_err(|e| RenderErr::IntParseFailure(e, String::from("width")))?;
     let height = u32::from_str(sub_m.get_one::<String>("height").unwrap())
@@ -276,7 +347,26 @@ fn render(sub_m: &clap::ArgMatches) -> Result<(), Box<RenderErr>> {
         .map_err(|e| RenderErr::IntParseFailure(e, String::from("init-seq")))?;
     let antialiasing_level = u32::from_str(sub_m.get_one::<String>("anti-aliasing").unwrap())
         .map_err(|e| RenderErr::IntParseFailure(e, String::from("anti-aliasing")))?;
+    let passes = *sub_m.get_one::<u32>("passes").unwrap();
+    let progressive = passes > 1 || sub_m.get_flag("resume");
+    let hdr_file = ldr_file.with_extension("").with_extension("pfm");
+    let output_hdr = sub_m.get_one::<String>("output-hdr").map(Path::new);
+    let exr_format = ExrFormat {
+        precision: match sub_m.get_one::<String>("exr-precision").unwrap().as_str() {
+            "half" => ExrPrecision::Half,
+            _ => ExrPrecision::Float,
+        },
+        compression: match sub_m.get_one::<String>("exr-compression").unwrap().as_str() {
+            "none" => ExrCompression::Uncompressed,
+            "piz" => ExrCompression::Piz,
+            _ => ExrCompression::Zip,
+        },
+        alpha: sub_m.get_flag("exr-alpha"),
+    };
     check!(ldr_file).map_err(RenderErr::IoError)?;
+    if let Some(output_hdr) = output_hdr {
+        check_hdr!(output_hdr).map_err(RenderErr::IoError)?;
+    }
     if sub_m.get_flag("verbose") {
         println!(
             "{} reading scene from file {:?}",
@@ -302,21 +392,69 @@ fn render(sub_m: &clap::ArgMatches) -> Result<(), Box<RenderErr>> {
             height
         );
     }
-    let mut hdr_img = HdrImage::new(width, height);
+    let samples_per_pass = antialiasing_level.pow(2);
+    let (mut hdr_img, mut pass) = if sub_m.get_flag("resume") {
+        let (hdr_img, samples) = read_checkpoint(&hdr_file).map_err(RenderErr::CheckpointError)?;
+        if hdr_img.shape() != (width, height) || samples % samples_per_pass != 0 {
+            return Err(Box::new(RenderErr::CheckpointError(
+                CheckpointErr::InvalidCheckpoint(format!(
+                    "{samples} samples of shape {:?}, expected multiple of {samples_per_pass} \
+                     samples of shape {:?}",
+                    hdr_img.shape(),
+                    (width, height)
+                )),
+            )));
+        }
+        if sub_m.get_flag("verbose") {
+            println!(
+                "{} resuming from {:?} ({} samples per pixel)",
+                "[info]".green(),
+                hdr_file,
+                samples
+            );
+        }
+        (hdr_img, samples / samples_per_pass)
+    } else {
+        (HdrImage::new(width, height), 0)
+    };
     let mut tracer = ImageTracer::new(&mut hdr_img, scene.camera.unwrap());
     let world = scene.shapes.unwrap();
     let renderer = match algorithm.as_str() {
         "onoff" => Renderer::OnOff(OnOffRenderer::new(&world, BLACK, WHITE)),
         "flat" => Renderer::Flat(FlatRenderer::new(&world, BLACK)),
-        "pathtracer" => {
-            Renderer::PathTracer(PathTracer::new(&world, BLACK, num_of_rays, max_depth, 3))
-        },
+        "pathtracer" => Renderer::PathTracer(PathTracer::new(
+            &world,
+            BLACK,
+            num_of_rays,
+            max_depth,
+            3,
+            sub_m.get_flag("light-sampling"),
+        )),
+        "pointlight" => Renderer::PointLight(PointLightRenderer::new(
+            &world,
+            BLACK,
+            Color::from((0.1, 0.1, 0.1)),
+        )),
         // This branch should not be triggered (dummy behaviour).
         _ => Renderer::Dummy(DummyRenderer),
     };
-    tracer.fire_all_rays(&renderer, init_state, init_seq, antialiasing_level);
-    if sub_m.get_flag("output-pfm") {
-        let hdr_file = ldr_file.with_extension("").with_extension("pfm");
+    while pass < passes {
+        tracer.fire_pass(&renderer, init_state, init_seq, antialiasing_level, pass);
+        pass += 1;
+        if progressive {
+            write_checkpoint(tracer.image(), pass * samples_per_pass, &hdr_file)
+                .map_err(RenderErr::CheckpointError)?;
+            if sub_m.get_flag("verbose") {
+                println!(
+                    "{} pass {}/{} checkpoint has been written to disk",
+                    "[info]".green(),
+                    pass,
+                    passes
+                );
+            }
+        }
+    }
+    if sub_m.get_flag("output-pfm") && !progressive {
         hdr_img
             .write_pfm_file(&hdr_file, ByteOrder::LittleEndian)
             .map_err(RenderErr::IoError)?;
@@ -328,10 +466,27 @@ fn render(sub_m: &clap::ArgMatches) -> Result<(), Box<RenderErr>> {
             );
         }
     }
+    if let Some(output_hdr) = output_hdr {
+        hdr_img
+            .write_hdr_file(output_hdr, exr_format)
+            .map_err(RenderErr::IoError)?;
+        if sub_m.get_flag("verbose") {
+            println!(
+                "{} {:?} has been written to disk",
+                "[info]".green(),
+                output_hdr
+            );
+        }
+    }
     hdr_img.normalize_image(factor, Luminosity::AverageLuminosity);
-    hdr_img.clamp_image();
+    let tone_map = tone_map(
+        sub_m.get_one::<String>("tonemap").unwrap(),
+        white_point,
+        &hdr_img,
+    );
+    hdr_img.clamp_image(tone_map);
     hdr_img
-        .write_ldr_file(ldr_file, gamma)
+        .write_ldr_file(ldr_file, ldr_format(sub_m, gamma, quality))
         .map_err(RenderErr::IoError)?;
     if sub_m.get_flag("verbose") {
         println!(
@@ -343,6 +498,124 @@ fn render(sub_m: &clap::ArgMatches) -> Result<(), Box<RenderErr>> {
     Ok(())
 }
 
+/// Compare a test image against a reference image.
+///
+/// Called when `rustracer-compare` subcommand is used.
+fn compare(sub_m: &clap::ArgMatches) -> Result<(), CompareErr> {
+    let reference_file = Path::new(sub_m.get_one::<String>("REFERENCE").unwrap());
+    let test_file = Path::new(sub_m.get_one::<String>("TEST").unwrap());
+    let diff_file = sub_m.get_one::<String>("diff").map(Path::new);
+    let metric = match sub_m.get_one::<String>("metric").unwrap().as_str() {
+        "max-error" => Metric::MaxError,
+        "psnr" => Metric::Psnr,
+        "ssim" => Metric::Ssim,
+        _ => Metric::Rmse,
+    };
+    let threshold = sub_m
+        .get_one::<String>("threshold")
+        .map(|threshold| f32::from_str(threshold))
+        .transpose()
+        .map_err(|e| CompareErr::FloatParseFailure(e, String::from("threshold")))?;
+    if let Some(diff_file) = diff_file {
+        check!(diff_file).map_err(CompareErr::IoError)?;
+    }
+    let read_image = |path: &Path| {
+        if check_hdr!(path).is_ok() {
+            HdrImage::read_hdr_file(path)
+        } else {
+            HdrImage::read_ldr_file(path)
+        }
+    };
+    let reference = read_image(reference_file).map_err(CompareErr::IoError)?;
+    let test = read_image(test_file).map_err(CompareErr::IoError)?;
+    if sub_m.get_flag("verbose") {
+        println!(
+            "{} {:?} and {:?} have been read from disk",
+            "[info]".green(),
+            reference_file,
+            test_file
+        );
+    }
+    if reference.shape() != test.shape() {
+        return Err(CompareErr::ShapeMismatch(reference.shape(), test.shape()));
+    }
+    let comparison = Comparison::new(&reference, &test);
+    println!(
+        "{:>7} {:>12} {:>12} {:>12} {:>12}",
+        "channel", "rmse", "max-error", "psnr", "ssim"
+    );
+    for (channel, (((rmse, max_error), psnr), ssim)) in ["r", "g", "b"].iter().zip(
+        comparison
+            .rmse
+            .into_iter()
+            .zip(comparison.max_error)
+            .zip(comparison.psnr)
+            .zip(comparison.ssim),
+    ) {
+        println!("{channel:>7} {rmse:>12.6} {max_error:>12.6} {psnr:>12.4} {ssim:>12.6}");
+    }
+    if let Some(diff_file) = diff_file {
+        difference_image(&reference, &test)
+            .write_ldr_file(diff_file, LdrFormat::default())
+            .map_err(CompareErr::IoError)?;
+        if sub_m.get_flag("verbose") {
+            println!(
+                "{} {:?} has been written to disk",
+                "[info]".green(),
+                diff_file
+            );
+        }
+    }
+    if let Some(threshold) = threshold {
+        let value = comparison.worst(metric);
+        if !metric.passes(value, threshold) {
+            return Err(CompareErr::ThresholdExceeded(
+                String::from(sub_m.get_one::<String>("metric").unwrap()),
+                value,
+                threshold,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Return the [`ToneMap`] variant named `name` from cli.
+///
+/// Without a `white_point` extended Reinhard maps to pure white
+/// the maximum luminosity of the normalized `hdr_img`.
+fn tone_map(name: &str, white_point: Option<f32>, hdr_img: &HdrImage) -> ToneMap {
+    match name {
+        "extended-reinhard" => ToneMap::ExtendedReinhard(ExtendedReinhardToneMap::new(
+            white_point.unwrap_or_else(|| hdr_img.max_luminosity()),
+        )),
+        "aces" => ToneMap::Aces(AcesToneMap),
+        "hable" => ToneMap::Hable(HableToneMap),
+        "clip" => ToneMap::Clip(ClipToneMap),
+        _ => ToneMap::default(),
+    }
+}
+
+/// Return the [`LdrFormat`] of the ldr output file from cli.
+fn ldr_format(sub_m: &clap::ArgMatches, gamma: f32, quality: u8) -> LdrFormat {
+    LdrFormat {
+        transfer: if sub_m.get_flag("srgb") {
+            TransferFunction::Srgb
+        } else {
+            TransferFunction::Gamma(gamma)
+        },
+        bit_depth: match sub_m.get_one::<String>("bit-depth").unwrap().as_str() {
+            "16" => BitDepth::Sixteen,
+            _ => BitDepth::Eight,
+        },
+        dither: match sub_m.get_one::<String>("dither").unwrap().as_str() {
+            "ordered" => Dither::Ordered,
+            "blue-noise" => Dither::BlueNoise,
+            _ => Dither::None,
+        },
+        quality,
+    }
+}
+
 /// Generate shell completions file for `rustracer` command and its subcommands.
 ///
 /// Called when `rustracer-completion` subcommand is used.
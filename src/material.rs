@@ -7,7 +7,8 @@
 use crate::{
     color::{Color, BLACK, WHITE},
     hdrimage::HdrImage,
-    misc::Vector2D,
+    misc::{Footprint, Vector2D},
+    noise::Noise,
     normal::{create_onb_from_z, Normal},
     point::Point,
     ray::Ray,
@@ -18,6 +19,13 @@ use std::f32::consts::PI;
 /// Trait that associates a [`Color`] with each point on a parametric surface `(u,v)`.
 pub trait GetColor {
     fn get_color(&self, uv: Vector2D) -> Color;
+
+    /// Return the [`Color`] averaged over the `footprint` around `(u,v)`.
+    ///
+    /// Pigments without details to alias fall back to [`get_color`](#tymethod.get_color).
+    fn filtered_color(&self, uv: Vector2D, _point: Point, _footprint: Footprint) -> Color {
+        self.get_color(uv)
+    }
 }
 
 /// A uniform pigment.
@@ -35,37 +43,168 @@ impl GetColor for UniformPigment {
     }
 }
 
+/// Variants of texture filtering used by [`ImagePigment`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Filter {
+    /// Color of the nearest texel.
+    #[default]
+    Nearest,
+    /// Bilinear interpolation of the 2x2 nearest texels.
+    Bilinear,
+    /// Bicubic (Catmull-Rom) interpolation of the 4x4 nearest texels.
+    Bicubic,
+}
+
+/// Variants of texture wrapping used by [`ImagePigment`]
+/// for `(u,v)` coordinates outside `[0, 1]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Wrap {
+    /// Texture is tiled.
+    Repeat,
+    /// Texture edges are extended.
+    #[default]
+    Clamp,
+    /// Texture is tiled, flipping every other tile.
+    Mirror,
+}
+
+impl Wrap {
+    /// Map a texel `index` (maybe outside the texture) into `[0, size)`.
+    fn wrap(&self, index: i64, size: u32) -> u32 {
+        let size = size as i64;
+        (match self {
+            Wrap::Repeat => index.rem_euclid(size),
+            Wrap::Clamp => index.clamp(0, size - 1),
+            Wrap::Mirror => {
+                let index = index.rem_euclid(2 * size);
+                if index < size {
+                    index
+                } else {
+                    2 * size - 1 - index
+                }
+            },
+        }) as u32
+    }
+}
+
 /// A textured pigment.
 ///
-/// The texture is given through a [`HdrImage`], maybe read from pfm file.
+/// The texture is given through a [`HdrImage`], maybe read from pfm file.\
+/// Its texels are looked up with a [`Filter`] and a [`Wrap`] mode.
+///
+/// When a [`Footprint`] wider than a texel is available, texels are looked up
+/// in its [mip pyramid](../hdrimage/struct.HdrImage.html#method.mip_pyramid)
+/// with a trilinear filter.
 #[derive(Clone, Debug)]
 pub struct ImagePigment {
-    /// An [`HdrImage`] reference.
-    hdr_img: HdrImage,
+    /// Mip pyramid of the texture, the first level is the texture itself.
+    levels: Vec<HdrImage>,
+    /// Texture filtering.
+    pub filter: Filter,
+    /// Texture wrapping.
+    pub wrap: Wrap,
 }
 
 impl ImagePigment {
-    /// Create a new [`ImagePigment`] from [`HdrImage`].
+    /// Create a new [`ImagePigment`] from [`HdrImage`],
+    /// with [`Filter::Nearest`] and [`Wrap::Clamp`].
     pub fn new(hdr_img: HdrImage) -> Self {
-        Self { hdr_img }
+        Self {
+            levels: hdr_img.mip_pyramid(),
+            filter: Filter::default(),
+            wrap: Wrap::default(),
+        }
+    }
+
+    /// Return the texel of `level` at `(col, row)`, wrapped inside it.
+    fn texel(&self, level: &HdrImage, col: i64, row: i64) -> Color {
+        let (width, height) = level.shape();
+        level
+            .get_pixel(self.wrap.wrap(col, width), self.wrap.wrap(row, height))
+            .unwrap()
+    }
+
+    /// Return the texels of `level` around `(u,v)`, starting from the `(col, row)` one,
+    /// with the offsets `(tx, ty)` of `(u,v)` from its center.
+    fn texel_neighborhood(&self, level: &HdrImage, uv: Vector2D) -> (i64, i64, f32, f32) {
+        let (width, height) = level.shape();
+        // Texel centers are at half-integer coordinates.
+        let (x, y) = (uv.u * width as f32 - 0.5, uv.v * height as f32 - 0.5);
+        let (col, row) = (x.floor(), y.floor());
+        (col as i64, row as i64, x - col, y - row)
+    }
+
+    /// Return the bilinear interpolation of the 2x2 texels of `level` around `(u,v)`.
+    fn bilinear(&self, level: &HdrImage, uv: Vector2D) -> Color {
+        let (col, row, tx, ty) = self.texel_neighborhood(level, uv);
+        self.texel(level, col, row) * ((1.0 - tx) * (1.0 - ty))
+            + self.texel(level, col + 1, row) * (tx * (1.0 - ty))
+            + self.texel(level, col, row + 1) * ((1.0 - tx) * ty)
+            + self.texel(level, col + 1, row + 1) * (tx * ty)
     }
 }
 
+/// Return the Catmull-Rom weights of the four texels around `t` in `[0, 1)`.
+fn catmull_rom(t: f32) -> [f32; 4] {
+    let (t2, t3) = (t * t, t * t * t);
+    [
+        0.5 * (-t3 + 2.0 * t2 - t),
+        0.5 * (3.0 * t3 - 5.0 * t2 + 2.0),
+        0.5 * (-3.0 * t3 + 4.0 * t2 + t),
+        0.5 * (t3 - t2),
+    ]
+}
+
 impl GetColor for ImagePigment {
     fn get_color(&self, uv: Vector2D) -> Color {
-        let mut col = (uv.u * self.hdr_img.shape().0 as f32) as u32;
-        let mut row = (uv.v * self.hdr_img.shape().1 as f32) as u32;
-        if col >= self.hdr_img.shape().0 {
-            col = self.hdr_img.shape().0 - 1;
+        let texture = &self.levels[0];
+        let (width, height) = texture.shape();
+        match self.filter {
+            Filter::Nearest => self.texel(
+                texture,
+                (uv.u * width as f32).floor() as i64,
+                (uv.v * height as f32).floor() as i64,
+            ),
+            Filter::Bilinear => self.bilinear(texture, uv),
+            Filter::Bicubic => {
+                let (col, row, tx, ty) = self.texel_neighborhood(texture, uv);
+                let (wx, wy) = (catmull_rom(tx), catmull_rom(ty));
+                let mut color = BLACK;
+                for (j, wy) in wy.iter().enumerate() {
+                    for (i, wx) in wx.iter().enumerate() {
+                        color = color
+                            + self.texel(texture, col - 1 + i as i64, row - 1 + j as i64)
+                                * (wx * wy);
+                    }
+                }
+                // Catmull-Rom spline overshoots near sharp edges.
+                Color::from((color.r.max(0.0), color.g.max(0.0), color.b.max(0.0)))
+            },
+        }
+    }
+
+    /// Footprints up to a texel wide are looked up with the configured [`Filter`],
+    /// wider ones are trilinearly interpolated between the two nearest mip levels.
+    fn filtered_color(&self, uv: Vector2D, _point: Point, footprint: Footprint) -> Color {
+        let (width, height) = self.levels[0].shape();
+        let texels = |duv: Vector2D| (duv.u * width as f32).hypot(duv.v * height as f32);
+        let lod = texels(footprint.duv_dx)
+            .max(texels(footprint.duv_dy))
+            .log2();
+        // Also catches not finite footprints.
+        if lod.is_nan() || lod <= 0.0 {
+            return self.get_color(uv);
         }
-        if row >= self.hdr_img.shape().1 {
-            row = self.hdr_img.shape().1 - 1
+        let lod = lod.min((self.levels.len() - 1) as f32);
+        let level = lod.floor() as usize;
+        let color = self.bilinear(&self.levels[level], uv);
+        match self.levels.get(level + 1) {
+            Some(next) => {
+                let t = lod - level as f32;
+                color * (1.0 - t) + self.bilinear(next, uv) * t
+            },
+            None => color,
         }
-        // TODO
-        // A nicer solution would implement bilinear interpolation
-        // to reduce pixelization artifacts.
-        // See https://en.wikipedia.org/wiki/Bilinear_interpolation.
-        self.hdr_img.get_pixel(col, row).unwrap()
     }
 }
 
@@ -95,12 +234,124 @@ impl GetColor for CheckeredPigment {
     }
 }
 
+/// Phase distortion of the marble veins, per unit of turbulence.
+const MARBLE_TURBULENCE: f32 = 8.0;
+/// Radius distortion of the wood rings, per unit of turbulence.
+const WOOD_TURBULENCE: f32 = 0.5;
+
+/// Variants of procedural patterns used by [`NoisePigment`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Pattern {
+    /// Plain noise.
+    Noise,
+    /// Fractional Brownian motion, a sum of noise octaves.
+    #[default]
+    Fbm,
+    /// Sum of absolute noise octaves.
+    Turbulence,
+    /// Sine veins along `x`, distorted by turbulence.
+    Marble,
+    /// Concentric rings around `z` axis, distorted by turbulence.
+    Wood,
+}
+
+/// Variants of coordinates where [`NoisePigment`] is evaluated.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Space {
+    /// Surface coordinates `(u, v, 0)`.
+    Uv,
+    /// Coordinates of the point in the frame of reference of the shape.
+    #[default]
+    Object,
+}
+
+/// A procedural pigment.
+///
+/// Two colors are blended by a [`Pattern`] built on a seedable [`Noise`],\
+/// evaluated in [`Space::Object`] or [`Space::Uv`] coordinates, multiplied by `scale`.
+#[derive(Clone, Debug)]
+pub struct NoisePigment {
+    /// First [`Color`], where the pattern is zero.
+    pub color1: Color,
+    /// Second [`Color`], where the pattern is one.
+    pub color2: Color,
+    /// Procedural pattern.
+    pub pattern: Pattern,
+    /// Frequency of the pattern.
+    pub scale: f32,
+    /// Number of noise octaves of fractal patterns.
+    pub octaves: u32,
+    /// Coordinates of evaluation.
+    pub space: Space,
+    /// Underlying noise.
+    noise: Noise,
+}
+
+impl NoisePigment {
+    /// Create a new [`NoisePigment`] evaluated in [`Space::Object`].
+    pub fn new(
+        pattern: Pattern,
+        color1: Color,
+        color2: Color,
+        scale: f32,
+        octaves: u32,
+        noise: Noise,
+    ) -> Self {
+        Self {
+            color1,
+            color2,
+            pattern,
+            scale,
+            octaves,
+            space: Space::default(),
+            noise,
+        }
+    }
+
+    /// Return the blended [`Color`] at `point`, scaled by `scale` before evaluating the pattern.
+    fn color(&self, point: Point) -> Color {
+        let point = point * self.scale;
+        let t = match self.pattern {
+            Pattern::Noise => 0.5 * (self.noise.noise(point) + 1.0),
+            Pattern::Fbm => 0.5 * (self.noise.fbm(point, self.octaves) + 1.0),
+            Pattern::Turbulence => self.noise.turbulence(point, self.octaves),
+            Pattern::Marble => {
+                let turbulence = self.noise.turbulence(point, self.octaves);
+                0.5 * (1.0 + f32::sin(PI * point.x + MARBLE_TURBULENCE * turbulence))
+            },
+            Pattern::Wood => {
+                let radius = point.x.hypot(point.y)
+                    + WOOD_TURBULENCE * self.noise.turbulence(point, self.octaves);
+                radius - radius.floor()
+            },
+        }
+        .clamp(0.0, 1.0);
+        self.color1 * (1.0 - t) + self.color2 * t
+    }
+}
+
+impl GetColor for NoisePigment {
+    /// Evaluate the pattern in surface coordinates `(u, v, 0)`.
+    fn get_color(&self, uv: Vector2D) -> Color {
+        self.color(Point::from((uv.u, uv.v, 0.0)))
+    }
+
+    /// Evaluate the pattern in the coordinates chosen by [`Space`].
+    fn filtered_color(&self, uv: Vector2D, point: Point, _footprint: Footprint) -> Color {
+        match self.space {
+            Space::Uv => self.get_color(uv),
+            Space::Object => self.color(point),
+        }
+    }
+}
+
 /// Enum of pigments.
 #[derive(Clone, Debug)]
 pub enum Pigment {
     Uniform(UniformPigment),
     Image(ImagePigment),
     Checkered(CheckeredPigment),
+    Noise(NoisePigment),
 }
 
 impl GetColor for Pigment {
@@ -110,16 +361,42 @@ impl GetColor for Pigment {
             Pigment::Uniform(uniform) => uniform.get_color(uv),
             Pigment::Image(image) => image.get_color(uv),
             Pigment::Checkered(checkered) => checkered.get_color(uv),
+            Pigment::Noise(noise) => noise.get_color(uv),
+        }
+    }
+
+    /// Return a different filtered [`Color`] as function of [`Pigment`] variant.
+    fn filtered_color(&self, uv: Vector2D, point: Point, footprint: Footprint) -> Color {
+        match self {
+            Pigment::Uniform(uniform) => uniform.filtered_color(uv, point, footprint),
+            Pigment::Image(image) => image.filtered_color(uv, point, footprint),
+            Pigment::Checkered(checkered) => checkered.filtered_color(uv, point, footprint),
+            Pigment::Noise(noise) => noise.filtered_color(uv, point, footprint),
         }
     }
 }
 
-/// A trait for evaluating a particular BRDF on a parametric surface `(u,v)`.
+/// A trait for evaluating a particular BRDF on a parametric surface `(u,v)`,
+/// with pigments filtered over a [`Footprint`].
 pub trait Eval {
-    fn eval(&self, normal: Normal, in_dir: Vector, out_dir: Vector, uv: Vector2D) -> Color;
+    fn eval(
+        &self,
+        normal: Normal,
+        in_dir: Vector,
+        out_dir: Vector,
+        uv: Vector2D,
+        point: Point,
+        footprint: Footprint,
+    ) -> Color;
 }
 
 /// A trait for scatter a [`Ray`] for a particular BRDF.
+///
+/// [`scatter_pdf`](#tymethod.scatter_pdf) returns the probability density (per unit solid angle)
+/// of scattering `incoming_dir` towards `out_dir` with [`scatter_ray`](#tymethod.scatter_ray),\
+/// [`None`] if the BRDF is a delta distribution (e.g. ideal mirror) that can only be sampled.\
+/// A ray scattered by a delta distribution is weighted by
+/// [`scatter_weight`](#method.scatter_weight), given the same `pcg` numbers.
 pub trait ScatterRay {
     fn scatter_ray(
         &self,
@@ -127,8 +404,33 @@ pub trait ScatterRay {
         incoming_dir: Vector,
         interaction_point: Point,
         normal: Normal,
+        inside: bool,
         depth: u32,
     ) -> Ray;
+    fn scatter_pdf(&self, normal: Normal, incoming_dir: Vector, out_dir: Vector) -> Option<f32>;
+    /// By default the BRDF `color`, delta distributions sample proportionally to their reflectance.
+    fn scatter_weight(
+        &self,
+        _pcg: (f32, f32),
+        _normal: Normal,
+        _incoming_dir: Vector,
+        _out_dir: Vector,
+        _inside: bool,
+        color: Color,
+    ) -> Color {
+        color
+    }
+}
+
+/// Return a cosine-weighted random direction on the semi-sphere around `normal`,
+/// using two [`f32`] generated by [`Pcg`](../../../random/struct.Pcg.html) random generator.
+fn cosine_weighted_direction(normal: Normal, pcg: (f32, f32)) -> Vector {
+    // Cosine-weighted distribution around the z (local) axis.
+    let (e1, e2, e3) = create_onb_from_z(normal);
+    let cos_theta_sq = pcg.0;
+    let (cos_theta, sin_theta) = (f32::sqrt(cos_theta_sq), f32::sqrt(1.0 - cos_theta_sq));
+    let phi = 2.0 * PI * pcg.1;
+    e1 * f32::cos(phi) * cos_theta + e2 * f32::sin(phi) * cos_theta + e3 * sin_theta
 }
 
 /// A class representing an ideal diffuse BRDF (also called "Lambertian").
@@ -150,11 +452,23 @@ impl GetColor for DiffuseBRDF {
     fn get_color(&self, uv: Vector2D) -> Color {
         self.pigment.get_color(uv)
     }
+
+    fn filtered_color(&self, uv: Vector2D, point: Point, footprint: Footprint) -> Color {
+        self.pigment.filtered_color(uv, point, footprint)
+    }
 }
 
 impl Eval for DiffuseBRDF {
-    fn eval(&self, _normal: Normal, _in_dir: Vector, _out_dir: Vector, uv: Vector2D) -> Color {
-        self.pigment.get_color(uv) * (1.0 / PI)
+    fn eval(
+        &self,
+        _normal: Normal,
+        _in_dir: Vector,
+        _out_dir: Vector,
+        uv: Vector2D,
+        point: Point,
+        footprint: Footprint,
+    ) -> Color {
+        self.pigment.filtered_color(uv, point, footprint) * (1.0 / PI)
     }
 }
 
@@ -167,22 +481,27 @@ impl ScatterRay for DiffuseBRDF {
         _incoming_dir: Vector,
         interaction_point: Point,
         normal: Normal,
+        _inside: bool,
         depth: u32,
     ) -> Ray {
-        // Cosine-weighted distribution around the z (local) axis.
-        let (e1, e2, e3) = create_onb_from_z(normal);
-        let cos_theta_sq = pcg.0;
-        let (cos_theta, sin_theta) = (f32::sqrt(cos_theta_sq), f32::sqrt(1.0 - cos_theta_sq));
-        let phi = 2.0 * PI * pcg.1;
-
         Ray {
             origin: interaction_point,
-            dir: e1 * f32::cos(phi) * cos_theta + e2 * f32::sin(phi) * cos_theta + e3 * sin_theta,
+            dir: cosine_weighted_direction(normal, pcg),
             tmin: 1.0e-3,
             depth,
             ..Default::default()
         }
     }
+
+    fn scatter_pdf(&self, normal: Normal, _incoming_dir: Vector, out_dir: Vector) -> Option<f32> {
+        Some(
+            Vector::from(normal)
+                .normalize()
+                .dot(out_dir.normalize())
+                .max(0.0)
+                / PI,
+        )
+    }
 }
 
 /// A class representing an ideal mirror BRDF.
@@ -207,15 +526,27 @@ impl GetColor for SpecularBRDF {
     fn get_color(&self, uv: Vector2D) -> Color {
         self.pigment.get_color(uv)
     }
+
+    fn filtered_color(&self, uv: Vector2D, point: Point, footprint: Footprint) -> Color {
+        self.pigment.filtered_color(uv, point, footprint)
+    }
 }
 
 impl Eval for SpecularBRDF {
-    fn eval(&self, normal: Normal, in_dir: Vector, out_dir: Vector, uv: Vector2D) -> Color {
+    fn eval(
+        &self,
+        normal: Normal,
+        in_dir: Vector,
+        out_dir: Vector,
+        uv: Vector2D,
+        point: Point,
+        footprint: Footprint,
+    ) -> Color {
         let theta_in = f32::acos(Vector::from(normal).normalize().dot(in_dir.normalize()));
         let theta_out = f32::acos(Vector::from(normal).normalize().dot(out_dir.normalize()));
 
         if (theta_in - theta_out).abs() < self.threshold_angle_rad {
-            self.pigment.get_color(uv)
+            self.pigment.filtered_color(uv, point, footprint)
         } else {
             BLACK
         }
@@ -230,6 +561,7 @@ impl ScatterRay for SpecularBRDF {
         incoming_dir: Vector,
         interaction_point: Point,
         normal: Normal,
+        _inside: bool,
         depth: u32,
     ) -> Ray {
         let ray_dir = incoming_dir.normalize();
@@ -243,6 +575,518 @@ impl ScatterRay for SpecularBRDF {
             ..Default::default()
         }
     }
+
+    fn scatter_pdf(&self, _normal: Normal, _incoming_dir: Vector, _out_dir: Vector) -> Option<f32> {
+        None
+    }
+}
+
+/// A class representing an ideal dielectric BRDF (e.g. glass, water).
+///
+/// Light is either reflected or refracted, with a probability
+/// given by the Fresnel equations (Schlick's approximation).
+#[derive(Clone, Debug)]
+pub struct DielectricBRDF {
+    /// A generic pigment that implement [`GetColor`] trait.
+    pub pigment: Pigment,
+    /// Index of refraction of the material (surrounding medium is vacuum).
+    pub ior: f32,
+}
+
+impl Default for DielectricBRDF {
+    fn default() -> Self {
+        Self {
+            pigment: Pigment::Uniform(UniformPigment { color: WHITE }),
+            ior: 1.5,
+        }
+    }
+}
+
+impl GetColor for DielectricBRDF {
+    fn get_color(&self, uv: Vector2D) -> Color {
+        self.pigment.get_color(uv)
+    }
+
+    fn filtered_color(&self, uv: Vector2D, point: Point, footprint: Footprint) -> Color {
+        self.pigment.filtered_color(uv, point, footprint)
+    }
+}
+
+impl Eval for DielectricBRDF {
+    /// A dielectric BRDF is a delta distribution,
+    /// so the probability to match exactly `out_dir` is zero.
+    fn eval(
+        &self,
+        _normal: Normal,
+        _in_dir: Vector,
+        _out_dir: Vector,
+        _uv: Vector2D,
+        _point: Point,
+        _footprint: Footprint,
+    ) -> Color {
+        BLACK
+    }
+}
+
+/// Schlick's approximation of Fresnel reflectance, for a ray
+/// with `cos_theta` incidence angle in the less dense medium.
+fn schlick(cos_theta: f32, ior: f32) -> f32 {
+    let r0 = ((1.0 - ior) / (1.0 + ior)).powi(2);
+    r0 + (1.0 - r0) * (1.0 - cos_theta).powi(5)
+}
+
+impl ScatterRay for DielectricBRDF {
+    /// Reflect or refract the incoming ray, choosing by Fresnel reflectance probability.
+    ///
+    /// `inside` tells if the ray is leaving the material, in that case
+    /// total internal reflection can occur.
+    fn scatter_ray(
+        &self,
+        pcg: (f32, f32),
+        incoming_dir: Vector,
+        interaction_point: Point,
+        normal: Normal,
+        inside: bool,
+        depth: u32,
+    ) -> Ray {
+        let ray_dir = incoming_dir.normalize();
+        let normal = Vector::from(normal).normalize();
+        // Ratio between incident and transmitted medium indices.
+        let eta = if inside { self.ior } else { 1.0 / self.ior };
+        let cos_i = -normal.dot(ray_dir);
+        let sin_t_sq = eta * eta * (1.0 - cos_i * cos_i).max(0.0);
+        let dir = if sin_t_sq >= 1.0 {
+            // Total internal reflection.
+            ray_dir + normal * 2.0 * cos_i
+        } else {
+            let cos_t = (1.0 - sin_t_sq).sqrt();
+            let reflectance = schlick(if inside { cos_t } else { cos_i }, self.ior);
+            if pcg.0 < reflectance {
+                ray_dir + normal * 2.0 * cos_i
+            } else {
+                ray_dir * eta + normal * (eta * cos_i - cos_t)
+            }
+        };
+        Ray {
+            origin: interaction_point,
+            dir,
+            tmin: 1.0e-3,
+            depth,
+            ..Default::default()
+        }
+    }
+
+    fn scatter_pdf(&self, _normal: Normal, _incoming_dir: Vector, _out_dir: Vector) -> Option<f32> {
+        None
+    }
+}
+
+/// Index of refraction of the dielectric base of [`MicrofacetBRDF`],
+/// a reflectance of 4% at normal incidence (e.g. plastic).
+const MICROFACET_IOR: f32 = 1.5;
+/// Minimum width of the microfacet distribution,
+/// a smoother surface is numerically unstable.
+const MIN_ALPHA: f32 = 1e-3;
+
+/// A class representing a rough BRDF,
+/// with a GGX (Trowbridge-Reitz) distribution of microfacet normals.
+///
+/// The `metallic` parameter blends between a dielectric (e.g. plastic),
+/// a diffuse base under an uncolored specular reflection,
+/// and a conductor (e.g. metal), a specular reflection colored by the pigment.\
+/// Fresnel reflectance uses Schlick's approximation,
+/// shadowing and masking of microfacets the height-correlated Smith function.
+///
+/// Light is only reflected, a rough transmitting dielectric (e.g. frosted glass)
+/// is not modeled: [`DielectricBRDF`] is the only refracting BRDF.
+#[derive(Clone, Debug)]
+pub struct MicrofacetBRDF {
+    /// A generic pigment that implement [`GetColor`] trait.
+    pub pigment: Pigment,
+    /// Perceptual roughness in `[0, 1]`, its square is the width of the distribution.
+    pub roughness: f32,
+    /// Metalness in `[0, 1]`, from dielectric to conductor.
+    pub metallic: f32,
+}
+
+impl Default for MicrofacetBRDF {
+    fn default() -> Self {
+        Self {
+            pigment: Pigment::Uniform(UniformPigment { color: WHITE }),
+            roughness: 0.5,
+            metallic: 0.0,
+        }
+    }
+}
+
+impl MicrofacetBRDF {
+    /// Width of the microfacet distribution.
+    fn alpha(&self) -> f32 {
+        (self.roughness * self.roughness).max(MIN_ALPHA)
+    }
+
+    /// Probability of sampling the specular lobe instead of the diffuse one.
+    fn specular_probability(&self) -> f32 {
+        0.5 + 0.5 * self.metallic
+    }
+}
+
+/// GGX distribution of microfacet normals, with `cos_theta` from the surface normal.
+fn ggx_distribution(cos_theta: f32, alpha: f32) -> f32 {
+    let alpha_sq = alpha * alpha;
+    let denominator = cos_theta * cos_theta * (alpha_sq - 1.0) + 1.0;
+    alpha_sq / (PI * denominator * denominator)
+}
+
+/// Smith auxiliary function of GGX, for a direction with `cos_theta` from the surface normal.
+fn smith_lambda(cos_theta: f32, alpha: f32) -> f32 {
+    let cos_theta_sq = cos_theta * cos_theta;
+    let tan_theta_sq = (1.0 - cos_theta_sq).max(0.0) / cos_theta_sq;
+    0.5 * ((1.0 + alpha * alpha * tan_theta_sq).sqrt() - 1.0)
+}
+
+/// Sample a microfacet normal of a GGX distribution of width `alpha` visible from `out_dir`,
+/// both in the local frame of the surface ([Heitz](https://jcgt.org/published/0007/04/01/), 2018).
+fn sample_visible_normal(out_dir: Vector, alpha: f32, pcg: (f32, f32)) -> Vector {
+    // Stretch the view direction to a hemisphere configuration.
+    let view = Vector::from((alpha * out_dir.x, alpha * out_dir.y, out_dir.z)).normalize();
+    let len_sq = view.x * view.x + view.y * view.y;
+    let t1 = if len_sq > 0.0 {
+        Vector::from((-view.y, view.x, 0.0)) * (1.0 / len_sq.sqrt())
+    } else {
+        Vector::from((1.0, 0.0, 0.0))
+    };
+    let t2 = view * t1;
+    // Sample the projected area of the visible hemisphere.
+    let (r, phi) = (pcg.0.sqrt(), 2.0 * PI * pcg.1);
+    let p1 = r * phi.cos();
+    let s = 0.5 * (1.0 + view.z);
+    let p2 = (1.0 - s) * (1.0 - p1 * p1).max(0.0).sqrt() + s * r * phi.sin();
+    let normal = t1 * p1 + t2 * p2 + view * (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt();
+    // Unstretch back to the ellipsoid configuration.
+    Vector::from((alpha * normal.x, alpha * normal.y, normal.z.max(0.0))).normalize()
+}
+
+/// Reflect the observer direction `out_dir` on a GGX microfacet normal visible from it.
+fn sample_ggx_reflection(normal: Normal, out_dir: Vector, alpha: f32, pcg: (f32, f32)) -> Vector {
+    let (e1, e2, e3) = create_onb_from_z(normal.normalize());
+    let local_out = Vector::from((out_dir.dot(e1), out_dir.dot(e2), out_dir.dot(e3)));
+    let local_half = sample_visible_normal(local_out, alpha, pcg);
+    let half = e1 * local_half.x + e2 * local_half.y + e3 * local_half.z;
+    half * (2.0 * out_dir.dot(half)) - out_dir
+}
+
+/// Return the GGX specular reflection without the Fresnel term, and the probability density
+/// of reflecting `out_dir` towards `in_dir` by [`sample_ggx_reflection`].
+///
+/// Directions must be normalized and above the surface.
+fn ggx_reflection(normal: Vector, in_dir: Vector, out_dir: Vector, alpha: f32) -> (f32, f32) {
+    let (cos_in, cos_out) = (normal.dot(in_dir), normal.dot(out_dir));
+    let half = (in_dir + out_dir).normalize();
+    let distribution = ggx_distribution(normal.dot(half), alpha);
+    let lambda_out = smith_lambda(cos_out, alpha);
+    let shadowing = 1.0 / (1.0 + smith_lambda(cos_in, alpha) + lambda_out);
+    // Visible normals density, changed of variable from half vector to reflected direction.
+    let pdf = distribution / ((1.0 + lambda_out) * 4.0 * cos_out);
+    (distribution * shadowing / (4.0 * cos_in * cos_out), pdf)
+}
+
+impl GetColor for MicrofacetBRDF {
+    fn get_color(&self, uv: Vector2D) -> Color {
+        self.pigment.get_color(uv)
+    }
+
+    fn filtered_color(&self, uv: Vector2D, point: Point, footprint: Footprint) -> Color {
+        self.pigment.filtered_color(uv, point, footprint)
+    }
+}
+
+impl Eval for MicrofacetBRDF {
+    fn eval(
+        &self,
+        normal: Normal,
+        in_dir: Vector,
+        out_dir: Vector,
+        uv: Vector2D,
+        point: Point,
+        footprint: Footprint,
+    ) -> Color {
+        let normal = Vector::from(normal).normalize();
+        let (in_dir, out_dir) = (in_dir.normalize(), out_dir.normalize());
+        if normal.dot(in_dir) <= 0.0 || normal.dot(out_dir) <= 0.0 {
+            return BLACK;
+        }
+        let half = (in_dir + out_dir).normalize();
+        let cos_half = out_dir.dot(half).max(0.0);
+        let color = self.pigment.filtered_color(uv, point, footprint);
+        // Reflectance at normal incidence, from dielectric to conductor.
+        let dielectric = schlick(1.0, MICROFACET_IOR);
+        let f0 = WHITE * (dielectric * (1.0 - self.metallic)) + color * self.metallic;
+        let weight = (1.0 - cos_half).powi(5);
+        let fresnel = f0 * (1.0 - weight) + WHITE * weight;
+        let specular = fresnel * ggx_reflection(normal, in_dir, out_dir, self.alpha()).0;
+        // The light not reflected by the dielectric interface reaches the diffuse base.
+        let diffuse =
+            color * ((1.0 - self.metallic) * (1.0 - schlick(cos_half, MICROFACET_IOR)) / PI);
+        specular + diffuse
+    }
+}
+
+impl ScatterRay for MicrofacetBRDF {
+    /// Choose randomly between the specular and the diffuse lobe,\
+    /// then reflect the incoming ray on a visible microfacet normal
+    /// or scatter it on the semi-sphere.
+    fn scatter_ray(
+        &self,
+        pcg: (f32, f32),
+        incoming_dir: Vector,
+        interaction_point: Point,
+        normal: Normal,
+        _inside: bool,
+        depth: u32,
+    ) -> Ray {
+        let specular_probability = self.specular_probability();
+        let dir = if pcg.0 < specular_probability {
+            let offset = pcg.0 / specular_probability;
+            sample_ggx_reflection(
+                normal,
+                incoming_dir.normalize().neg(),
+                self.alpha(),
+                (offset, pcg.1),
+            )
+        } else {
+            let offset = (pcg.0 - specular_probability) / (1.0 - specular_probability);
+            cosine_weighted_direction(normal, (offset, pcg.1))
+        };
+        Ray {
+            origin: interaction_point,
+            dir,
+            tmin: 1.0e-3,
+            depth,
+            ..Default::default()
+        }
+    }
+
+    fn scatter_pdf(&self, normal: Normal, incoming_dir: Vector, out_dir: Vector) -> Option<f32> {
+        let normal = Vector::from(normal).normalize();
+        let (in_dir, out_dir) = (out_dir.normalize(), incoming_dir.normalize().neg());
+        let cos_in = normal.dot(in_dir);
+        if cos_in <= 0.0 || normal.dot(out_dir) <= 0.0 {
+            return Some(0.0);
+        }
+        let specular = ggx_reflection(normal, in_dir, out_dir, self.alpha()).1;
+        let specular_probability = self.specular_probability();
+        Some(specular_probability * specular + (1.0 - specular_probability) * cos_in / PI)
+    }
+}
+
+/// Index of refraction of the coat of [`CoatedBRDF`] by default (e.g. varnish).
+const COAT_IOR: f32 = 1.5;
+/// Minimum probability of sampling the reflection of a rough coat,
+/// whose Fresnel reflectance is low at normal incidence.
+const MIN_COAT_PROBABILITY: f32 = 0.25;
+
+/// A class representing a layered BRDF, a clear dielectric coat over a base [`BRDF`]
+/// (e.g. car paint, varnished wood, glossy plastic).
+///
+/// The coat reflects light by its Fresnel reflectance, with a GGX distribution
+/// of microfacet normals (smooth for zero `roughness`),
+/// the base scatters the light transmitted through the coat in and out.\
+/// Any [`BRDF`] can be a base, even another coated one.
+/// Over a delta base (e.g. [`SpecularBRDF`]) the whole BRDF is a delta distribution,
+/// the uncolored coat reflection and the base are told apart by
+/// [`scatter_weight`](trait.ScatterRay.html#method.scatter_weight).
+#[derive(Clone, Debug)]
+pub struct CoatedBRDF {
+    /// The [`BRDF`] under the coat.
+    pub base: Box<BRDF>,
+    /// Index of refraction of the coat.
+    pub ior: f32,
+    /// Perceptual roughness of the coat in `[0, 1]`, its square is the width of the distribution.
+    pub roughness: f32,
+}
+
+impl Default for CoatedBRDF {
+    fn default() -> Self {
+        Self {
+            base: Box::new(BRDF::Diffuse(DiffuseBRDF::default())),
+            ior: COAT_IOR,
+            roughness: 0.0,
+        }
+    }
+}
+
+impl CoatedBRDF {
+    /// Width of the microfacet distribution of the coat.
+    fn alpha(&self) -> f32 {
+        (self.roughness * self.roughness).max(MIN_ALPHA)
+    }
+
+    /// Probability of sampling the coat reflection instead of the base,
+    /// for an observer at `cos_out` from the surface normal.
+    fn coat_probability(&self, cos_out: f32) -> f32 {
+        let fresnel = schlick(cos_out.clamp(0.0, 1.0), self.ior);
+        if self.base.is_delta() {
+            fresnel
+        } else {
+            fresnel.max(MIN_COAT_PROBABILITY)
+        }
+    }
+}
+
+impl GetColor for CoatedBRDF {
+    fn get_color(&self, uv: Vector2D) -> Color {
+        self.base.get_color(uv)
+    }
+
+    fn filtered_color(&self, uv: Vector2D, point: Point, footprint: Footprint) -> Color {
+        self.base.filtered_color(uv, point, footprint)
+    }
+}
+
+impl Eval for CoatedBRDF {
+    fn eval(
+        &self,
+        normal: Normal,
+        in_dir: Vector,
+        out_dir: Vector,
+        uv: Vector2D,
+        point: Point,
+        footprint: Footprint,
+    ) -> Color {
+        if self.base.is_delta() {
+            return BLACK;
+        }
+        let base = self
+            .base
+            .eval(normal, in_dir, out_dir, uv, point, footprint);
+        let normal = Vector::from(normal).normalize();
+        let (in_dir, out_dir) = (in_dir.normalize(), out_dir.normalize());
+        let (cos_in, cos_out) = (normal.dot(in_dir), normal.dot(out_dir));
+        if cos_in <= 0.0 || cos_out <= 0.0 {
+            return BLACK;
+        }
+        let cos_half = out_dir.dot((in_dir + out_dir).normalize()).max(0.0);
+        let coat =
+            schlick(cos_half, self.ior) * ggx_reflection(normal, in_dir, out_dir, self.alpha()).0;
+        // Energy not reflected by the coat, in both ways.
+        let transmittance = (1.0 - schlick(cos_in, self.ior)) * (1.0 - schlick(cos_out, self.ior));
+        WHITE * coat + base * transmittance
+    }
+}
+
+impl ScatterRay for CoatedBRDF {
+    /// Choose randomly between the coat and the base, by the Fresnel reflectance of the coat,\
+    /// then reflect the incoming ray on the coat or scatter it with the base.
+    ///
+    /// A ray `inside` a delta base (e.g. [`DielectricBRDF`]) is scattered by the base only.
+    fn scatter_ray(
+        &self,
+        pcg: (f32, f32),
+        incoming_dir: Vector,
+        interaction_point: Point,
+        normal: Normal,
+        inside: bool,
+        depth: u32,
+    ) -> Ray {
+        if inside && self.base.is_delta() {
+            return self.base.scatter_ray(
+                pcg,
+                incoming_dir,
+                interaction_point,
+                normal,
+                inside,
+                depth,
+            );
+        }
+        let out_dir = incoming_dir.normalize().neg();
+        let cos_out = Vector::from(normal).normalize().dot(out_dir);
+        let coat_probability = self.coat_probability(cos_out);
+        if pcg.0 >= coat_probability {
+            let offset = (pcg.0 - coat_probability) / (1.0 - coat_probability);
+            return self.base.scatter_ray(
+                (offset, pcg.1),
+                incoming_dir,
+                interaction_point,
+                normal,
+                inside,
+                depth,
+            );
+        }
+        let offset = pcg.0 / coat_probability;
+        let dir = sample_ggx_reflection(normal, out_dir, self.alpha(), (offset, pcg.1));
+        Ray {
+            origin: interaction_point,
+            dir,
+            tmin: 1.0e-3,
+            depth,
+            ..Default::default()
+        }
+    }
+
+    fn scatter_pdf(&self, normal: Normal, incoming_dir: Vector, out_dir: Vector) -> Option<f32> {
+        let base = self.base.scatter_pdf(normal, incoming_dir, out_dir)?;
+        let normal = Vector::from(normal).normalize();
+        let (in_dir, out_dir) = (out_dir.normalize(), incoming_dir.normalize().neg());
+        let cos_out = normal.dot(out_dir);
+        if normal.dot(in_dir) <= 0.0 || cos_out <= 0.0 {
+            return Some(0.0);
+        }
+        let coat = ggx_reflection(normal, in_dir, out_dir, self.alpha()).1;
+        let coat_probability = self.coat_probability(cos_out);
+        Some(coat_probability * coat + (1.0 - coat_probability) * base)
+    }
+
+    /// Replay the choice of [`scatter_ray`](#method.scatter_ray) from the same `pcg` numbers:\
+    /// the coat reflection is uncolored, while the light scattered back by the base
+    /// is weighted by the base and by the coat transmittance on the way out.
+    fn scatter_weight(
+        &self,
+        pcg: (f32, f32),
+        normal: Normal,
+        incoming_dir: Vector,
+        out_dir: Vector,
+        inside: bool,
+        color: Color,
+    ) -> Color {
+        if inside && self.base.is_delta() {
+            return self
+                .base
+                .scatter_weight(pcg, normal, incoming_dir, out_dir, inside, color);
+        }
+        let normal_vec = Vector::from(normal).normalize();
+        let (in_dir, out_dir) = (out_dir.normalize(), incoming_dir.normalize().neg());
+        let (cos_in, cos_out) = (normal_vec.dot(in_dir), normal_vec.dot(out_dir));
+        let coat_probability = self.coat_probability(cos_out);
+        if pcg.0 >= coat_probability {
+            let offset = (pcg.0 - coat_probability) / (1.0 - coat_probability);
+            let base = self.base.scatter_weight(
+                (offset, pcg.1),
+                normal,
+                incoming_dir,
+                in_dir,
+                inside,
+                color,
+            );
+            // Light refracted by the base doesn't cross the coat again.
+            return if cos_in > 0.0 {
+                base * (1.0 - schlick(cos_in, self.ior))
+            } else {
+                base
+            };
+        }
+        if cos_in <= 0.0 || cos_out <= 0.0 {
+            return BLACK;
+        }
+        // Visible normals sampling leaves the Fresnel reflectance and Smith shadowing.
+        let cos_half = out_dir.dot((in_dir + out_dir).normalize()).max(0.0);
+        let (lambda_in, lambda_out) = (
+            smith_lambda(cos_in, self.alpha()),
+            smith_lambda(cos_out, self.alpha()),
+        );
+        let shadowing = (1.0 + lambda_out) / (1.0 + lambda_in + lambda_out);
+        WHITE * (schlick(cos_half, self.ior) * shadowing / coat_probability)
+    }
 }
 
 /// Enum of BRDFs.
@@ -250,14 +1094,70 @@ impl ScatterRay for SpecularBRDF {
 pub enum BRDF {
     Diffuse(DiffuseBRDF),
     Specular(SpecularBRDF),
+    Dielectric(DielectricBRDF),
+    Microfacet(MicrofacetBRDF),
+    Coated(CoatedBRDF),
+}
+
+impl BRDF {
+    /// Return `true` if a particular [`BRDF`] variant is a delta distribution
+    /// (see [`scatter_pdf`](trait.ScatterRay.html#tymethod.scatter_pdf)),
+    /// that can't be sampled towards a given direction.
+    pub fn is_delta(&self) -> bool {
+        match self {
+            BRDF::Specular(_) | BRDF::Dielectric(_) => true,
+            BRDF::Coated(coated) => coated.base.is_delta(),
+            _ => false,
+        }
+    }
+
+    /// Return an estimate of the fraction of light reflected by a particular [`BRDF`] variant
+    /// with a pigment of `color`, its maximum over the color components.
+    ///
+    /// Used by Russian roulette, since glossy BRDFs reflect light even with a black pigment.
+    pub fn albedo(&self, color: Color) -> f32 {
+        let albedo = match self {
+            BRDF::Microfacet(microfacet) => {
+                // Specular reflectance at normal incidence, plus the diffuse base.
+                let dielectric = schlick(1.0, MICROFACET_IOR);
+                WHITE * (dielectric * (1.0 - microfacet.metallic))
+                    + color * microfacet.metallic
+                    + color * ((1.0 - microfacet.metallic) * (1.0 - dielectric))
+            },
+            BRDF::Coated(coated) => {
+                // The coat reflects at least its reflectance at normal incidence.
+                let coat = schlick(1.0, coated.ior);
+                return coat + (1.0 - coat) * coated.base.albedo(color);
+            },
+            _ => color,
+        };
+        albedo.r.max(albedo.g.max(albedo.b))
+    }
 }
 
 impl Eval for BRDF {
     /// Eval a particular [`BRDF`] variant.
-    fn eval(&self, normal: Normal, in_dir: Vector, out_dir: Vector, uv: Vector2D) -> Color {
+    fn eval(
+        &self,
+        normal: Normal,
+        in_dir: Vector,
+        out_dir: Vector,
+        uv: Vector2D,
+        point: Point,
+        footprint: Footprint,
+    ) -> Color {
         match self {
-            BRDF::Diffuse(diffuse) => diffuse.eval(normal, in_dir, out_dir, uv),
-            BRDF::Specular(specular) => specular.eval(normal, in_dir, out_dir, uv),
+            BRDF::Diffuse(diffuse) => diffuse.eval(normal, in_dir, out_dir, uv, point, footprint),
+            BRDF::Specular(specular) => {
+                specular.eval(normal, in_dir, out_dir, uv, point, footprint)
+            },
+            BRDF::Dielectric(dielectric) => {
+                dielectric.eval(normal, in_dir, out_dir, uv, point, footprint)
+            },
+            BRDF::Microfacet(microfacet) => {
+                microfacet.eval(normal, in_dir, out_dir, uv, point, footprint)
+            },
+            BRDF::Coated(coated) => coated.eval(normal, in_dir, out_dir, uv, point, footprint),
         }
     }
 }
@@ -270,17 +1170,56 @@ impl ScatterRay for BRDF {
         incoming_dir: Vector,
         interaction_point: Point,
         normal: Normal,
+        inside: bool,
         depth: u32,
     ) -> Ray {
         match self {
             BRDF::Diffuse(diffuse) => {
-                diffuse.scatter_ray(pcg, incoming_dir, interaction_point, normal, depth)
+                diffuse.scatter_ray(pcg, incoming_dir, interaction_point, normal, inside, depth)
             },
             BRDF::Specular(specular) => {
-                specular.scatter_ray(pcg, incoming_dir, interaction_point, normal, depth)
+                specular.scatter_ray(pcg, incoming_dir, interaction_point, normal, inside, depth)
+            },
+            BRDF::Dielectric(dielectric) => {
+                dielectric.scatter_ray(pcg, incoming_dir, interaction_point, normal, inside, depth)
+            },
+            BRDF::Microfacet(microfacet) => {
+                microfacet.scatter_ray(pcg, incoming_dir, interaction_point, normal, inside, depth)
+            },
+            BRDF::Coated(coated) => {
+                coated.scatter_ray(pcg, incoming_dir, interaction_point, normal, inside, depth)
             },
         }
     }
+
+    /// Return the scattering probability density of a particular [`BRDF`] variant.
+    fn scatter_pdf(&self, normal: Normal, incoming_dir: Vector, out_dir: Vector) -> Option<f32> {
+        match self {
+            BRDF::Diffuse(diffuse) => diffuse.scatter_pdf(normal, incoming_dir, out_dir),
+            BRDF::Specular(specular) => specular.scatter_pdf(normal, incoming_dir, out_dir),
+            BRDF::Dielectric(dielectric) => dielectric.scatter_pdf(normal, incoming_dir, out_dir),
+            BRDF::Microfacet(microfacet) => microfacet.scatter_pdf(normal, incoming_dir, out_dir),
+            BRDF::Coated(coated) => coated.scatter_pdf(normal, incoming_dir, out_dir),
+        }
+    }
+
+    /// Return the weight of a [`Ray`] scattered by a particular delta [`BRDF`] variant.
+    fn scatter_weight(
+        &self,
+        pcg: (f32, f32),
+        normal: Normal,
+        incoming_dir: Vector,
+        out_dir: Vector,
+        inside: bool,
+        color: Color,
+    ) -> Color {
+        match self {
+            BRDF::Coated(coated) => {
+                coated.scatter_weight(pcg, normal, incoming_dir, out_dir, inside, color)
+            },
+            _ => color,
+        }
+    }
 }
 
 impl GetColor for BRDF {
@@ -289,6 +1228,20 @@ impl GetColor for BRDF {
         match self {
             BRDF::Diffuse(diffuse) => diffuse.get_color(uv),
             BRDF::Specular(specular) => specular.get_color(uv),
+            BRDF::Dielectric(dielectric) => dielectric.get_color(uv),
+            BRDF::Microfacet(microfacet) => microfacet.get_color(uv),
+            BRDF::Coated(coated) => coated.get_color(uv),
+        }
+    }
+
+    /// Return a different filtered Color as function of Pigment variant.
+    fn filtered_color(&self, uv: Vector2D, point: Point, footprint: Footprint) -> Color {
+        match self {
+            BRDF::Diffuse(diffuse) => diffuse.filtered_color(uv, point, footprint),
+            BRDF::Specular(specular) => specular.filtered_color(uv, point, footprint),
+            BRDF::Dielectric(dielectric) => dielectric.filtered_color(uv, point, footprint),
+            BRDF::Microfacet(microfacet) => microfacet.filtered_color(uv, point, footprint),
+            BRDF::Coated(coated) => coated.filtered_color(uv, point, footprint),
         }
     }
 }
@@ -311,10 +1264,25 @@ impl Default for Material {
     }
 }
 
+impl Material {
+    /// Return `true` if the material could emit light,
+    /// i.e. its emitted pigment is not uniformly [`BLACK`].
+    pub fn is_emissive(&self) -> bool {
+        match &self.emitted_radiance {
+            Pigment::Uniform(uniform) => uniform.color != BLACK,
+            Pigment::Checkered(checkered) => checkered.color1 != BLACK || checkered.color2 != BLACK,
+            Pigment::Image(_) => true,
+            Pigment::Noise(noise) => noise.color1 != BLACK || noise.color2 != BLACK,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::{
+        misc::IsClose,
+        noise::Basis,
         normal::{E1, E2, E3},
         random::Pcg,
         vector::{E1 as vE1, E2 as vE2, E3 as vE3},
@@ -347,6 +1315,136 @@ mod test {
         assert_eq!(image.get_color(Vector2D { u: 1.0, v: 1.0 }), WHITE)
     }
 
+    #[test]
+    fn test_image_pigment_filter_wrap() {
+        let mut hdr_img = HdrImage::new(2, 2);
+        hdr_img.set_pixel(1, 0, WHITE).unwrap();
+        hdr_img.set_pixel(1, 1, WHITE).unwrap();
+        let mut image = ImagePigment::new(hdr_img);
+
+        // Nearest filter with clamp, repeat and mirror wrap.
+        assert_eq!(image.get_color(Vector2D { u: 1.2, v: 0.5 }), WHITE);
+        assert_eq!(image.get_color(Vector2D { u: -0.7, v: 0.5 }), BLACK);
+        image.wrap = Wrap::Repeat;
+        assert_eq!(image.get_color(Vector2D { u: 1.2, v: 0.5 }), BLACK);
+        assert_eq!(image.get_color(Vector2D { u: -0.3, v: 0.5 }), WHITE);
+        image.wrap = Wrap::Mirror;
+        assert_eq!(image.get_color(Vector2D { u: 1.2, v: 0.5 }), WHITE);
+        assert_eq!(image.get_color(Vector2D { u: 1.7, v: 0.5 }), BLACK);
+        assert_eq!(Wrap::Mirror.wrap(-1, 2), 0);
+        assert_eq!(Wrap::Mirror.wrap(5, 2), 1);
+
+        // Bilinear filter interpolates between texel centers.
+        image.wrap = Wrap::Clamp;
+        image.filter = Filter::Bilinear;
+        assert!(image
+            .get_color(Vector2D { u: 0.5, v: 0.5 })
+            .is_close(WHITE * 0.5));
+        assert!(image
+            .get_color(Vector2D { u: 0.375, v: 0.1 })
+            .is_close(WHITE * 0.25));
+        assert_eq!(image.get_color(Vector2D { u: 0.1, v: 0.9 }), BLACK);
+
+        // Bicubic filter interpolates texel centers, with no overshoot below zero.
+        image.filter = Filter::Bicubic;
+        assert!(image
+            .get_color(Vector2D { u: 0.75, v: 0.25 })
+            .is_close(WHITE));
+        assert!(image
+            .get_color(Vector2D { u: 0.5, v: 0.5 })
+            .is_close(WHITE * 0.5));
+        assert!(image
+            .get_color(Vector2D { u: 0.3, v: 0.5 })
+            .into_iter()
+            .all(|el| el >= 0.0));
+        assert!(catmull_rom(0.3).iter().sum::<f32>().is_close(1.0));
+    }
+
+    #[test]
+    fn test_filtered_color() {
+        let mut hdr_img = HdrImage::new(4, 4);
+        hdr_img
+            .set_pixels(
+                (0..16)
+                    .map(|i| if (i + i / 4) % 2 == 0 { WHITE } else { BLACK })
+                    .collect(),
+            )
+            .unwrap();
+        let image = ImagePigment::new(hdr_img);
+        let checkered = CheckeredPigment {
+            color1: BLACK,
+            color2: WHITE,
+            steps: 10,
+        };
+        let footprint = |d: f32| Footprint {
+            duv_dx: Vector2D { u: d, v: 0.0 },
+            duv_dy: Vector2D { u: 0.0, v: d },
+        };
+        let uv = Vector2D { u: 0.33, v: 0.64 };
+
+        // Footprints smaller than texels are point sampled.
+        let pigment = Pigment::Image(image.clone());
+        assert_eq!(
+            pigment.filtered_color(uv, Point::default(), Footprint::default()),
+            pigment.get_color(uv)
+        );
+        assert_eq!(
+            pigment.filtered_color(uv, Point::default(), footprint(0.01)),
+            pigment.get_color(uv)
+        );
+        assert!(pigment
+            .filtered_color(uv, Point::default(), footprint(4.0))
+            .is_close(WHITE * 0.5));
+        // Two texels wide footprint looks up the 2x2 level, where texels are averaged.
+        assert!(image
+            .filtered_color(uv, Point::default(), footprint(0.5))
+            .is_close(WHITE * 0.5));
+        assert!(image
+            .filtered_color(uv, Point::default(), footprint(0.25))
+            .into_iter()
+            .all(|el| (0.0..=1.0).contains(&el)));
+        // Other pigments are never filtered, not to change the look of existing scenes.
+        let checkered = Pigment::Checkered(checkered);
+        assert_eq!(
+            checkered.filtered_color(uv, Point::default(), footprint(4.0)),
+            checkered.get_color(uv)
+        );
+    }
+
+    #[test]
+    fn test_noise_pigment() {
+        let noise = Noise::new(Basis::Perlin, 42);
+        let point = Point::from((0.3, 0.7, 0.2));
+        let uv = Vector2D { u: 0.3, v: 0.7 };
+        for pattern in [
+            Pattern::Noise,
+            Pattern::Fbm,
+            Pattern::Turbulence,
+            Pattern::Marble,
+            Pattern::Wood,
+        ] {
+            let mut pigment = NoisePigment::new(pattern, BLACK, WHITE, 3.0, 4, noise.clone());
+            let color = pigment.filtered_color(uv, point, Footprint::default());
+            // Colors are blended, same for each component.
+            assert!((0.0..=1.0).contains(&color.r));
+            assert!(color.r == color.g && color.g == color.b);
+            // Object space is the default, surface coordinates are `(u, v, 0)`.
+            assert_eq!(color, pigment.color(point));
+            pigment.space = Space::Uv;
+            assert_eq!(
+                pigment.filtered_color(uv, point, Footprint::default()),
+                pigment.get_color(uv)
+            );
+            assert_eq!(
+                pigment.get_color(uv),
+                pigment.color(Point::from((0.3, 0.7, 0.0)))
+            );
+        }
+        // Wood rings are concentric around `z` axis.
+        let wood = NoisePigment::new(Pattern::Wood, BLACK, WHITE, 1.0, 1, noise);
+        assert!(wood.color(Point::from((0.0, 0.0, 0.0))).is_close(BLACK));
+    }
+
     #[test]
     fn test_brdf() {
         let diff_brdf = BRDF::Diffuse(DiffuseBRDF::default());
@@ -359,12 +1457,32 @@ mod test {
             matches!(&spec_brdf, BRDF::Specular(spec) if (spec.get_color(uv), spec.threshold_angle_rad) == (WHITE, PI/1800.))
         );
 
-        assert_eq!(diff_brdf.eval(E1, vE2, vE3, uv), WHITE * (1.0 / PI));
         assert_eq!(
-            spec_brdf.eval(E3, vE1 + vE2 + vE3, vE1 + vE2 + vE3, uv),
+            diff_brdf.eval(E1, vE2, vE3, uv, Point::default(), Footprint::default()),
+            WHITE * (1.0 / PI)
+        );
+        assert_eq!(
+            spec_brdf.eval(
+                E3,
+                vE1 + vE2 + vE3,
+                vE1 + vE2 + vE3,
+                uv,
+                Point::default(),
+                Footprint::default()
+            ),
             WHITE
         );
-        assert_eq!(spec_brdf.eval(E2, vE1 + vE2 + vE3, vE1 + vE3, uv), BLACK);
+        assert_eq!(
+            spec_brdf.eval(
+                E2,
+                vE1 + vE2 + vE3,
+                vE1 + vE3,
+                uv,
+                Point::default(),
+                Footprint::default()
+            ),
+            BLACK
+        );
         assert_eq!(
             spec_brdf
                 .scatter_ray(
@@ -372,6 +1490,7 @@ mod test {
                     Vector::from((-1.0, 0.0, -1.0)),
                     Point::default(),
                     E3,
+                    false,
                     10
                 )
                 .dir,
@@ -393,6 +1512,7 @@ mod test {
                         vE2,
                         Point::default(),
                         E3,
+                        false,
                         10,
                     )
                     .dir
@@ -403,4 +1523,187 @@ mod test {
             sum.x.abs() <= eps && sum.y.abs() <= eps && (sum.z.abs() - (2.0 / 3.0)).abs() <= eps
         )
     }
+
+    #[test]
+    fn test_dielectric_brdf() {
+        let glass = BRDF::Dielectric(DielectricBRDF::default());
+        let uv = Vector2D { u: 0., v: 0. };
+        assert!(matches!(&glass, BRDF::Dielectric(glass) if glass.get_color(uv)==WHITE));
+        assert_eq!(
+            glass.eval(E3, vE3, vE3, uv, Point::default(), Footprint::default()),
+            BLACK
+        );
+
+        // Normal incidence from outside: 4% reflected, otherwise straight transmitted.
+        let reflected = glass.scatter_ray((0.01, 0.), vE3.neg(), Point::default(), E3, false, 1);
+        assert!(reflected.dir.is_close(vE3));
+        let refracted = glass.scatter_ray((0.5, 0.), vE3.neg(), Point::default(), E3, false, 1);
+        assert!(refracted.dir.is_close(vE3.neg()));
+
+        // Snell's law entering at 45 degrees: sin(theta_t) = sin(45) / 1.5.
+        let incoming = (vE1 - vE3).normalize();
+        let refracted = glass.scatter_ray((0.99, 0.), incoming, Point::default(), E3, false, 1);
+        let sin_t = f32::sqrt(0.5) / 1.5;
+        assert!(refracted
+            .dir
+            .is_close(Vector::from((sin_t, 0., -f32::sqrt(1. - sin_t * sin_t)))));
+
+        // Leaving at 60 degrees: beyond critical angle asin(1/1.5), always reflected.
+        let incoming = Vector::from((f32::sqrt(3.) / 2., 0., -0.5));
+        let reflected = glass.scatter_ray((0.99, 0.), incoming, Point::default(), E3, true, 1);
+        assert!(reflected
+            .dir
+            .is_close(Vector::from((f32::sqrt(3.) / 2., 0., 0.5))));
+    }
+
+    /// Estimate the albedo (green component) of `brdf` around [`E3`] normal,
+    /// for an observer along `incoming`, with `num` samples drawn by the BRDF
+    /// and as many drawn uniformly on the semi-sphere.
+    fn estimate_albedo(brdf: &BRDF, incoming: Vector, num: u32, pcg: &mut Pcg) -> (f32, f32) {
+        let uv = Vector2D { u: 0., v: 0. };
+        let footprint = Footprint::default();
+        let (mut sampled, mut uniform) = (0., 0.);
+        for _ in 0..num {
+            let dir = brdf
+                .scatter_ray(
+                    (pcg.random_float(), pcg.random_float()),
+                    incoming,
+                    Point::default(),
+                    E3,
+                    false,
+                    1,
+                )
+                .dir;
+            let pdf = brdf.scatter_pdf(E3, incoming, dir).unwrap();
+            if pdf > 0. {
+                let f = brdf.eval(E3, dir, incoming.neg(), uv, Point::default(), footprint);
+                sampled += f.g * dir.normalize().z / pdf;
+            }
+            let (z, phi) = (pcg.random_float(), 2. * PI * pcg.random_float());
+            let r = (1. - z * z).sqrt();
+            let dir = Vector::from((r * phi.cos(), r * phi.sin(), z));
+            let f = brdf.eval(E3, dir, incoming.neg(), uv, Point::default(), footprint);
+            uniform += f.g * z * 2. * PI;
+        }
+        (sampled / num as f32, uniform / num as f32)
+    }
+
+    #[test]
+    fn test_microfacet_brdf() {
+        let mut pcg = Pcg::default();
+        let uv = Vector2D { u: 0., v: 0. };
+        let footprint = Footprint::default();
+        let incoming = Vector::from((1., 0., -2.)).normalize();
+        let num = 20000;
+        for (roughness, metallic) in [(0.3, 0.), (0.6, 1.), (0.8, 0.5)] {
+            let brdf = BRDF::Microfacet(MicrofacetBRDF {
+                pigment: Pigment::Uniform(UniformPigment {
+                    color: Color::from((0.9, 0.6, 0.3)),
+                }),
+                roughness,
+                metallic,
+            });
+            assert!(!brdf.is_delta());
+            // Albedo estimated by sampling the BRDF or the semi-sphere uniformly.
+            let (sampled, uniform) = estimate_albedo(&brdf, incoming, num, &mut pcg);
+            assert!((sampled - uniform).abs() < 0.05 * uniform);
+            assert!(sampled < 1.);
+        }
+
+        // An almost smooth metal reflects like a mirror.
+        let metal = BRDF::Microfacet(MicrofacetBRDF {
+            roughness: 0.01,
+            metallic: 1.,
+            ..Default::default()
+        });
+        let reflected = metal.scatter_ray((0.3, 0.6), incoming, Point::default(), E3, false, 1);
+        let mirror = Vector::from((1., 0., 2.)).normalize();
+        assert!(reflected.dir.normalize().dot(mirror) > 0.9999);
+        // Nothing is reflected below the surface.
+        assert_eq!(
+            metal.eval(
+                E3,
+                vE3.neg(),
+                incoming.neg(),
+                uv,
+                Point::default(),
+                footprint
+            ),
+            BLACK
+        );
+        assert_eq!(metal.scatter_pdf(E3, incoming, vE3.neg()), Some(0.));
+        assert!(BRDF::Specular(SpecularBRDF::default()).is_delta());
+        assert_eq!(
+            BRDF::Dielectric(DielectricBRDF::default()).scatter_pdf(E3, incoming, vE3),
+            None
+        );
+    }
+
+    #[test]
+    fn test_coated_brdf() {
+        let mut pcg = Pcg::default();
+        let uv = Vector2D { u: 0., v: 0. };
+        let footprint = Footprint::default();
+        let incoming = Vector::from((1., 0., -2.)).normalize();
+        let num = 20000;
+        let diffuse = BRDF::Diffuse(DiffuseBRDF {
+            pigment: Pigment::Uniform(UniformPigment { color: WHITE }),
+        });
+        let metal = BRDF::Microfacet(MicrofacetBRDF {
+            roughness: 0.6,
+            metallic: 1.,
+            ..Default::default()
+        });
+        let varnish = BRDF::Coated(CoatedBRDF {
+            base: Box::new(diffuse.clone()),
+            ior: 1.3,
+            roughness: 0.5,
+        });
+        for (base, roughness) in [(diffuse, 0.3), (metal, 0.4), (varnish, 0.6)] {
+            let brdf = BRDF::Coated(CoatedBRDF {
+                base: Box::new(base),
+                ior: 1.5,
+                roughness,
+            });
+            assert!(!brdf.is_delta());
+            // Albedo estimated by sampling the BRDF or the semi-sphere uniformly.
+            let (sampled, uniform) = estimate_albedo(&brdf, incoming, num, &mut pcg);
+            assert!((sampled - uniform).abs() < 0.05 * uniform);
+            // The coat conserves energy, and mostly transmits it to a white base.
+            assert!(sampled < 1. && sampled > 0.75);
+        }
+
+        // An almost smooth coat over a black base reflects like a mirror.
+        let lacquer = BRDF::Coated(CoatedBRDF {
+            base: Box::new(BRDF::Diffuse(DiffuseBRDF::default())),
+            ..Default::default()
+        });
+        let mirror = Vector::from((1., 0., 2.)).normalize();
+        let reflected = lacquer.scatter_ray((0.1, 0.6), incoming, Point::default(), E3, false, 1);
+        assert!(reflected.dir.normalize().dot(mirror) > 0.9999);
+        let diffused = lacquer.scatter_ray((0.9, 0.6), incoming, Point::default(), E3, false, 1);
+        assert!(diffused.dir.normalize().dot(mirror) < 0.9999);
+
+        // A coat over a delta base is a delta distribution too.
+        let glazed = BRDF::Coated(CoatedBRDF {
+            base: Box::new(BRDF::Dielectric(DielectricBRDF::default())),
+            ..Default::default()
+        });
+        assert!(glazed.is_delta());
+        assert_eq!(glazed.scatter_pdf(E3, incoming, mirror), None);
+        assert_eq!(
+            glazed.eval(E3, mirror, incoming.neg(), uv, Point::default(), footprint),
+            BLACK
+        );
+        let reflected = glazed.scatter_ray((0.01, 0.6), incoming, Point::default(), E3, false, 1);
+        assert!(reflected.dir.normalize().dot(mirror) > 0.9999);
+        let refracted = glazed.scatter_ray((0.5, 0.6), incoming, Point::default(), E3, false, 1);
+        assert!(refracted.dir.z < 0.);
+        // The coat reflection is uncolored, the base keeps its color.
+        let red = Color::from((1., 0., 0.));
+        let coat = glazed.scatter_weight((0.01, 0.6), E3, incoming, reflected.dir, false, red);
+        assert!(coat.g > 0. && coat.r == coat.g && coat.g == coat.b);
+        let base = glazed.scatter_weight((0.5, 0.6), E3, incoming, refracted.dir, false, red);
+        assert_eq!(base, red);
+    }
 }
@@ -0,0 +1,284 @@
+//! Triangle Mesh module.
+//!
+//! Provides [`Mesh`](struct@Mesh) struct, the vertex/normal/uv buffers shared
+//! between the triangles of a [`TriangleMesh`](../shape/struct.TriangleMesh.html),
+//! read from [Wavefront OBJ](https://en.wikipedia.org/wiki/Wavefront_.obj_file) files.
+use crate::{bvh::BoundingBox, error::MeshErr, misc::Vector2D, normal::Normal, point::Point};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    str::FromStr,
+};
+
+/// A triangle face of a [`Mesh`].
+///
+/// Each field holds indices inside the respective [`Mesh`] buffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Face {
+    /// Indices of the three vertices, counter-clockwise ordered.
+    pub vertices: [usize; 3],
+    /// Indices of the three vertex normals (smooth shading), if any.
+    pub normals: Option<[usize; 3]>,
+    /// Indices of the three vertex texture coordinates, if any.
+    pub uvs: Option<[usize; 3]>,
+}
+
+/// Triangle mesh struct.
+///
+/// A list of triangle [`Face`] that index shared buffers
+/// of vertices, normals and texture coordinates.
+#[derive(Debug, Default)]
+pub struct Mesh {
+    /// Vertex positions.
+    pub vertices: Vec<Point>,
+    /// Vertex normals.
+    pub normals: Vec<Normal>,
+    /// Vertex texture coordinates.
+    pub uvs: Vec<Vector2D>,
+    /// Triangle faces.
+    pub faces: Vec<Face>,
+}
+
+impl Mesh {
+    /// Return the [`BoundingBox`] of the `face`-th triangle.
+    pub fn face_bounding_box(&self, face: usize) -> BoundingBox {
+        let [i0, i1, i2] = self.faces[face].vertices;
+        BoundingBox::new(self.vertices[i0], self.vertices[i1])
+            .union(BoundingBox::new(self.vertices[i2], self.vertices[i2]))
+    }
+
+    /// Read a Wavefront OBJ mesh from `buf_reader`, a type that implements [`BufRead`] trait.
+    ///
+    /// Supported statements are `v`, `vt`, `vn` and `f`,
+    /// polygonal faces are split in triangles (fan triangulation),\
+    /// other statements (e.g. `o`, `g`, `s`, `usemtl`) are ignored.
+    ///
+    /// If an error occurs the result contains an [`MeshErr`] error variant.
+    fn read_obj<R: BufRead>(buf_reader: R) -> Result<Self, MeshErr> {
+        let mut mesh = Mesh::default();
+        for (line_num, line) in buf_reader.lines().enumerate() {
+            let line = line.map_err(MeshErr::ObjFileReadFailure)?;
+            let line_num = line_num + 1;
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                Some("v") => {
+                    let [x, y, z] = parse_floats(tokens, "vertex", line_num)?;
+                    mesh.vertices.push(Point::from((x, y, z)));
+                },
+                Some("vn") => {
+                    let [x, y, z] = parse_floats(tokens, "vertex normal", line_num)?;
+                    mesh.normals.push(Normal::from((x, y, z)).normalize());
+                },
+                Some("vt") => {
+                    let [u, v] = parse_floats(tokens, "texture coordinate", line_num)?;
+                    // OBJ `v` axis points upward,
+                    // while shapes surface `v` axis points downward.
+                    mesh.uvs.push(Vector2D { u, v: 1.0 - v });
+                },
+                Some("f") => {
+                    let polygon = tokens
+                        .map(|vertex| parse_face_vertex(vertex, &mesh, line_num))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if polygon.len() < 3 {
+                        return Err(MeshErr::InvalidObjFileFormat(format!(
+                            "face with less than three vertices at line {line_num}"
+                        )));
+                    }
+                    let has_normals = polygon.iter().all(|(_, _, n)| n.is_some());
+                    let has_uvs = polygon.iter().all(|(_, uv, _)| uv.is_some());
+                    for k in 1..polygon.len() - 1 {
+                        let triangle = [polygon[0], polygon[k], polygon[k + 1]];
+                        mesh.faces.push(Face {
+                            vertices: triangle.map(|(v, _, _)| v),
+                            normals: has_normals.then(|| triangle.map(|(_, _, n)| n.unwrap())),
+                            uvs: has_uvs.then(|| triangle.map(|(_, uv, _)| uv.unwrap())),
+                        });
+                    }
+                },
+                _ => (),
+            }
+        }
+        if mesh.faces.is_empty() {
+            return Err(MeshErr::InvalidObjFileFormat(String::from(
+                "no faces inside obj file",
+            )));
+        }
+        Ok(mesh)
+    }
+
+    /// Read a Wavefront OBJ mesh from `path`.
+    ///
+    /// Wrapper around [`read_obj`](#method.read_obj).
+    pub fn read_obj_file(path: &Path) -> Result<Self, MeshErr> {
+        let file = File::open(path).map_err(MeshErr::ObjFileReadFailure)?;
+        Mesh::read_obj(BufReader::new(file))
+    }
+}
+
+/// Parse the first `N` floats of an OBJ statement, extra values are ignored.
+///
+/// If parse fails the [`std::result::Result`] will be an [`MeshErr`] error variant.
+fn parse_floats<'a, I, const N: usize>(
+    mut tokens: I,
+    what: &str,
+    line_num: usize,
+) -> Result<[f32; N], MeshErr>
+where
+    I: Iterator<Item = &'a str>,
+{
+    let mut values = [0.0; N];
+    for value in values.iter_mut() {
+        let token = tokens.next().ok_or_else(|| {
+            MeshErr::InvalidObjFileFormat(format!("missing {what} component at line {line_num}"))
+        })?;
+        *value = f32::from_str(token).map_err(|e| {
+            MeshErr::ObjFloatParseFailure(e, format!("{what} component at line {line_num}"))
+        })?;
+    }
+    Ok(values)
+}
+
+/// Parse a face vertex of an OBJ `f` statement,
+/// with format `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+///
+/// Return the zero-based indices of vertex, texture coordinate and normal.\
+/// If parse fails the [`std::result::Result`] will be an [`MeshErr`] error variant.
+fn parse_face_vertex(
+    token: &str,
+    mesh: &Mesh,
+    line_num: usize,
+) -> Result<(usize, Option<usize>, Option<usize>), MeshErr> {
+    let mut indices = token.split('/');
+    let vertex =
+        parse_index(indices.next(), mesh.vertices.len(), "vertex", line_num)?.ok_or_else(|| {
+            MeshErr::InvalidObjFileFormat(format!("missing vertex index at line {line_num}"))
+        })?;
+    let uv = parse_index(
+        indices.next(),
+        mesh.uvs.len(),
+        "texture coordinate",
+        line_num,
+    )?;
+    let normal = parse_index(indices.next(), mesh.normals.len(), "normal", line_num)?;
+    Ok((vertex, uv, normal))
+}
+
+/// Parse an OBJ one-based (or negative, relative to the end) index
+/// into a zero-based index of a buffer of size `len`.
+///
+/// An empty index (e.g. `vt` in `v//vn`) returns [`None`].\
+/// If parse fails the [`std::result::Result`] will be an [`MeshErr`] error variant.
+fn parse_index(
+    token: Option<&str>,
+    len: usize,
+    what: &str,
+    line_num: usize,
+) -> Result<Option<usize>, MeshErr> {
+    match token {
+        None | Some("") => Ok(None),
+        Some(token) => {
+            let index = i64::from_str(token).map_err(|e| {
+                MeshErr::ObjIntParseFailure(e, format!("{what} index at line {line_num}"))
+            })?;
+            let index = if index < 0 {
+                len as i64 + index
+            } else {
+                index - 1
+            };
+            if index < 0 || index >= len as i64 {
+                Err(MeshErr::InvalidObjFileFormat(format!(
+                    "{what} index out of bounds at line {line_num}"
+                )))
+            } else {
+                Ok(Some(index as usize))
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::misc::IsClose;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_obj() {
+        let obj = concat!(
+            "# a unit square\n",
+            "o square\n",
+            "v 0 0 0\n",
+            "v 1 0 0\n",
+            "v 1 1 0\n",
+            "v 0 1 0\n",
+            "vt 0 0\n",
+            "vt 1 0\n",
+            "vt 1 1\n",
+            "vt 0 1\n",
+            "vn 0 0 2\n",
+            "s 1\n",
+            "f 1/1/1 2/2/1 3/3/1 4/4/1\n",
+            "f -4//-1 -3//-1 -2//-1\n",
+            "f 1 2 4\n",
+        );
+        let mesh = Mesh::read_obj(Cursor::new(obj)).unwrap();
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.uvs.len(), 4);
+        assert!(mesh.normals[0].is_close(Normal::from((0., 0., 1.))));
+        assert!(mesh.uvs[3].is_close(Vector2D { u: 0., v: 0. }));
+        assert_eq!(
+            mesh.faces,
+            vec![
+                Face {
+                    vertices: [0, 1, 2],
+                    normals: Some([0, 0, 0]),
+                    uvs: Some([0, 1, 2])
+                },
+                Face {
+                    vertices: [0, 2, 3],
+                    normals: Some([0, 0, 0]),
+                    uvs: Some([0, 2, 3])
+                },
+                Face {
+                    vertices: [0, 1, 2],
+                    normals: Some([0, 0, 0]),
+                    uvs: None
+                },
+                Face {
+                    vertices: [0, 1, 3],
+                    normals: None,
+                    uvs: None
+                },
+            ]
+        );
+        let bbox = mesh.face_bounding_box(1);
+        assert!(bbox.min.is_close(Point::from((0., 0., 0.))));
+        assert!(bbox.max.is_close(Point::from((1., 1., 0.))));
+
+        assert!(matches!(
+            Mesh::read_obj(Cursor::new("v 0 0\n")),
+            Err(MeshErr::InvalidObjFileFormat(_))
+        ));
+        assert!(matches!(
+            Mesh::read_obj(Cursor::new("v 0 0 a\n")),
+            Err(MeshErr::ObjFloatParseFailure(..))
+        ));
+        assert!(matches!(
+            Mesh::read_obj(Cursor::new("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n")),
+            Err(MeshErr::InvalidObjFileFormat(_))
+        ));
+        assert!(matches!(
+            Mesh::read_obj(Cursor::new("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 x\n")),
+            Err(MeshErr::ObjIntParseFailure(..))
+        ));
+        assert!(matches!(
+            Mesh::read_obj(Cursor::new("v 0 0 0\n")),
+            Err(MeshErr::InvalidObjFileFormat(_))
+        ));
+        assert!(matches!(
+            Mesh::read_obj_file(Path::new("/invalid/path.obj")),
+            Err(MeshErr::ObjFileReadFailure(_))
+        ));
+    }
+}
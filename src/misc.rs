@@ -42,6 +42,18 @@ impl IsClose for Vector2D {
     }
 }
 
+/// Footprint of a pixel on a shape's surface parametrization,
+/// as derivatives of `(u, v)` coordinates along image `x` and `y` directions.
+///
+/// A zero footprint (default) means a point sample.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Footprint {
+    /// Derivative of `(u, v)` along image `x` direction.
+    pub duv_dx: Vector2D,
+    /// Derivative of `(u, v)` along image `y` direction.
+    pub duv_dy: Vector2D,
+}
+
 /// Macro for wrap exit logic inside [`main`](fn.main.html).
 #[macro_export]
 macro_rules! exit {
@@ -67,8 +79,7 @@ macro_rules! exit {
 macro_rules! check {
     ($a:expr) => {
         match ImageFormat::from_path($a).map_err(HdrImageErr::LdrFileWriteFailure) {
-            Ok(ImageFormat::Png) => Ok(()),
-            Ok(ImageFormat::Farbfeld) => Ok(()),
+            Ok(format) if $crate::hdrimage::ldr_formats().contains(&format) => Ok(()),
             Ok(_) => Err(HdrImageErr::UnsupportedLdrFileFormat(String::from(
                 $a.extension().unwrap().to_str().unwrap_or(""),
             ))),
@@ -77,6 +88,29 @@ macro_rules! check {
     };
 }
 
+/// Macro for fail fast in [`main`](fn.main.html) subcommands
+/// (e.g. inside [`render`](fn.render.html)).
+///
+/// When unsupported hdr file is provided via cli fail immediately.
+///
+/// Same extensions accepted by
+/// [`write_hdr_file`](hdrimage/struct.HdrImage.html#method.write_hdr_file).
+#[macro_export]
+macro_rules! check_hdr {
+    ($a:expr) => {
+        match $a
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_lowercase()
+            .as_str()
+        {
+            "pfm" | "exr" | "hdr" => Ok(()),
+            ext => Err(HdrImageErr::UnsupportedHdrFileFormat(String::from(ext))),
+        }
+    };
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -0,0 +1,234 @@
+//! Noise module.
+//!
+//! Provides [`Noise`](struct@Noise) struct, a seedable 3D gradient noise
+//! with its fractal sums, used by procedural pigments.
+use crate::{point::Point, random::Pcg};
+
+/// Size of the permutation table, noise repeats with this period.
+const PERMUTATION_SIZE: usize = 256;
+/// Frequency multiplier between two consecutive octaves.
+const LACUNARITY: f32 = 2.0;
+/// Amplitude multiplier between two consecutive octaves.
+const GAIN: f32 = 0.5;
+/// Skewing factor of simplex noise, from cubic to simplicial grid.
+const SIMPLEX_F3: f32 = 1.0 / 3.0;
+/// Unskewing factor of simplex noise, from simplicial to cubic grid.
+const SIMPLEX_G3: f32 = 1.0 / 6.0;
+/// Gradient directions, the midpoints of the edges of a cube.
+const GRADIENTS: [(f32, f32, f32); 12] = [
+    (1.0, 1.0, 0.0),
+    (-1.0, 1.0, 0.0),
+    (1.0, -1.0, 0.0),
+    (-1.0, -1.0, 0.0),
+    (1.0, 0.0, 1.0),
+    (-1.0, 0.0, 1.0),
+    (1.0, 0.0, -1.0),
+    (-1.0, 0.0, -1.0),
+    (0.0, 1.0, 1.0),
+    (0.0, -1.0, 1.0),
+    (0.0, 1.0, -1.0),
+    (0.0, -1.0, -1.0),
+];
+
+/// Variants of gradient noise basis.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Basis {
+    /// [Improved Perlin](https://doi.org/10.1145/566654.566636) noise (Perlin, 2002).
+    #[default]
+    Perlin,
+    /// Simplex noise, see [Gustavson](https://weber.itn.liu.se/~stegu/simplexnoise/simplexnoise.pdf)
+    /// notes (2005).
+    Simplex,
+}
+
+/// A seedable 3D gradient noise.
+///
+/// Values are in `[-1, 1]`, smoothly varying on a unit scale.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Noise {
+    /// Noise basis.
+    pub basis: Basis,
+    /// Permutation table, shuffled by the seed.
+    permutation: Vec<usize>,
+}
+
+impl Noise {
+    /// Create a new [`Noise`] of `basis` variant,
+    /// with its permutation table shuffled by a [`Pcg`] seeded with `seed`.
+    pub fn new(basis: Basis, seed: u64) -> Self {
+        let mut pcg = Pcg::new(seed, 54);
+        let mut permutation: Vec<usize> = (0..PERMUTATION_SIZE).collect();
+        // Fisher-Yates shuffle.
+        for i in (1..PERMUTATION_SIZE).rev() {
+            permutation.swap(i, pcg.random() as usize % (i + 1));
+        }
+        Self { basis, permutation }
+    }
+
+    /// Return the gradient at lattice point `(i, j, k)`, dotted with `(x, y, z)`.
+    fn gradient(&self, (i, j, k): (i64, i64, i64), (x, y, z): (f32, f32, f32)) -> f32 {
+        let wrap = |index: i64| index.rem_euclid(PERMUTATION_SIZE as i64) as usize;
+        let hash = self.permutation[(wrap(i)
+            + self.permutation[(wrap(j) + self.permutation[wrap(k)]) % PERMUTATION_SIZE])
+            % PERMUTATION_SIZE];
+        let gradient = GRADIENTS[hash % GRADIENTS.len()];
+        gradient.0 * x + gradient.1 * y + gradient.2 * z
+    }
+
+    /// Return the noise value at `point`.
+    pub fn noise(&self, point: Point) -> f32 {
+        match self.basis {
+            Basis::Perlin => self.perlin(point),
+            Basis::Simplex => self.simplex(point),
+        }
+    }
+
+    /// Improved Perlin noise, interpolating the gradients of the 8 lattice points around `point`.
+    fn perlin(&self, point: Point) -> f32 {
+        let (x0, y0, z0) = (point.x.floor(), point.y.floor(), point.z.floor());
+        let (x, y, z) = (point.x - x0, point.y - y0, point.z - z0);
+        let (i, j, k) = (x0 as i64, y0 as i64, z0 as i64);
+        let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+        let lerp = |t: f32, a: f32, b: f32| a + t * (b - a);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+        let corner = |di: i64, dj: i64, dk: i64| {
+            self.gradient(
+                (i + di, j + dj, k + dk),
+                (x - di as f32, y - dj as f32, z - dk as f32),
+            )
+        };
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, corner(0, 0, 0), corner(1, 0, 0)),
+                lerp(u, corner(0, 1, 0), corner(1, 1, 0)),
+            ),
+            lerp(
+                v,
+                lerp(u, corner(0, 0, 1), corner(1, 0, 1)),
+                lerp(u, corner(0, 1, 1), corner(1, 1, 1)),
+            ),
+        )
+    }
+
+    /// Simplex noise, summing the radially attenuated gradients
+    /// of the 4 corners of the tetrahedron around `point`.
+    fn simplex(&self, point: Point) -> f32 {
+        // Skew the input space to find the simplicial cell.
+        let skew = (point.x + point.y + point.z) * SIMPLEX_F3;
+        let (i, j, k) = (
+            (point.x + skew).floor() as i64,
+            (point.y + skew).floor() as i64,
+            (point.z + skew).floor() as i64,
+        );
+        let unskew = (i + j + k) as f32 * SIMPLEX_G3;
+        let (x, y, z) = (
+            point.x - (i as f32 - unskew),
+            point.y - (j as f32 - unskew),
+            point.z - (k as f32 - unskew),
+        );
+        // Offsets of the second and third corners, ordering the coordinates.
+        let (second, third) = if x >= y {
+            if y >= z {
+                ((1, 0, 0), (1, 1, 0))
+            } else if x >= z {
+                ((1, 0, 0), (1, 0, 1))
+            } else {
+                ((0, 0, 1), (1, 0, 1))
+            }
+        } else if y < z {
+            ((0, 0, 1), (0, 1, 1))
+        } else if x < z {
+            ((0, 1, 0), (0, 1, 1))
+        } else {
+            ((0, 1, 0), (1, 1, 0))
+        };
+        [(0, 0, 0), second, third, (1, 1, 1)]
+            .into_iter()
+            .enumerate()
+            .map(|(n, (di, dj, dk))| {
+                let unskew = n as f32 * SIMPLEX_G3;
+                let (dx, dy, dz) = (
+                    x - di as f32 + unskew,
+                    y - dj as f32 + unskew,
+                    z - dk as f32 + unskew,
+                );
+                let t = 0.6 - dx * dx - dy * dy - dz * dz;
+                if t < 0.0 {
+                    0.0
+                } else {
+                    t.powi(4) * self.gradient((i + di, j + dj, k + dk), (dx, dy, dz))
+                }
+            })
+            .sum::<f32>()
+            * 32.0
+    }
+
+    /// Return the amplitude weighted sum of `octaves` noise values at `point`,
+    /// transformed by `map`, each octave doubling the frequency and halving the amplitude.
+    ///
+    /// The sum is normalized by the total amplitude.
+    fn octaves_sum(&self, point: Point, octaves: u32, map: impl Fn(f32) -> f32) -> f32 {
+        let (mut sum, mut total, mut amplitude, mut frequency) = (0.0, 0.0, 1.0, 1.0);
+        for _ in 0..octaves.max(1) {
+            sum += amplitude * map(self.noise(point * frequency));
+            total += amplitude;
+            amplitude *= GAIN;
+            frequency *= LACUNARITY;
+        }
+        sum / total
+    }
+
+    /// Return the fractional Brownian motion (fBm) at `point`,
+    /// the sum of `octaves` noise values, in `[-1, 1]`.
+    pub fn fbm(&self, point: Point, octaves: u32) -> f32 {
+        self.octaves_sum(point, octaves, |noise| noise)
+    }
+
+    /// Return the turbulence at `point`,
+    /// the sum of `octaves` absolute noise values, in `[0, 1]`.
+    pub fn turbulence(&self, point: Point, octaves: u32) -> f32 {
+        self.octaves_sum(point, octaves, f32::abs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::misc::IsClose;
+
+    #[test]
+    fn test_noise() {
+        for basis in [Basis::Perlin, Basis::Simplex] {
+            let noise = Noise::new(basis, 42);
+            let mut pcg = Pcg::default();
+            let points: Vec<Point> = (0..1000)
+                .map(|_| {
+                    Point::from((
+                        20.0 * pcg.random_float() - 10.0,
+                        20.0 * pcg.random_float() - 10.0,
+                        20.0 * pcg.random_float() - 10.0,
+                    ))
+                })
+                .collect();
+            for point in points.iter() {
+                assert!((-1.0..=1.0).contains(&noise.noise(*point)));
+                assert!((-1.0..=1.0).contains(&noise.fbm(*point, 5)));
+                assert!((0.0..=1.0).contains(&noise.turbulence(*point, 5)));
+            }
+            // Noise is not constant, but smooth.
+            assert!(points.iter().any(|point| noise.noise(*point).abs() > 0.2));
+            let point = Point::from((1.3, -2.7, 0.4));
+            assert!(noise.noise(point).is_close(noise.noise(point * 1.00001)));
+            // Same seed gives the same noise, different seeds different noises.
+            assert_eq!(Noise::new(basis, 42).noise(point), noise.noise(point));
+            assert_ne!(Noise::new(basis, 7).noise(point), noise.noise(point));
+            assert!(noise.fbm(point, 1).is_close(noise.noise(point)));
+        }
+        // Perlin noise vanishes on lattice points.
+        assert!(Noise::new(Basis::Perlin, 42)
+            .noise(Point::from((3.0, -1.0, 2.0)))
+            .is_close(0.0));
+    }
+}
@@ -42,6 +42,25 @@ impl Pcg {
         (xor_shifted >> rot) | (xor_shifted << ((-(rot as i64)) & 31))
     }
 
+    /// Advance the generator by `delta` steps, as if [`random`](#method.random)
+    /// was called `delta` times, but in `O(log(delta))` time.
+    ///
+    /// See Brown, "Random Number Generation with Arbitrary Stride" (1994).
+    pub fn advance(&mut self, mut delta: u64) {
+        let (mut acc_mult, mut acc_plus) = (1_u64, 0_u64);
+        let (mut cur_mult, mut cur_plus) = (6364136223846793005_u64, self.inc);
+        while delta > 0 {
+            if delta & 1 == 1 {
+                acc_mult = acc_mult.wrapping_mul(cur_mult);
+                acc_plus = acc_plus.wrapping_mul(cur_mult).wrapping_add(cur_plus);
+            }
+            cur_plus = cur_mult.wrapping_add(1).wrapping_mul(cur_plus);
+            cur_mult = cur_mult.wrapping_mul(cur_mult);
+            delta >>= 1;
+        }
+        self.state = acc_mult.wrapping_mul(self.state).wrapping_add(acc_plus);
+    }
+
     /// Generates a float random number.
     pub fn random_float(&mut self) -> f32 {
         self.random() as f32 / u32::MAX as f32
@@ -79,4 +98,17 @@ mod test {
             assert_eq!(pcg.random_float(), expected);
         }
     }
+
+    #[test]
+    fn test_advance() {
+        let mut pcg = Pcg::new(38, 62);
+        let mut jumped = pcg;
+        for _ in 0..1000 {
+            pcg.random();
+        }
+        jumped.advance(1000);
+        assert_eq!(jumped.random(), pcg.random());
+        jumped.advance(0);
+        assert_eq!(jumped.random(), pcg.random());
+    }
 }
@@ -18,6 +18,25 @@ pub struct Ray {
     /// If a ray is produced by a reflection,
     /// its `depth` is increased by 1 with respect to the original ray.
     pub depth: u32,
+    /// Optional [`RayDifferentials`], used to estimate the footprint of
+    /// the ray on a shape's surface.
+    pub differentials: Option<RayDifferentials>,
+}
+
+/// Ray differentials struct.
+///
+/// Two auxiliary rays offset by one pixel along image `x` and `y` directions,
+/// see [Igehy (1999)](https://doi.org/10.1145/311535.311555).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayDifferentials {
+    /// Origin [`Point`] of the ray offset along `x`.
+    pub rx_origin: Point,
+    /// [`Vector`] direction of the ray offset along `x`.
+    pub rx_dir: Vector,
+    /// Origin [`Point`] of the ray offset along `y`.
+    pub ry_origin: Point,
+    /// [`Vector`] direction of the ray offset along `y`.
+    pub ry_dir: Vector,
 }
 
 impl Ray {
@@ -26,6 +45,19 @@ impl Ray {
     pub fn at(self, t: f32) -> Point {
         self.origin + self.dir * t
     }
+
+    /// Scale the offset of [`differentials`](struct@RayDifferentials) by `scale`.
+    ///
+    /// Useful when a pixel is sampled by several rays (e.g. anti-aliasing),
+    /// each one covering only a fraction of the pixel.
+    pub fn scale_differentials(&mut self, scale: f32) {
+        if let Some(diff) = self.differentials.as_mut() {
+            diff.rx_origin = self.origin + (diff.rx_origin - self.origin) * scale;
+            diff.rx_dir = self.dir + (diff.rx_dir - self.dir) * scale;
+            diff.ry_origin = self.origin + (diff.ry_origin - self.origin) * scale;
+            diff.ry_dir = self.dir + (diff.ry_dir - self.dir) * scale;
+        }
+    }
 }
 
 impl IsClose for Ray {
@@ -43,6 +75,7 @@ impl Default for Ray {
     /// * `tmin = 1e-5`
     /// * `tmax = `[`f32::INFINITY`]
     /// * `depth = 0`
+    /// * no `differentials`
     fn default() -> Self {
         Ray {
             origin: Point::default(),
@@ -50,6 +83,7 @@ impl Default for Ray {
             tmin: 1e-5,
             tmax: f32::INFINITY,
             depth: 0,
+            differentials: None,
         }
     }
 }
@@ -92,4 +126,24 @@ mod test {
         assert!(ray.at(1.0).is_close(Point::from((5.0, 4.0, 5.0))));
         assert!(ray.at(2.0).is_close(Point::from((9.0, 6.0, 6.0))))
     }
+
+    #[test]
+    fn test_scale_differentials() {
+        let mut ray = Ray {
+            dir: Vector::from((1.0, 0.0, 0.0)),
+            differentials: Some(RayDifferentials {
+                rx_origin: Point::from((0.0, 2.0, 0.0)),
+                rx_dir: Vector::from((1.0, 0.0, 0.0)),
+                ry_origin: Point::default(),
+                ry_dir: Vector::from((1.0, 0.0, 4.0)),
+            }),
+            ..Default::default()
+        };
+        ray.scale_differentials(0.25);
+        let diff = ray.differentials.unwrap();
+        assert!(diff.rx_origin.is_close(Point::from((0.0, 0.5, 0.0))));
+        assert!(diff.rx_dir.is_close(Vector::from((1.0, 0.0, 0.0))));
+        assert!(diff.ry_origin.is_close(Point::default()));
+        assert!(diff.ry_dir.is_close(Vector::from((1.0, 0.0, 1.0))));
+    }
 }
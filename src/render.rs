@@ -2,10 +2,13 @@
 //!
 //! Provides different renderers that implement [`Solve`] trait.
 use crate::{
-    color::Color,
-    material::{GetColor, ScatterRay},
+    color::{Color, BLACK},
+    light::{Environment, SampleLight},
+    material::{Eval, GetColor, ScatterRay, BRDF},
     random::Pcg,
     ray::Ray,
+    shape::HitRecord,
+    vector::Vector,
     world::World,
 };
 
@@ -26,7 +29,8 @@ pub trait Solve {
 pub struct OnOffRenderer<'a> {
     /// A world instance.
     world: &'a World,
-    /// Background color (usually [`BLACK`](../color/constant.BLACK.html)).
+    /// Background color (usually [`BLACK`](../color/constant.BLACK.html)),
+    /// used when the world has no [`Environment`](../light/enum.Environment.html).
     bg_color: Color,
     /// Foreground color (usually [`WHITE`](../color/constant.WHITE.html)).
     fg_color: Color,
@@ -50,22 +54,31 @@ impl<'a> OnOffRenderer<'a> {
 pub struct FlatRenderer<'a> {
     /// A world instance.
     world: &'a World,
-    /// Background color (usually [`BLACK`](../color/constant.BLACK.html)).
+    /// Background color (usually [`BLACK`](../color/constant.BLACK.html)),
+    /// used when the world has no [`Environment`](../light/enum.Environment.html).
     bg_color: Color,
 }
 
 impl Solve for OnOffRenderer<'_> {
     /// Solve rendering with on/off strategy.
     ///
-    /// If intersection happens return `fg_color` otherwise `bg_color`.
+    /// If intersection happens return `fg_color` otherwise the [`background`].
     fn solve(&self, ray: Ray, _pcg: &mut Pcg) -> Color {
         match self.world.ray_intersection(ray) {
             Some(_hit) => self.fg_color,
-            None => self.bg_color,
+            None => background(self.world, self.bg_color, ray),
         }
     }
 }
 
+/// Return the radiance of the environment of `world` seen by `ray`,
+/// or `bg_color` if `world` has no [`Environment`](../light/enum.Environment.html).
+fn background(world: &World, bg_color: Color, ray: Ray) -> Color {
+    world
+        .environment()
+        .map_or(bg_color, |environment| environment.radiance(ray.dir))
+}
+
 impl<'a> FlatRenderer<'a> {
     /// Create a new [`FlatRenderer`] renderer.
     pub fn new(world: &'a World, bg_color: Color) -> Self {
@@ -76,14 +89,21 @@ impl<'a> FlatRenderer<'a> {
 impl Solve for FlatRenderer<'_> {
     /// Solve rendering with flat colors.
     ///
-    /// If intersection happens return the color of the hit shape, otherwise `bg_color`.
+    /// If intersection happens return the color of the hit shape, otherwise the [`background`].
     fn solve(&self, ray: Ray, _pcg: &mut Pcg) -> Color {
         match self.world.ray_intersection(ray) {
             Some(hit) => {
-                hit.material.emitted_radiance.get_color(hit.surface_point)
-                    + hit.material.brdf.get_color(hit.surface_point)
+                hit.material.emitted_radiance.filtered_color(
+                    hit.surface_point,
+                    hit.local_point,
+                    hit.footprint,
+                ) + hit.material.brdf.filtered_color(
+                    hit.surface_point,
+                    hit.local_point,
+                    hit.footprint,
+                )
             },
-            None => self.bg_color,
+            None => background(self.world, self.bg_color, ray),
         }
     }
 }
@@ -92,10 +112,16 @@ impl Solve for FlatRenderer<'_> {
 ///
 /// It resolves the rendering equations by means
 /// of a Monte Carlo numeric integration algorithm.
+///
+/// Optionally, emissive shapes (see [`World::emitters`]) and the environment
+/// (see [`World::environment`]) are also explicitly sampled at each diffuse impact
+/// (next-event estimation),\
+/// and combined with [`BRDF`] sampling through multiple importance sampling.
 pub struct PathTracer<'a> {
     /// A world instance.
     world: &'a World,
-    /// Background color (usually [`BLACK`](../color/constant.BLACK.html)).
+    /// Background color (usually [`BLACK`](../color/constant.BLACK.html)),
+    /// used when the world has no [`Environment`](../light/enum.Environment.html).
     bg_color: Color,
     /// Number of scattered rays after every impact.
     num_of_rays: u32,
@@ -105,6 +131,9 @@ pub struct PathTracer<'a> {
     /// After this level of depth the russian roulette algorithm came into play
     /// to eventually stop the rendering.
     russian_roulette_limit: u32,
+    /// Enable next-event estimation towards emissive shapes,
+    /// when disabled the original (noisier) algorithm is used.
+    light_sampling: bool,
 }
 
 impl<'a> PathTracer<'a> {
@@ -115,6 +144,7 @@ impl<'a> PathTracer<'a> {
         num_of_rays: u32,
         max_depth: u32,
         russian_roulette_limit: u32,
+        light_sampling: bool,
     ) -> Self {
         PathTracer {
             world,
@@ -122,54 +152,316 @@ impl<'a> PathTracer<'a> {
             num_of_rays,
             max_depth,
             russian_roulette_limit,
+            light_sampling,
         }
     }
-}
 
-impl Solve for PathTracer<'_> {
-    /// Solve the rendering equation using a path tracing algorithm.
+    /// Recursive step of [`solve`](#method.solve).
     ///
-    /// The algorithm implemented here allows the caller to tune number of\
-    /// rays thrown at each iteration,as well as the maximum depth.
-    ///
-    /// It implements Russian roulette, to avoid artefacts and speed up computation.
-    fn solve(&self, ray: Ray, pcg: &mut Pcg) -> Color {
+    /// `bsdf_pdf` is the probability density (per unit solid angle) used
+    /// to scatter `ray` from a diffuse surface when light sampling is enabled,\
+    /// the emitted radiance of an impacted emitter is then weighted
+    /// against the probability of having sampled it explicitly.
+    fn trace(&self, ray: Ray, pcg: &mut Pcg, bsdf_pdf: Option<f32>) -> Color {
         if ray.depth > self.max_depth {
             return Color::default();
         }
-        let hit_record = self.world.ray_intersection(ray);
+        let hit_record = self.world.shape_intersection(ray);
         if hit_record.is_none() {
-            return self.bg_color;
+            return match (self.world.environment(), bsdf_pdf) {
+                (Some(environment), Some(bsdf_pdf)) => {
+                    let light_pdf =
+                        environment.pdf(ray.origin, ray.dir) / self.light_count() as f32;
+                    environment.radiance(ray.dir) * power_heuristic(bsdf_pdf, light_pdf)
+                },
+                _ => background(self.world, self.bg_color, ray),
+            };
+        }
+        let (hit_index, hit) = hit_record.unwrap();
+        let hit_material = &hit.material;
+        let hit_color =
+            hit_material
+                .brdf
+                .filtered_color(hit.surface_point, hit.local_point, hit.footprint);
+        let mut emitted_radiance = hit_material.emitted_radiance.filtered_color(
+            hit.surface_point,
+            hit.local_point,
+            hit.footprint,
+        );
+        if let Some(bsdf_pdf) = bsdf_pdf {
+            emitted_radiance = emitted_radiance * self.emitter_weight(hit_index, ray, bsdf_pdf);
         }
-        let hit = hit_record.unwrap();
-        let hit_material = hit.material;
-        let mut hit_color = hit_material.brdf.get_color(hit.surface_point);
-        let emitted_radiance = hit_material.emitted_radiance.get_color(hit.surface_point);
         let hit_color_lum = hit_color.r.max(hit_color.g.max(hit_color.b));
+        let mut survival = 1.;
         if ray.depth >= self.russian_roulette_limit {
-            let q = (1. - hit_color_lum).max(0.05);
+            let q = (1. - hit_material.brdf.albedo(hit_color)).max(0.05);
             if pcg.random_float() > q {
-                hit_color = hit_color * (1.0 / (1. - q));
+                survival = 1.0 / (1. - q);
             } else {
                 return emitted_radiance;
             }
         }
+        let sample_lights =
+            self.light_sampling && self.light_count() > 0 && !hit_material.brdf.is_delta();
         let mut cum_radiance = Color::default();
-        if hit_color_lum > 0. {
+        // Microfacet and coated BRDFs reflect light even with a black pigment.
+        let glossy = matches!(hit_material.brdf, BRDF::Microfacet(_) | BRDF::Coated(_));
+        if hit_color_lum > 0. || glossy {
             for _ in 0..self.num_of_rays {
+                if sample_lights {
+                    cum_radiance = cum_radiance + self.sample_emitter(&hit, pcg) * survival;
+                }
+                let random = (pcg.random_float(), pcg.random_float());
                 let new_ray = hit_material.brdf.scatter_ray(
-                    (pcg.random_float(), pcg.random_float()),
+                    random,
                     hit.ray.dir,
                     hit.world_point,
                     hit.normal,
+                    hit.inside,
                     ray.depth + 1,
                 );
-                let new_radiance = Self::solve(self, new_ray, pcg);
-                cum_radiance = cum_radiance + (hit_color * new_radiance);
+                // Diffuse BRDFs and delta distributions are sampled proportionally
+                // to their color, unless their density is needed by light sampling.
+                let pdf = if sample_lights || glossy {
+                    hit_material
+                        .brdf
+                        .scatter_pdf(hit.normal, hit.ray.dir, new_ray.dir)
+                } else {
+                    None
+                };
+                let (weight, new_bsdf_pdf) = match pdf {
+                    None => (
+                        hit_material.brdf.scatter_weight(
+                            random,
+                            hit.normal,
+                            hit.ray.dir,
+                            new_ray.dir,
+                            hit.inside,
+                            hit_color,
+                        ) * survival,
+                        None,
+                    ),
+                    Some(bsdf_pdf) if bsdf_pdf > 0. => (
+                        self.brdf_cosine(&hit, new_ray.dir) * (survival / bsdf_pdf),
+                        sample_lights.then_some(bsdf_pdf),
+                    ),
+                    Some(_) => continue,
+                };
+                if weight.luminosity() <= 0. {
+                    continue;
+                }
+                let new_radiance = self.trace(new_ray, pcg, new_bsdf_pdf);
+                cum_radiance = cum_radiance + (weight * new_radiance);
             }
         }
         emitted_radiance + cum_radiance * (1. / (self.num_of_rays as f32))
     }
+
+    /// Return the BRDF of the impacted surface, between `in_dir` and the observer,
+    /// times the cosine of `in_dir` with the surface normal.
+    fn brdf_cosine(&self, hit: &HitRecord, in_dir: Vector) -> Color {
+        let cos_theta = Vector::from(hit.normal)
+            .normalize()
+            .dot(in_dir.normalize())
+            .max(0.);
+        hit.material.brdf.eval(
+            hit.normal,
+            in_dir,
+            hit.ray.dir.neg(),
+            hit.surface_point,
+            hit.local_point,
+            hit.footprint,
+        ) * cos_theta
+    }
+
+    /// Number of light sources sampled by next-event estimation,
+    /// the emitters plus the environment (if any).
+    fn light_count(&self) -> usize {
+        self.world.emitters().len() + usize::from(self.world.environment().is_some())
+    }
+
+    /// Next-event estimation from an impacted surface (not a delta distribution).
+    ///
+    /// Choose uniformly one of the emitters or the environment, sample a direction towards it\
+    /// and return its radiance reflected by the surface BRDF, weighted by the multiple
+    /// importance sampling (power heuristic) weight, divided by the sample probability density.
+    fn sample_emitter(&self, hit: &HitRecord, pcg: &mut Pcg) -> Color {
+        let point = hit.world_point;
+        let emitters = self.world.emitters();
+        let light_count = self.light_count();
+        let choice = ((pcg.random_float() * light_count as f32) as usize).min(light_count - 1);
+        // The environment (if any) follows the emitters.
+        let emitter = emitters.get(choice);
+        let random = (pcg.random_float(), pcg.random_float());
+        let (dir, light_pdf) = match (emitter, self.world.environment()) {
+            (Some((_, area_light)), _) => area_light.sample(point, random),
+            (None, Some(environment)) => environment.sample(point, random),
+            (None, None) => return BLACK,
+        };
+        let cos_theta = Vector::from(hit.normal).normalize().dot(dir);
+        if cos_theta <= 0. || light_pdf <= 0. {
+            return BLACK;
+        }
+        let shadow_ray = Ray {
+            origin: point,
+            dir,
+            tmin: 1.0e-3,
+            ..Default::default()
+        };
+        let radiance = match (self.world.shape_intersection(shadow_ray), emitter) {
+            (Some((index, hit)), Some((emitter_index, _))) if index == *emitter_index => hit
+                .material
+                .emitted_radiance
+                .filtered_color(hit.surface_point, hit.local_point, hit.footprint),
+            (None, None) => background(self.world, self.bg_color, shadow_ray),
+            _ => return BLACK,
+        };
+        let light_pdf = light_pdf / light_count as f32;
+        let bsdf_pdf = hit
+            .material
+            .brdf
+            .scatter_pdf(hit.normal, hit.ray.dir, dir)
+            .unwrap_or(0.);
+        radiance * self.brdf_cosine(hit, dir) * (power_heuristic(light_pdf, bsdf_pdf) / light_pdf)
+    }
+
+    /// Multiple importance sampling weight of the emitted radiance of the `index`-th shape,
+    /// impacted by `ray` scattered with `bsdf_pdf` probability density.
+    fn emitter_weight(&self, index: usize, ray: Ray, bsdf_pdf: f32) -> f32 {
+        let emitters = self.world.emitters();
+        match emitters
+            .iter()
+            .find(|(emitter_index, _)| *emitter_index == index)
+        {
+            Some((_, area_light)) => {
+                let light_pdf = area_light.pdf(ray.origin, ray.dir) / self.light_count() as f32;
+                power_heuristic(bsdf_pdf, light_pdf)
+            },
+            None => 1.,
+        }
+    }
+}
+
+/// Power heuristic (with exponent two) for multiple importance sampling,
+/// return the weight of a sample drawn with probability density `pdf`\
+/// against another strategy with probability density `other_pdf`.
+fn power_heuristic(pdf: f32, other_pdf: f32) -> f32 {
+    let (pdf_sq, other_pdf_sq) = (pdf * pdf, other_pdf * other_pdf);
+    if pdf_sq + other_pdf_sq > 0. {
+        pdf_sq / (pdf_sq + other_pdf_sq)
+    } else {
+        0.
+    }
+}
+
+impl Solve for PathTracer<'_> {
+    /// Solve the rendering equation using a path tracing algorithm.
+    ///
+    /// The algorithm implemented here allows the caller to tune number of\
+    /// rays thrown at each iteration,as well as the maximum depth.
+    ///
+    /// It implements Russian roulette, to avoid artefacts and speed up computation.
+    fn solve(&self, ray: Ray, pcg: &mut Pcg) -> Color {
+        self.trace(ray, pcg, None)
+    }
+}
+
+/// A point-light renderer.
+///
+/// This renderer is similar to what POV-Ray provides by default,\
+/// it only considers the direct contribution of the
+/// [`PointLight`](../light/struct.PointLight.html) sources in the world
+/// and of the [`SunLight`](../light/struct.SunLight.html) of its environment (if any),
+/// casting a shadow ray towards each of them.
+pub struct PointLightRenderer<'a> {
+    /// A world instance.
+    world: &'a World,
+    /// Background color (usually [`BLACK`](../color/constant.BLACK.html)),
+    /// used when the world has no [`Environment`](../light/enum.Environment.html).
+    bg_color: Color,
+    /// Ambient color, added to the surface color
+    /// to mimic light diffused by the environment.
+    ambient_color: Color,
+}
+
+impl<'a> PointLightRenderer<'a> {
+    /// Create a new [`PointLightRenderer`] renderer.
+    pub fn new(world: &'a World, bg_color: Color, ambient_color: Color) -> Self {
+        Self {
+            world,
+            bg_color,
+            ambient_color,
+        }
+    }
+}
+
+impl Solve for PointLightRenderer<'_> {
+    /// Solve rendering with point light sources.
+    ///
+    /// If intersection happens return the emitted radiance plus,
+    /// for each visible light source, the [`BRDF`](../material/enum.BRDF.html)
+    /// evaluated between the light and the observer.\
+    /// Otherwise return the [`background`].
+    fn solve(&self, ray: Ray, _pcg: &mut Pcg) -> Color {
+        let hit = match self.world.ray_intersection(ray) {
+            Some(hit) => hit,
+            None => return background(self.world, self.bg_color, ray),
+        };
+        let hit_material = hit.material;
+        let mut result_color = hit_material.emitted_radiance.filtered_color(
+            hit.surface_point,
+            hit.local_point,
+            hit.footprint,
+        ) + self.ambient_color
+            * hit_material
+                .brdf
+                .filtered_color(hit.surface_point, hit.local_point, hit.footprint);
+        let normal = Vector::from(hit.normal).normalize();
+        for light in self.world.lights() {
+            if !self.world.is_point_visible(light.position, hit.world_point) {
+                continue;
+            }
+            let distance_vec = light.position - hit.world_point;
+            let distance = distance_vec.norm();
+            let in_dir = distance_vec * (1.0 / distance);
+            let cos_theta = normal.dot(in_dir).max(0.0);
+            let distance_factor = if light.linear_radius > 0.0 {
+                (light.linear_radius / distance).powi(2)
+            } else {
+                1.0
+            };
+            let brdf_color = hit_material.brdf.eval(
+                hit.normal,
+                in_dir,
+                ray.dir.neg(),
+                hit.surface_point,
+                hit.local_point,
+                hit.footprint,
+            );
+            result_color = result_color + brdf_color * light.color * (cos_theta * distance_factor);
+        }
+        if let Some(sun) = self.world.environment().and_then(Environment::sun) {
+            let shadow_ray = Ray {
+                origin: hit.world_point,
+                dir: sun.direction,
+                tmin: 1e-3,
+                ..Default::default()
+            };
+            let cos_theta = normal.dot(sun.direction);
+            if cos_theta > 0.0 && self.world.ray_intersection(shadow_ray).is_none() {
+                let brdf_color = hit_material.brdf.eval(
+                    hit.normal,
+                    sun.direction,
+                    ray.dir.neg(),
+                    hit.surface_point,
+                    hit.local_point,
+                    hit.footprint,
+                );
+                result_color = result_color + brdf_color * sun.illuminance() * cos_theta;
+            }
+        }
+        result_color
+    }
 }
 
 /// A dummy renderer.
@@ -190,6 +482,7 @@ pub enum Renderer<'a> {
     Dummy(DummyRenderer),
     PathTracer(PathTracer<'a>),
     Flat(FlatRenderer<'a>),
+    PointLight(PointLightRenderer<'a>),
 }
 
 impl Solve for Renderer<'_> {
@@ -200,6 +493,7 @@ impl Solve for Renderer<'_> {
             Renderer::Dummy(dummy) => dummy.solve(ray, pcg),
             Renderer::PathTracer(pathtracer) => pathtracer.solve(ray, pcg),
             Renderer::Flat(flat) => flat.solve(ray, pcg),
+            Renderer::PointLight(pointlight) => pointlight.solve(ray, pcg),
         }
     }
 }
@@ -208,14 +502,23 @@ impl Solve for Renderer<'_> {
 mod test {
     use super::*;
     use crate::{
-        material::{DiffuseBRDF, Pigment, UniformPigment, BRDF},
+        hdrimage::HdrImage,
+        light::{EnvironmentLight, PointLight, SkyLight},
+        material::{
+            CoatedBRDF, DiffuseBRDF, MicrofacetBRDF, NoisePigment, Pattern, Pigment, SpecularBRDF,
+            UniformPigment, BRDF,
+        },
         misc::IsClose,
+        noise::{Basis, Noise},
         point::Point,
+        scaling,
+        shape::Plane,
         transformation::Transformation,
         translation,
-        vector::E1,
+        vector::{E1, E3},
         CheckeredPigment, Material, Sphere, BLACK, WHITE,
     };
+    use std::f32::consts::PI;
 
     #[test]
     fn test_flat() {
@@ -293,7 +596,8 @@ mod test {
             let furnace = Sphere::new(Transformation::default(), furnace_material);
             let mut world = World::default();
             world.add(Box::new(furnace));
-            let path_tracer = Renderer::PathTracer(PathTracer::new(&world, BLACK, 1, 100, 101));
+            let path_tracer =
+                Renderer::PathTracer(PathTracer::new(&world, BLACK, 1, 100, 101, false));
             let color = path_tracer.solve(Ray::default(), &mut pcg);
             let expected = emitted_radiance / (1. - reflectance);
             assert!(expected.is_close(color.r));
@@ -302,13 +606,352 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_furnace_light_sampling() {
+        let mut pcg = Pcg::default();
+        for _ in 0..5 {
+            let emitted_radiance = pcg.random_float();
+            let reflectance = pcg.random_float() * 0.9;
+            let furnace_material = Material {
+                brdf: BRDF::Diffuse(DiffuseBRDF {
+                    pigment: Pigment::Uniform(UniformPigment {
+                        color: WHITE * reflectance,
+                    }),
+                }),
+                emitted_radiance: Pigment::Uniform(UniformPigment {
+                    color: WHITE * emitted_radiance,
+                }),
+            };
+            let furnace = Sphere::new(Transformation::default(), furnace_material);
+            let mut world = World::default();
+            world.add(Box::new(furnace));
+            let path_tracer =
+                Renderer::PathTracer(PathTracer::new(&world, BLACK, 1, 100, 101, true));
+            // Light sampling is unbiased but no more exact sample by sample.
+            let num = 2000;
+            let mean = (0..num)
+                .map(|_| path_tracer.solve(Ray::default(), &mut pcg).r)
+                .sum::<f32>()
+                / num as f32;
+            let expected = emitted_radiance / (1. - reflectance);
+            assert!((mean - expected).abs() < 0.02 * expected.max(1.));
+        }
+    }
+
+    #[test]
+    fn test_light_sampling() {
+        // A diffuse plane lit by a small emissive sphere right above the observed point:
+        // the reflected radiance is `reflectance * radiance * (radius / distance)^2`.
+        let mut world = World::default();
+        world.add(Box::new(Plane::new(
+            Transformation::default(),
+            Material {
+                brdf: BRDF::Diffuse(DiffuseBRDF {
+                    pigment: Pigment::Uniform(UniformPigment { color: WHITE * 0.5 }),
+                }),
+                ..Default::default()
+            },
+        )));
+        world.add(Box::new(Sphere::new(
+            translation(E3 * 4.) * scaling(Vector::from((0.5, 0.5, 0.5))),
+            Material {
+                emitted_radiance: Pigment::Uniform(UniformPigment { color: WHITE }),
+                ..Default::default()
+            },
+        )));
+        world.build_bvh();
+        let ray = Ray {
+            origin: Point::from((-1., 0., 1.)),
+            dir: Vector::from((1., 0., -1.)),
+            ..Default::default()
+        };
+        let expected = 0.5 * (0.5_f32 / 4.).powi(2);
+        let num = 5000;
+        let (mut means, mut variances) = (Vec::new(), Vec::new());
+        for light_sampling in [false, true] {
+            let mut pcg = Pcg::default();
+            let path_tracer =
+                Renderer::PathTracer(PathTracer::new(&world, BLACK, 1, 1, 100, light_sampling));
+            let samples: Vec<f32> = (0..num)
+                .map(|_| path_tracer.solve(ray, &mut pcg).r)
+                .collect();
+            let mean = samples.iter().sum::<f32>() / num as f32;
+            let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f32>() / num as f32;
+            means.push(mean);
+            variances.push(variance);
+        }
+        assert!((means[1] - expected).abs() < 0.05 * expected);
+        assert!(variances[1] * 10. < variances[0]);
+    }
+
+    /// Check that light sampling and BRDF sampling agree on the mean radiance
+    /// reflected by a plane of `brdf`, lit by a sphere emitting `emitted_radiance`.
+    fn check_light_sampling(brdf: BRDF, emitted_radiance: Pigment) {
+        let mut world = World::default();
+        world.add(Box::new(Plane::new(
+            Transformation::default(),
+            Material {
+                brdf,
+                ..Default::default()
+            },
+        )));
+        world.add(Box::new(Sphere::new(
+            translation(Vector::from((1., 0., 3.))),
+            Material {
+                emitted_radiance,
+                ..Default::default()
+            },
+        )));
+        world.build_bvh();
+        let ray = Ray {
+            origin: Point::from((-1., 0., 1.)),
+            dir: Vector::from((1., 0., -1.)),
+            ..Default::default()
+        };
+        let num = 20000;
+        let means: Vec<f32> = [false, true]
+            .into_iter()
+            .map(|light_sampling| {
+                let mut pcg = Pcg::default();
+                let path_tracer =
+                    Renderer::PathTracer(PathTracer::new(&world, BLACK, 1, 1, 100, light_sampling));
+                (0..num)
+                    .map(|_| path_tracer.solve(ray, &mut pcg).r)
+                    .sum::<f32>()
+                    / num as f32
+            })
+            .collect();
+        assert!(means[1] > 0.);
+        assert!((means[0] - means[1]).abs() < 0.05 * means[1]);
+    }
+
+    #[test]
+    fn test_microfacet_light_sampling() {
+        // A glossy plane lit by an emissive sphere: light sampling and BRDF sampling agree.
+        check_light_sampling(
+            BRDF::Microfacet(MicrofacetBRDF {
+                pigment: Pigment::Uniform(UniformPigment { color: WHITE * 0.5 }),
+                roughness: 0.4,
+                metallic: 0.5,
+            }),
+            Pigment::Uniform(UniformPigment { color: WHITE }),
+        );
+    }
+
+    #[test]
+    fn test_microfacet_russian_roulette() {
+        // A glossy plane with a black pigment, beyond the Russian roulette limit,
+        // still reflects the background by its specular lobe.
+        let mut world = World::default();
+        world.add(Box::new(Plane::new(
+            Transformation::default(),
+            Material {
+                brdf: BRDF::Microfacet(MicrofacetBRDF {
+                    pigment: Pigment::Uniform(UniformPigment { color: BLACK }),
+                    roughness: 0.2,
+                    metallic: 0.,
+                }),
+                ..Default::default()
+            },
+        )));
+        world.build_bvh();
+        let ray = Ray {
+            origin: Point::from((-1., 0., 1.)),
+            dir: Vector::from((1., 0., -1.)),
+            depth: 5,
+            ..Default::default()
+        };
+        let mut pcg = Pcg::default();
+        let path_tracer = Renderer::PathTracer(PathTracer::new(&world, WHITE, 1, 10, 3, false));
+        let num = 1000;
+        let mean = (0..num)
+            .map(|_| path_tracer.solve(ray, &mut pcg).r)
+            .sum::<f32>()
+            / num as f32;
+        assert!(mean > 0.);
+    }
+
+    #[test]
+    fn test_coated_mirror() {
+        // A red mirror reflects no green light, unless it is coated:
+        // the coat reflection is uncolored and the mirror is dimmed by the coat transmittance.
+        let mirror = BRDF::Specular(SpecularBRDF {
+            pigment: Pigment::Uniform(UniformPigment {
+                color: Color::from((1., 0., 0.)),
+            }),
+            ..Default::default()
+        });
+        let coated = BRDF::Coated(CoatedBRDF {
+            base: Box::new(mirror.clone()),
+            ..Default::default()
+        });
+        let ray = Ray {
+            origin: Point::from((-1., 0., 1.)),
+            dir: Vector::from((1., 0., -1.)),
+            ..Default::default()
+        };
+        let num = 2000;
+        let means: Vec<Color> = [mirror, coated]
+            .into_iter()
+            .map(|brdf| {
+                let mut world = World::default();
+                world.add(Box::new(Plane::new(
+                    Transformation::default(),
+                    Material {
+                        brdf,
+                        ..Default::default()
+                    },
+                )));
+                world.build_bvh();
+                let mut pcg = Pcg::default();
+                let path_tracer =
+                    Renderer::PathTracer(PathTracer::new(&world, WHITE, 1, 10, 10, false));
+                (0..num).fold(BLACK, |sum, _| sum + path_tracer.solve(ray, &mut pcg))
+                    * (1. / num as f32)
+            })
+            .collect();
+        assert!(means[0].is_close(Color::from((1., 0., 0.))));
+        assert!(means[1].g > 0.02 && means[1].g == means[1].b);
+        assert!(means[1].r < 1. && means[1].r > 0.9);
+    }
+
+    #[test]
+    fn test_noise_emitter_light_sampling() {
+        // A diffuse plane lit by a sphere emitting a noise pattern in object space:
+        // light sampling and BRDF sampling see the same emitted pattern.
+        check_light_sampling(
+            BRDF::Diffuse(DiffuseBRDF {
+                pigment: Pigment::Uniform(UniformPigment { color: WHITE * 0.5 }),
+            }),
+            Pigment::Noise(NoisePigment::new(
+                Pattern::Wood,
+                BLACK,
+                WHITE,
+                1.0,
+                4,
+                Noise::new(Basis::Perlin, 42),
+            )),
+        );
+    }
+
+    #[test]
+    fn test_environment_light() {
+        // A diffuse plane under a single bright texel around the zenith:
+        // the reflected radiance is `reflectance * radiance * (pi / 8) / pi`.
+        let mut world = World::default();
+        world.add(Box::new(Plane::new(
+            Transformation::default(),
+            Material {
+                brdf: BRDF::Diffuse(DiffuseBRDF {
+                    pigment: Pigment::Uniform(UniformPigment { color: WHITE * 0.5 }),
+                }),
+                ..Default::default()
+            },
+        )));
+        world.build_bvh();
+        let mut image = HdrImage::new(4, 4);
+        image.set_pixel(1, 0, WHITE * 2.).unwrap();
+        world.set_environment(Environment::Map(EnvironmentLight::new(
+            image,
+            2.,
+            Transformation::default(),
+        )));
+        let mut pcg = Pcg::default();
+        let sky_ray = Ray {
+            dir: Vector::from((-1., 1., 5.)),
+            ..Default::default()
+        };
+        let flat_renderer = Renderer::Flat(FlatRenderer::new(&world, BLACK));
+        assert!(flat_renderer.solve(sky_ray, &mut pcg).is_close(WHITE * 4.));
+        let onoff_renderer = Renderer::OnOff(OnOffRenderer::new(&world, BLACK, WHITE));
+        assert!(onoff_renderer
+            .solve(Ray { dir: E1, ..sky_ray }, &mut pcg)
+            .is_close(BLACK));
+
+        let ray = Ray {
+            origin: Point::from((-1., 0., 1.)),
+            dir: Vector::from((1., 0., -1.)),
+            ..Default::default()
+        };
+        let expected = 0.5 * 4. / 8.;
+        let num = 5000;
+        let (mut means, mut variances) = (Vec::new(), Vec::new());
+        for light_sampling in [false, true] {
+            let mut pcg = Pcg::default();
+            let path_tracer =
+                Renderer::PathTracer(PathTracer::new(&world, BLACK, 1, 1, 100, light_sampling));
+            let samples: Vec<f32> = (0..num)
+                .map(|_| path_tracer.solve(ray, &mut pcg).r)
+                .collect();
+            let mean = samples.iter().sum::<f32>() / num as f32;
+            let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f32>() / num as f32;
+            means.push(mean);
+            variances.push(variance);
+        }
+        assert!((means[0] - expected).abs() < 0.1 * expected);
+        assert!((means[1] - expected).abs() < 0.05 * expected);
+        assert!(variances[1] * 10. < variances[0]);
+    }
+
     #[test]
     fn test_background() {
         let mut pcg = Pcg::default();
         let sphere = Sphere::new(translation(E1 * 2.), Material::default());
         let mut world = World::default();
         world.add(Box::new(sphere));
-        let path_tracer = Renderer::PathTracer(PathTracer::new(&world, BLACK, 1000, 1000, 0));
+        let path_tracer =
+            Renderer::PathTracer(PathTracer::new(&world, BLACK, 1000, 1000, 0, false));
         assert!(path_tracer.solve(Ray::default(), &mut pcg).is_close(BLACK))
     }
+
+    #[test]
+    fn test_pointlight() {
+        let mut pcg = Pcg::default();
+        let ray = Ray {
+            origin: Point::from((-2., 0., 0.)),
+            ..Default::default()
+        };
+        let ambient = Color::from((0.1, 0.2, 0.3));
+        let mut world = World::default();
+        world.add(Box::<Sphere>::default());
+        world.add_light(PointLight::new(Point::from((-10., 0., 0.)), WHITE, 0.));
+        let renderer = Renderer::PointLight(PointLightRenderer::new(&world, BLACK, ambient));
+        assert!(renderer
+            .solve(ray, &mut pcg)
+            .is_close(ambient + WHITE * (1. / PI)));
+        assert!(renderer.solve(Ray::default(), &mut pcg).is_close(ambient));
+        assert!(renderer
+            .solve(
+                Ray {
+                    origin: Point::from((-2., 3., 0.)),
+                    ..Default::default()
+                },
+                &mut pcg
+            )
+            .is_close(BLACK));
+
+        let mut world = World::default();
+        world.add(Box::<Sphere>::default());
+        world.add_light(PointLight::new(Point::from((10., 0., 0.)), WHITE, 0.));
+        world.add_light(PointLight::new(Point::from((-5., 0., 0.)), WHITE, 2.));
+        let renderer = Renderer::PointLight(PointLightRenderer::new(&world, BLACK, BLACK));
+        assert!(renderer.solve(ray, &mut pcg).is_close(WHITE * (0.25 / PI)));
+
+        // The sun at the zenith lights the top of the sphere, not its side.
+        let mut world = World::default();
+        world.add(Box::<Sphere>::default());
+        let sky = SkyLight::new(E3, 3., f32::to_radians(0.53), 1.);
+        let illuminance = sky.sun().illuminance();
+        world.set_environment(Environment::Sky(sky));
+        let renderer = Renderer::PointLight(PointLightRenderer::new(&world, BLACK, BLACK));
+        let top_ray = Ray {
+            origin: Point::from((0., 0., 2.)),
+            dir: E3.neg(),
+            ..Default::default()
+        };
+        assert!(renderer
+            .solve(top_ray, &mut pcg)
+            .is_close(illuminance * (1. / PI)));
+        assert!(renderer.solve(ray, &mut pcg).is_close(BLACK));
+    }
 }
@@ -7,11 +7,23 @@ use crate::{
     color::{Color, BLACK, WHITE},
     error::SceneErr,
     hdrimage::HdrImage,
+    light::{Environment, EnvironmentLight, PointLight, SkyLight},
     material::{
-        CheckeredPigment, DiffuseBRDF, ImagePigment, Material, Pigment, SpecularBRDF,
-        UniformPigment, BRDF,
+        CheckeredPigment, CoatedBRDF, DielectricBRDF, DiffuseBRDF, Filter, ImagePigment, Material,
+        MicrofacetBRDF, NoisePigment, Pattern, Pigment, Space, SpecularBRDF, UniformPigment, Wrap,
+        BRDF,
+    },
+    mesh::Mesh,
+    noise::{Basis, Noise},
+    point::Point,
+    sdf::{
+        BoxSdf, CombineSdf, MandelbulbSdf, RepetitionSdf, Sdf, SphereSdf, TorusSdf, TransformSdf,
+        TwistSdf,
+    },
+    shape::{
+        Cone, Csg, CsgOperation, Cube, Cylinder, Disk, Group, Instance, Plane, RayIntersection,
+        SdfShape, Sphere, Torus, Triangle, TriangleMesh,
     },
-    shape::{Plane, RayIntersection, Sphere},
     transformation::{rotation_x, rotation_y, rotation_z, scaling, translation, Transformation},
     vector::{Vector, E1, E2, E3},
     world::World,
@@ -24,6 +36,7 @@ use std::{
     io::{BufReader, Read},
     path::Path,
     str::FromStr,
+    sync::Arc,
 };
 
 /// Chars that must be considered special when parsed.
@@ -43,30 +56,59 @@ pub struct SourceLocation {
 /// Enum for all the possible keywords of [`Token::Keyword`].
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum Keywords {
+    Aperture,
+    Box,
     Camera,
     Checkered,
+    Coated,
     Color,
     Colors,
     Compose,
+    Cone,
+    Csg,
+    Cylinder,
+    Diameter,
+    Dielectric,
     Diffuse,
+    Disk,
     Distance,
+    Environment,
+    Focus,
+    Group,
+    Groups,
     Image,
+    Intensity,
+    Ior,
+    Lights,
     Material,
     Materials,
+    Mesh,
+    Metallic,
+    Microfacet,
     Name,
+    Noise,
     Plane,
+    Position,
+    Radius,
     Ratio,
     RotationX,
     RotationY,
     RotationZ,
+    Roughness,
     Scaling,
+    Sdf,
+    Sdfs,
     Shape,
     Shapes,
     Specular,
     Sphere,
+    Sun,
+    Torus,
     Transformation,
     Transformations,
     Translation,
+    Triangle,
+    Turbidity,
     Type,
     Uniform,
 }
@@ -350,30 +392,59 @@ impl<R: Read> InputStream<R> {
             token.push(ch);
         }
         match token.as_str() {
+            "aperture" => Token::Keyword(token_location, Keywords::Aperture),
+            "box" => Token::Keyword(token_location, Keywords::Box),
             "camera" => Token::Keyword(token_location, Keywords::Camera),
             "checkered" => Token::Keyword(token_location, Keywords::Checkered),
+            "coated" => Token::Keyword(token_location, Keywords::Coated),
             "color" => Token::Keyword(token_location, Keywords::Color),
             "colors" => Token::Keyword(token_location, Keywords::Colors),
             "compose" => Token::Keyword(token_location, Keywords::Compose),
+            "cone" => Token::Keyword(token_location, Keywords::Cone),
+            "csg" => Token::Keyword(token_location, Keywords::Csg),
+            "cylinder" => Token::Keyword(token_location, Keywords::Cylinder),
+            "diameter" => Token::Keyword(token_location, Keywords::Diameter),
+            "dielectric" => Token::Keyword(token_location, Keywords::Dielectric),
             "diffuse" => Token::Keyword(token_location, Keywords::Diffuse),
+            "disk" => Token::Keyword(token_location, Keywords::Disk),
             "distance" => Token::Keyword(token_location, Keywords::Distance),
+            "environment" => Token::Keyword(token_location, Keywords::Environment),
+            "focus" => Token::Keyword(token_location, Keywords::Focus),
+            "group" => Token::Keyword(token_location, Keywords::Group),
+            "groups" => Token::Keyword(token_location, Keywords::Groups),
             "image" => Token::Keyword(token_location, Keywords::Image),
+            "intensity" => Token::Keyword(token_location, Keywords::Intensity),
+            "ior" => Token::Keyword(token_location, Keywords::Ior),
+            "lights" => Token::Keyword(token_location, Keywords::Lights),
             "material" => Token::Keyword(token_location, Keywords::Material),
             "materials" => Token::Keyword(token_location, Keywords::Materials),
+            "mesh" => Token::Keyword(token_location, Keywords::Mesh),
+            "metallic" => Token::Keyword(token_location, Keywords::Metallic),
+            "microfacet" => Token::Keyword(token_location, Keywords::Microfacet),
             "name" => Token::Keyword(token_location, Keywords::Name),
+            "noise" => Token::Keyword(token_location, Keywords::Noise),
             "plane" => Token::Keyword(token_location, Keywords::Plane),
+            "position" => Token::Keyword(token_location, Keywords::Position),
+            "radius" => Token::Keyword(token_location, Keywords::Radius),
             "ratio" => Token::Keyword(token_location, Keywords::Ratio),
             "rotationx" => Token::Keyword(token_location, Keywords::RotationX),
             "rotationy" => Token::Keyword(token_location, Keywords::RotationY),
             "rotationz" => Token::Keyword(token_location, Keywords::RotationZ),
+            "roughness" => Token::Keyword(token_location, Keywords::Roughness),
             "scaling" => Token::Keyword(token_location, Keywords::Scaling),
+            "sdf" => Token::Keyword(token_location, Keywords::Sdf),
+            "sdfs" => Token::Keyword(token_location, Keywords::Sdfs),
             "shape" => Token::Keyword(token_location, Keywords::Shape),
             "shapes" => Token::Keyword(token_location, Keywords::Shapes),
             "specular" => Token::Keyword(token_location, Keywords::Specular),
             "sphere" => Token::Keyword(token_location, Keywords::Sphere),
+            "sun" => Token::Keyword(token_location, Keywords::Sun),
+            "torus" => Token::Keyword(token_location, Keywords::Torus),
             "transformation" => Token::Keyword(token_location, Keywords::Transformation),
             "transformations" => Token::Keyword(token_location, Keywords::Transformations),
             "translation" => Token::Keyword(token_location, Keywords::Translation),
+            "triangle" => Token::Keyword(token_location, Keywords::Triangle),
+            "turbidity" => Token::Keyword(token_location, Keywords::Turbidity),
             "type" => Token::Keyword(token_location, Keywords::Type),
             "uniform" => Token::Keyword(token_location, Keywords::Uniform),
             _ => Token::Identifier(token_location, token),
@@ -528,6 +599,14 @@ impl<R: Read> InputStream<R> {
     fn match_identifier(&mut self) -> Result<(SourceLocation, String), SceneErr> {
         // Match a ' ' plus an identifier.
         self.match_symbol(' ')?;
+        self.read_identifier()
+    }
+
+    /// Read a token from stream and check that it matches [`Token::Identifier`],
+    /// without a leading ' ' (e.g. first item of a list).\
+    /// Return, wrapped inside a [`Result`], the identifier location and value.\
+    /// Otherwise return a [`SceneErr::NotMatch`] error.
+    fn read_identifier(&mut self) -> Result<(SourceLocation, String), SceneErr> {
         let token = self.read_token()?;
         match token {
             Token::Identifier(loc, id) => Ok((loc, id)),
@@ -559,6 +638,22 @@ impl<R: Read> InputStream<R> {
         }
     }
 
+    /// Read a token from stream and check that it matches [`Token::LiteralNumber`]
+    /// with a value accepted by `valid`, described by `expected` (e.g. `"in [0, 1]"`).\
+    /// Return, wrapped inside a [`Result`], the number value.\
+    /// Otherwise return a [`SceneErr::NotMatch`] or a [`SceneErr::InvalidValue`] error.
+    fn match_number_in(&mut self, valid: fn(f32) -> bool, expected: &str) -> Result<f32, SceneErr> {
+        let token = self.read_token()?;
+        match token {
+            Token::LiteralNumber(_, num) if valid(num) => Ok(num),
+            Token::LiteralNumber(loc, num) => Err(SceneErr::InvalidValue {
+                loc,
+                msg: format!("found {num} expected number {expected}"),
+            }),
+            _ => not_matches!(token, "floating-point number"),
+        }
+    }
+
     /// Read a token from stream and check that it matches [`Token::LiteralNumber`] or
     /// a [`Token::Identifier`]\
     /// with a particular string instance, that if match means
@@ -723,6 +818,85 @@ impl<R: Read> InputStream<R> {
         Ok(colors)
     }
 
+    /// Parse an image pigment [`Filter`] from an identifier.\
+    /// Otherwise return a variant of [`SceneErr`] error.
+    fn parse_filter(&mut self) -> Result<Filter, SceneErr> {
+        let (loc, filter) = self.match_identifier()?;
+        match filter.as_str() {
+            "nearest" => Ok(Filter::Nearest),
+            "bilinear" => Ok(Filter::Bilinear),
+            "bicubic" => Ok(Filter::Bicubic),
+            _ => Err(SceneErr::UndefinedIdentifier {
+                loc,
+                msg: format!(
+                    "{filter:?} filter not defined, available [nearest, bilinear, bicubic]"
+                ),
+            }),
+        }
+    }
+
+    /// Parse an image pigment [`Wrap`] from an identifier.\
+    /// Otherwise return a variant of [`SceneErr`] error.
+    fn parse_wrap(&mut self) -> Result<Wrap, SceneErr> {
+        let (loc, wrap) = self.match_identifier()?;
+        match wrap.as_str() {
+            "repeat" => Ok(Wrap::Repeat),
+            "clamp" => Ok(Wrap::Clamp),
+            "mirror" => Ok(Wrap::Mirror),
+            _ => Err(SceneErr::UndefinedIdentifier {
+                loc,
+                msg: format!("{wrap:?} wrap not defined, available [repeat, clamp, mirror]"),
+            }),
+        }
+    }
+
+    /// Parse a noise pigment [`Pattern`] from an identifier.\
+    /// Otherwise return a variant of [`SceneErr`] error.
+    fn parse_pattern(&mut self) -> Result<Pattern, SceneErr> {
+        let (loc, pattern) = self.match_identifier()?;
+        match pattern.as_str() {
+            "noise" => Ok(Pattern::Noise),
+            "fbm" => Ok(Pattern::Fbm),
+            "turbulence" => Ok(Pattern::Turbulence),
+            "marble" => Ok(Pattern::Marble),
+            "wood" => Ok(Pattern::Wood),
+            _ => Err(SceneErr::UndefinedIdentifier {
+                loc,
+                msg: format!(
+                    "{pattern:?} pattern not defined, available [noise, fbm, turbulence, marble, wood]"
+                ),
+            }),
+        }
+    }
+
+    /// Parse a noise pigment [`Basis`] from an identifier.\
+    /// Otherwise return a variant of [`SceneErr`] error.
+    fn parse_basis(&mut self) -> Result<Basis, SceneErr> {
+        let (loc, basis) = self.match_identifier()?;
+        match basis.as_str() {
+            "perlin" => Ok(Basis::Perlin),
+            "simplex" => Ok(Basis::Simplex),
+            _ => Err(SceneErr::UndefinedIdentifier {
+                loc,
+                msg: format!("{basis:?} basis not defined, available [perlin, simplex]"),
+            }),
+        }
+    }
+
+    /// Parse a noise pigment [`Space`] from an identifier.\
+    /// Otherwise return a variant of [`SceneErr`] error.
+    fn parse_space(&mut self) -> Result<Space, SceneErr> {
+        let (loc, space) = self.match_identifier()?;
+        match space.as_str() {
+            "uv" => Ok(Space::Uv),
+            "object" => Ok(Space::Object),
+            _ => Err(SceneErr::UndefinedIdentifier {
+                loc,
+                msg: format!("{space:?} space not defined, available [uv, object]"),
+            }),
+        }
+    }
+
     /// Parse a `pigment` [`Pigment`] from stream combining previous match and parse methods.\
     /// Otherwise return a variant of [`SceneErr`] error.
     fn parse_pigment(&mut self, nested: u32, var: &Var) -> Result<Pigment, SceneErr> {
@@ -733,6 +907,7 @@ impl<R: Read> InputStream<R> {
             Keywords::Uniform,
             Keywords::Checkered,
             Keywords::Image,
+            Keywords::Noise,
         ])?;
         self.match_symbol(' ')?;
         match pigment {
@@ -740,16 +915,33 @@ impl<R: Read> InputStream<R> {
                 color: self.parse_color(var)?,
             })),
             Keywords::Image => {
-                let (loc, pfm_file) = self.match_string()?;
-                Ok(Pigment::Image(ImagePigment::new(
-                    HdrImage::read_pfm_file(Path::new(&pfm_file)).map_err(|err| {
-                        SceneErr::PfmFileReadFailure {
+                // Filter and wrap modes are optional, inside a list with the file path.
+                let token = self.read_token()?;
+                let listed = matches!(token, Token::Symbol(_, sym) if sym == '[');
+                if !listed {
+                    self.unread_token(token);
+                }
+                let (loc, hdr_file) = self.match_string()?;
+                let mut image =
+                    ImagePigment::new(HdrImage::read_hdr_file(Path::new(&hdr_file)).map_err(
+                        |err| SceneErr::PfmFileReadFailure {
                             loc,
-                            msg: format!("{pfm_file:?} pfm file read failure"),
+                            msg: format!("{hdr_file:?} hdr file read failure"),
                             src: err,
-                        }
-                    })?,
-                )))
+                        },
+                    )?);
+                if listed {
+                    self.match_symbol(',')?;
+                    image.filter = self.parse_filter()?;
+                    let token = self.read_token()?;
+                    if matches!(token, Token::Symbol(_, sym) if sym == ',') {
+                        image.wrap = self.parse_wrap()?;
+                    } else {
+                        self.unread_token(token);
+                    }
+                    self.match_symbol(']')?;
+                }
+                Ok(Pigment::Image(image))
             },
             Keywords::Checkered => {
                 self.match_symbol('[')?;
@@ -767,6 +959,49 @@ impl<R: Read> InputStream<R> {
                     steps,
                 }))
             },
+            Keywords::Noise => {
+                self.match_symbol('[')?;
+                let color1 = self.parse_color(var)?;
+                self.match_symbol(',')?;
+                self.match_symbol(' ')?;
+                let color2 = self.parse_color(var)?;
+                self.match_symbol(',')?;
+                let pattern = self.parse_pattern()?;
+                self.match_symbol(',')?;
+                self.match_symbol(' ')?;
+                let scale = self.match_number()?;
+                self.match_symbol(',')?;
+                self.match_symbol(' ')?;
+                let octaves = self.match_number()? as u32;
+                self.match_symbol(',')?;
+                self.match_symbol(' ')?;
+                let seed = self.match_number()? as u64;
+                // Basis and space are optional, in this order.
+                let (mut basis, mut space) = (Basis::default(), Space::default());
+                let token = self.read_token()?;
+                if matches!(token, Token::Symbol(_, sym) if sym == ',') {
+                    basis = self.parse_basis()?;
+                    let token = self.read_token()?;
+                    if matches!(token, Token::Symbol(_, sym) if sym == ',') {
+                        space = self.parse_space()?;
+                    } else {
+                        self.unread_token(token);
+                    }
+                } else {
+                    self.unread_token(token);
+                }
+                self.match_symbol(']')?;
+                let mut noise = NoisePigment::new(
+                    pattern,
+                    color1,
+                    color2,
+                    scale,
+                    octaves,
+                    Noise::new(basis, seed),
+                );
+                noise.space = space;
+                Ok(Pigment::Noise(noise))
+            },
             // This branch should never be triggered (a dummy error).
             _ => Err(SceneErr::UnexpectedMatch(String::from(
                 "unexpected match (report it to devel)",
@@ -775,21 +1010,84 @@ impl<R: Read> InputStream<R> {
     }
 
     /// Parse a `brdf` [`BRDF`] from stream combining previous match methods.\
+    /// A coated BRDF nests its base one, `nested` times the materials block spaces deeper.\
     /// Otherwise return a variant of [`SceneErr`] error.
-    fn parse_brdf(&mut self, var: &Var) -> Result<BRDF, SceneErr> {
-        // Match indent with materials block spaces + 1 level (2 spaces).
-        self.match_spaces(1, 0)?;
-        let brdf = self.match_keywords(&vec![Keywords::Diffuse, Keywords::Specular])?;
+    fn parse_brdf(&mut self, nested: u32, var: &Var) -> Result<BRDF, SceneErr> {
+        // Match indent with materials block spaces + 1 level (2 spaces) +
+        // + nested * (materials block spaces).
+        self.match_spaces(1, nested)?;
+        let brdf = self.match_keywords(&vec![
+            Keywords::Diffuse,
+            Keywords::Specular,
+            Keywords::Dielectric,
+            Keywords::Microfacet,
+            Keywords::Coated,
+        ])?;
         // Can only be a eol or inline comment.
         self.match_eol_or_inline_comment()?;
         match brdf {
             Keywords::Diffuse => Ok(BRDF::Diffuse(DiffuseBRDF {
-                pigment: self.parse_pigment(1, var)?,
+                pigment: self.parse_pigment(nested + 1, var)?,
             })),
             Keywords::Specular => Ok(BRDF::Specular(SpecularBRDF {
-                pigment: self.parse_pigment(1, var)?,
+                pigment: self.parse_pigment(nested + 1, var)?,
                 threshold_angle_rad: PI / 1800.0,
             })),
+            Keywords::Dielectric => {
+                let pigment = self.parse_pigment(nested + 1, var)?;
+                // Can only be a eol or inline comment.
+                self.match_eol_or_inline_comment()?;
+                // Match indent aligned with pigment.
+                self.match_spaces(1, nested + 1)?;
+                self.match_keyword(Keywords::Ior)?;
+                self.match_symbol(' ')?;
+                let ior = self.match_number()?;
+                Ok(BRDF::Dielectric(DielectricBRDF { pigment, ior }))
+            },
+            Keywords::Microfacet => {
+                let pigment = self.parse_pigment(nested + 1, var)?;
+                // Can only be a eol or inline comment.
+                self.match_eol_or_inline_comment()?;
+                // Match indent aligned with pigment.
+                self.match_spaces(1, nested + 1)?;
+                self.match_keyword(Keywords::Roughness)?;
+                self.match_symbol(' ')?;
+                let roughness = self.match_number_in(|x| (0.0..=1.0).contains(&x), "in [0, 1]")?;
+                // Can only be a eol or inline comment.
+                self.match_eol_or_inline_comment()?;
+                // Match indent aligned with pigment.
+                self.match_spaces(1, nested + 1)?;
+                self.match_keyword(Keywords::Metallic)?;
+                self.match_symbol(' ')?;
+                let metallic = self.match_number_in(|x| (0.0..=1.0).contains(&x), "in [0, 1]")?;
+                Ok(BRDF::Microfacet(MicrofacetBRDF {
+                    pigment,
+                    roughness,
+                    metallic,
+                }))
+            },
+            Keywords::Coated => {
+                // Match indent aligned with base BRDF.
+                self.match_spaces(1, nested + 1)?;
+                self.match_keyword(Keywords::Ior)?;
+                self.match_symbol(' ')?;
+                let ior = self.match_number()?;
+                // Can only be a eol or inline comment.
+                self.match_eol_or_inline_comment()?;
+                // Match indent aligned with base BRDF.
+                self.match_spaces(1, nested + 1)?;
+                self.match_keyword(Keywords::Roughness)?;
+                self.match_symbol(' ')?;
+                let roughness = self.match_number()?;
+                // Can only be a eol or inline comment.
+                self.match_eol_or_inline_comment()?;
+                let base = self.parse_brdf(nested + 1, var)?;
+                Ok(BRDF::Coated(CoatedBRDF {
+                    base: Box::new(base),
+                    ior,
+                    roughness,
+                }))
+            },
             // This branch should never be triggered (a dummy error).
             _ => Err(SceneErr::UnexpectedMatch(String::from(
                 "unexpected match (report it to devel)",
@@ -810,7 +1108,7 @@ impl<R: Read> InputStream<R> {
         let (_, name) = self.match_identifier()?;
         // Can only be a eol or inline comment.
         self.match_eol_or_inline_comment()?;
-        let brdf = self.parse_brdf(var)?;
+        let brdf = self.parse_brdf(0, var)?;
         // Can only be a eol or inline comment.
         self.match_eol_or_inline_comment()?;
         let emitted_radiance = self.parse_pigment(0, var)?;
@@ -1053,87 +1351,168 @@ impl<R: Read> InputStream<R> {
         Ok(transformations)
     }
 
-    /// Parse shape inside shapes block using `var.materials` and `var.transformations`.\
+    /// Parse an [`Sdf`] (after `sdf:` keyword) from a list with its type followed
+    /// by its arguments,\
+    /// where operators combine the previously defined `sdfs`
+    /// and a `transform` uses `var.transformations`.\
     /// Otherwise return a variant of [`SceneErr`] error.
-    fn parse_shape(&mut self, var: &Var) -> Result<Box<dyn RayIntersection>, SceneErr> {
-        self.match_keyword(Keywords::Shape)?;
-        self.match_symbol(' ')?;
-        // Shape type e.g. sphere, plane.
-        let shapes = vec![Keywords::Plane, Keywords::Sphere];
+    fn parse_sdf(&mut self, sdfs: &BTreeMap<String, Sdf>, var: &Var) -> Result<Sdf, SceneErr> {
+        self.match_symbol('[')?;
         let token = self.read_token()?;
-        let shape = match token {
-            Token::Keyword(loc, key) => {
-                if shapes.contains(&key) {
-                    Ok(key)
-                } else {
-                    not_match!(loc, key, &shapes)
-                }
-            },
-            _ => not_matches!(token, &shapes),
+        let (loc, sdf_type) = match token {
+            Token::Identifier(loc, id) => Ok((loc, id)),
+            // If sdf type is named as a keyword (e.g. sphere), no problem.
+            Token::Keyword(loc, key) => Ok((loc, key.to_string())),
+            _ => not_matches!(token, "sdf type"),
         }?;
-        // Can only be a eol or inline comment.
-        self.match_eol_or_inline_comment()?;
-        // Match indent with shapes block spaces + 1 level (2 spaces).
-        self.match_spaces(1, 0)?;
-        self.match_keyword(Keywords::Material)?;
-        let (loc, material_id) = self.match_identifier()?;
-        // Match `material_id` from variables `var`.
-        let material =
-            var.materials
-                .get(&material_id)
+        self.match_symbol(',')?;
+        // Match `sdf_id` from previously defined `sdfs`.
+        let named_sdf = |(loc, sdf_id): (SourceLocation, String)| {
+            sdfs.get(&sdf_id)
                 .cloned()
                 .ok_or(SceneErr::UndefinedIdentifier {
                     loc,
-                    msg: format!("{material_id:?} material not defined"),
-                })?;
+                    msg: format!("{sdf_id:?} sdf not defined"),
+                })
+        };
+        let sdf = match sdf_type.as_str() {
+            "sphere" => {
+                self.match_symbol(' ')?;
+                Sdf::Sphere(SphereSdf {
+                    radius: self.match_number()?,
+                })
+            },
+            "box" => {
+                self.match_symbol(' ')?;
+                let half_size = self.parse_vector(var)?;
+                self.match_symbol(',')?;
+                self.match_symbol(' ')?;
+                Sdf::Box(BoxSdf {
+                    half_size,
+                    rounding: self.match_number()?,
+                })
+            },
+            "torus" => {
+                self.match_symbol(' ')?;
+                let major_radius = self.match_number()?;
+                self.match_symbol(',')?;
+                self.match_symbol(' ')?;
+                Sdf::Torus(TorusSdf {
+                    major_radius,
+                    minor_radius: self.match_number()?,
+                })
+            },
+            "mandelbulb" => {
+                self.match_symbol(' ')?;
+                let power = self.match_number()?;
+                self.match_symbol(',')?;
+                self.match_symbol(' ')?;
+                Sdf::Mandelbulb(MandelbulbSdf {
+                    power,
+                    iterations: self.match_number()? as u32,
+                })
+            },
+            "union" | "intersection" | "difference" => {
+                let operation = match sdf_type.as_str() {
+                    "union" => CsgOperation::Union,
+                    "intersection" => CsgOperation::Intersection,
+                    _ => CsgOperation::Difference,
+                };
+                let left = named_sdf(self.match_identifier()?)?;
+                self.match_symbol(',')?;
+                let right = named_sdf(self.match_identifier()?)?;
+                self.match_symbol(',')?;
+                self.match_symbol(' ')?;
+                Sdf::Combine(CombineSdf::new(
+                    operation,
+                    left,
+                    right,
+                    self.match_number()?,
+                ))
+            },
+            "repetition" => {
+                let sdf = named_sdf(self.match_identifier()?)?;
+                self.match_symbol(',')?;
+                self.match_symbol(' ')?;
+                Sdf::Repetition(RepetitionSdf::new(sdf, self.parse_vector(var)?))
+            },
+            "twist" => {
+                let sdf = named_sdf(self.match_identifier()?)?;
+                self.match_symbol(',')?;
+                self.match_symbol(' ')?;
+                Sdf::Twist(TwistSdf::new(sdf, self.match_number()?))
+            },
+            "transform" => {
+                let sdf = named_sdf(self.match_identifier()?)?;
+                self.match_symbol(',')?;
+                let (loc, transformation_id) = self.match_identifier()?;
+                // Match `transformation_id` from variables `var`.
+                let transformation = var.transformations.get(&transformation_id).copied().ok_or(
+                    SceneErr::UndefinedIdentifier {
+                        loc,
+                        msg: format!("{transformation_id:?} transformation not defined"),
+                    },
+                )?;
+                Sdf::Transform(TransformSdf::new(sdf, transformation))
+            },
+            _ => {
+                return Err(SceneErr::UndefinedIdentifier {
+                    loc,
+                    msg: format!(
+                        "{sdf_type:?} sdf type not defined, available [sphere, box, torus, \
+                         mandelbulb, union, intersection, difference, repetition, twist, transform]"
+                    ),
+                })
+            },
+        };
+        self.match_symbol(']')?;
+        Ok(sdf)
+    }
+
+    /// Parse a signed distance field from sdfs block combining [`parse_sdf`](#method.parse_sdf)
+    /// and put it inside `sdfs` map.\
+    /// Otherwise return a variant of [`SceneErr`] error.
+    fn parse_sdf_name(
+        &mut self,
+        sdfs: &mut BTreeMap<String, Sdf>,
+        var: &Var,
+    ) -> Result<(), SceneErr> {
+        self.match_keyword(Keywords::Name)?;
+        let (_, name) = self.match_identifier()?;
         // Can only be a eol or inline comment.
         self.match_eol_or_inline_comment()?;
-        // Match indent with shapes block spaces + 1 level (2 spaces).
+        // Match indent with sdfs block spaces + 1 level (2 spaces)
         self.match_spaces(1, 0)?;
-        self.match_keyword(Keywords::Transformation)?;
-        let (loc, transformation_id) = self.match_identifier()?;
-        // Match `transformation_id` from variables `var`.
-        let transformation = var.transformations.get(&transformation_id).copied().ok_or(
-            SceneErr::UndefinedIdentifier {
-                loc,
-                msg: format!("{transformation_id:?} transformation not defined"),
-            },
-        )?;
-        match shape {
-            Keywords::Plane => Ok(Box::new(Plane::new(transformation, material))),
-            Keywords::Sphere => Ok(Box::new(Sphere::new(transformation, material))),
-            // This branch should never be triggered (a dummy error).
-            _ => Err(SceneErr::UnexpectedMatch(String::from(
-                "unexpected match (report it to devel)",
-            ))),
-        }
+        self.match_keyword(Keywords::Sdf)?;
+        self.match_symbol(' ')?;
+        let sdf = self.parse_sdf(sdfs, var)?;
+        sdfs.insert(name, sdf);
+        Ok(())
     }
 
-    /// Parse shapes inside shapes block iterating
-    /// [`parse_shape`](#method.parse_shape) until the block end.\
+    /// Parse signed distance fields inside sdfs block iterating
+    /// [`parse_sdf_name`](#method.parse_sdf_name) until the block end.\
     /// Otherwise return a variant of [`SceneErr`] error.
-    fn parse_shapes(&mut self, var: &Var) -> Result<World, SceneErr> {
-        // Init an empty world object.
-        let mut shapes = World::default();
-        // The keyword `Keywords::Shapes` is parsed inside `parse_scene`.
-        // After 'shapes:' can only be a eol or inline comment.
+    fn parse_sdfs(&mut self, var: &Var) -> Result<BTreeMap<String, Sdf>, SceneErr> {
+        let mut sdfs = BTreeMap::new();
+        // The keyword `Keywords::Sdfs` is parsed inside `parse_scene`.
+        // After 'sdfs:' can only be a eol or inline comment.
         self.match_eol_or_inline_comment()?;
         // A minimum of one space indent is absolutely needed.
         self.match_symbol(' ')?;
-        // Count spaces for shapes block, used to parse indent.
+        // Count spaces for sdfs block, used to parse indent.
         self.count_spaces()?;
         self.match_symbol('-')?;
         self.match_symbol(' ')?;
-        shapes.add(self.parse_shape(var)?);
+        self.parse_sdf_name(&mut sdfs, var)?;
         loop {
             // Can only be a eol or inline comment.
             self.match_eol_or_inline_comment()?;
-            // Condition token: read a new shape or not?
+            // Condition token: read a new sdf or not?
             let tk_nx = self.read_token()?;
-            // If there is a space a new shape can be parsed.
-            // Otherwise stop with shapes block.
+            // If there is a space a new sdf can be parsed.
+            // Otherwise stop with sdfs block.
             // No other suppositions are made! To reduce grammar complexity.
-            // So for example no comment infra line of a block of shapes.
             if matches!(tk_nx, Token::Symbol(_, sym) if sym==' ') {
                 // Unread a space token to complete parse the correct
                 // indent using `match_spaces`.
@@ -1141,79 +1520,549 @@ impl<R: Read> InputStream<R> {
                 self.match_spaces(0, 0)?;
                 self.match_symbol('-')?;
                 self.match_symbol(' ')?;
-                shapes.add(self.parse_shape(var)?);
+                self.parse_sdf_name(&mut sdfs, var)?;
             } else {
-                // Unread condition token.
+                // Unread the condition token.
                 self.unread_token(tk_nx);
                 break;
             }
         }
-        Ok(shapes)
+        Ok(sdfs)
     }
 
-    /// Parse camera inside camera block using `var.materials` and `var.transformations`,\
-    /// and optionally for particular identifiers read standard values from `cli`.\
+    /// Parse an [`Instance`] (after `group:` keyword) of a group from `var.groups`,
+    /// placed by a transformation from `var.transformations`.\
     /// Otherwise return a variant of [`SceneErr`] error.
-    fn parse_camera(&mut self, var: &Var, cli: Cli) -> Result<Camera, SceneErr> {
-        // The keyword `Keywords::Camera` is parsed inside `parse_scene`.
-        // After 'camera:' can only be a eol or inline comment.
+    fn parse_instance(&mut self, var: &Var) -> Result<Box<dyn RayIntersection>, SceneErr> {
+        let (loc, group_id) = self.match_identifier()?;
+        // Match `group_id` from variables `var`.
+        let group = var
+            .groups
+            .get(&group_id)
+            .cloned()
+            .ok_or(SceneErr::UndefinedIdentifier {
+                loc,
+                msg: format!("{group_id:?} group not defined"),
+            })?;
+        // Can only be a eol or inline comment.
         self.match_eol_or_inline_comment()?;
-        // A minimum of one space indent is absolutely needed.
-        self.match_symbol(' ')?;
-        // Count spaces for camera block, used to parse indent.
-        self.count_spaces()?;
-        self.match_keyword(Keywords::Type)?;
-        self.match_symbol(' ')?;
-        // Fail fast if invalid camera type parsed.
-        let (loc, camera) = self.match_string()?;
-        if !(camera == "orthogonal" || camera == "perspective") {
-            return Err(SceneErr::InvalidCamera {
+        // Match indent with shapes block spaces + 1 level (2 spaces).
+        self.match_spaces(1, 0)?;
+        self.match_keyword(Keywords::Transformation)?;
+        let (loc, transformation_id) = self.match_identifier()?;
+        // Match `transformation_id` from variables `var`.
+        let transformation = var.transformations.get(&transformation_id).copied().ok_or(
+            SceneErr::UndefinedIdentifier {
                 loc,
-                msg: format!("found {camera:?} camera expected [\"orthogonal\", \"perspective\"]"),
-            });
-        }
+                msg: format!("{transformation_id:?} transformation not defined"),
+            },
+        )?;
+        Ok(Box::new(Instance::new(transformation, group)))
+    }
+
+    /// Parse a group of shapes inside groups block, a `name` followed by a list of `shapes`
+    /// parsed with [`parse_shape`](#method.parse_shape) (2 levels indented),\
+    /// and put it inside `var.groups`, so that next groups can instance it.\
+    /// Return `true` if a new group follows, otherwise a variant of [`SceneErr`] error.
+    fn parse_group(&mut self, var: &mut Var) -> Result<bool, SceneErr> {
+        self.match_keyword(Keywords::Name)?;
+        let (_, name) = self.match_identifier()?;
         // Can only be a eol or inline comment.
         self.match_eol_or_inline_comment()?;
-        // Match indent with camera block spaces.
-        self.match_spaces(0, 0)?;
-        self.match_keyword(Keywords::Ratio)?;
-        self.match_symbol(' ')?;
-        let ratio = self.match_number_cli(cli)?;
+        // Match indent with groups block spaces + 1 level (2 spaces).
+        self.match_spaces(1, 0)?;
+        self.match_keyword(Keywords::Shapes)?;
         // Can only be a eol or inline comment.
         self.match_eol_or_inline_comment()?;
-        // Init a default distance.
-        let mut distance = 1.0;
-        // Override it if the camera is perspective,
-        // otherwise will remain unused.
-        if camera == "perspective" {
-            // Match indent with camera block spaces.
-            self.match_spaces(0, 0)?;
-            self.match_keyword(Keywords::Distance)?;
-            self.match_symbol(' ')?;
-            distance = self.match_number_cli(cli)?;
+        // Match indent with groups block spaces + 2 levels (4 spaces).
+        self.match_spaces(2, 0)?;
+        self.match_symbol('-')?;
+        self.match_symbol(' ')?;
+        let block_spaces = self.spaces;
+        let mut shapes = Vec::new();
+        // Named shapes are not added to the group, but they can be combined by csg shapes.
+        let mut named = BTreeMap::new();
+        let next_group = loop {
+            // Shapes of the group are parsed as a shapes block indented by 2 levels.
+            self.spaces = block_spaces + 4;
+            let shape = self.parse_shape(var, &named);
+            self.spaces = block_spaces;
+            match shape? {
+                (Some(name), shape) => {
+                    named.insert(name, Arc::from(shape));
+                },
+                (None, shape) => shapes.push(Arc::from(shape)),
+            }
             // Can only be a eol or inline comment.
             self.match_eol_or_inline_comment()?;
-        }
-        // Match indent with camera block spaces.
-        self.match_spaces(0, 0)?;
-        self.match_keyword(Keywords::Transformation)?;
-        let (loc, transformation_id) = self.match_identifier()?;
-        // Match `transformation_id` from variables `var`.
-        let transformation = rotation_z(f32::to_radians(cli.angle_deg))
-            * var.transformations.get(&transformation_id).copied().ok_or(
-                SceneErr::UndefinedIdentifier {
-                    loc,
-                    msg: format!("{transformation_id:?} transformation not defined"),
+            // Condition token: read a new shape, a new group or not?
+            let tk_nx = self.read_token()?;
+            if !matches!(tk_nx, Token::Symbol(_, sym) if sym==' ') {
+                // Unread the condition token.
+                self.unread_token(tk_nx);
+                break false;
+            }
+            // Unread a space token to complete parse the correct
+            // indent using `match_spaces`.
+            self.unread_token(tk_nx);
+            self.match_spaces(0, 0)?;
+            let tk_nx_nx = self.read_token()?;
+            match tk_nx_nx {
+                // If there is '-' this is a new group.
+                Token::Symbol(_, '-') => break true,
+                // If there are more spaces this is a new shape of the group.
+                Token::Symbol(_, ' ') => {
+                    self.unread_token(tk_nx_nx);
+                    // Match the remaining indent of the 2 levels (4 spaces).
+                    for _ in 0..4 {
+                        self.match_symbol(' ')?;
+                    }
+                    self.match_symbol('-')?;
+                    self.match_symbol(' ')?;
                 },
-            )?;
-        match camera.as_str() {
-            "orthogonal" => Ok(Camera::Orthogonal(OrthogonalCamera::new(
-                ratio,
-                transformation,
-            ))),
-            "perspective" => Ok(Camera::Perspective(PerspectiveCamera::new(
+                // No other suppositions are made! To reduce grammar complexity.
+                _ => not_matches!(tk_nx_nx, "[' ', '-']")?,
+            }
+        };
+        var.groups.insert(name, Arc::new(Group::new(shapes)));
+        Ok(next_group)
+    }
+
+    /// Parse groups inside groups block iterating
+    /// [`parse_group`](#method.parse_group) until the block end.\
+    /// Otherwise return a variant of [`SceneErr`] error.
+    fn parse_groups(
+        &mut self,
+        var: &Var,
+    ) -> Result<BTreeMap<String, Arc<dyn RayIntersection>>, SceneErr> {
+        // Groups can instance the previously defined ones.
+        let mut var = var.clone();
+        // The keyword `Keywords::Groups` is parsed inside `parse_scene`.
+        // After 'groups:' can only be a eol or inline comment.
+        self.match_eol_or_inline_comment()?;
+        // A minimum of one space indent is absolutely needed.
+        self.match_symbol(' ')?;
+        // Count spaces for groups block, used to parse indent.
+        self.count_spaces()?;
+        self.match_symbol('-')?;
+        self.match_symbol(' ')?;
+        while self.parse_group(&mut var)? {
+            self.match_symbol(' ')?;
+        }
+        Ok(var.groups)
+    }
+
+    /// Parse shape inside shapes block using `var.materials` and `var.transformations`.\
+    /// A shape is a `shape` of a particular type, a `mesh` read from a Wavefront OBJ file,
+    /// an `sdf` surface from `var.sdfs`, an [`Instance`] of a `group` from `var.groups`,\
+    /// or a `csg` combination of two `named` shapes.\
+    /// The shape can be preceded by a `name`, returned together with the shape.\
+    /// Otherwise return a variant of [`SceneErr`] error.
+    fn parse_shape(
+        &mut self,
+        var: &Var,
+        named: &BTreeMap<String, Arc<dyn RayIntersection>>,
+    ) -> Result<(Option<String>, Box<dyn RayIntersection>), SceneErr> {
+        let mut name = None;
+        let mut shape = self.match_keywords(&vec![
+            Keywords::Csg,
+            Keywords::Group,
+            Keywords::Mesh,
+            Keywords::Name,
+            Keywords::Sdf,
+            Keywords::Shape,
+        ])?;
+        if shape == Keywords::Name {
+            name = Some(self.match_identifier()?.1);
+            // Can only be a eol or inline comment.
+            self.match_eol_or_inline_comment()?;
+            // Match indent with shapes block spaces + 1 level (2 spaces).
+            self.match_spaces(1, 0)?;
+            shape = self.match_keywords(&vec![
+                Keywords::Csg,
+                Keywords::Group,
+                Keywords::Mesh,
+                Keywords::Sdf,
+                Keywords::Shape,
+            ])?;
+        }
+        if shape == Keywords::Csg {
+            return Ok((name, self.parse_csg(named)?));
+        }
+        if shape == Keywords::Group {
+            return Ok((name, self.parse_instance(var)?));
+        }
+        self.match_symbol(' ')?;
+        let (mut mesh, mut sdf) = (None, None);
+        let shape =
+            match shape {
+                Keywords::Mesh => {
+                    let (loc, obj_file) = self.match_string()?;
+                    mesh = Some(Arc::new(
+                        Mesh::read_obj_file(Path::new(&obj_file)).map_err(|err| {
+                            SceneErr::ObjFileReadFailure {
+                                loc,
+                                msg: format!("{obj_file:?} obj file read failure"),
+                                src: err,
+                            }
+                        })?,
+                    ));
+                    Ok(Keywords::Mesh)
+                },
+                Keywords::Sdf => {
+                    let token = self.read_token()?;
+                    // Match `sdf_id` from variables `var`.
+                    sdf =
+                        Some(match token {
+                            Token::Identifier(loc, sdf_id) => var.sdfs.get(&sdf_id).cloned().ok_or(
+                                SceneErr::UndefinedIdentifier {
+                                    loc,
+                                    msg: format!("{sdf_id:?} sdf not defined"),
+                                },
+                            ),
+                            _ => not_matches!(token, "identifier"),
+                        }?);
+                    Ok(Keywords::Sdf)
+                },
+                _ => {
+                    // Shape type e.g. sphere, plane.
+                    let shapes = vec![
+                        Keywords::Box,
+                        Keywords::Cone,
+                        Keywords::Cylinder,
+                        Keywords::Disk,
+                        Keywords::Plane,
+                        Keywords::Sphere,
+                        Keywords::Torus,
+                        Keywords::Triangle,
+                    ];
+                    let token = self.read_token()?;
+                    match token {
+                        Token::Keyword(loc, key) => {
+                            if shapes.contains(&key) {
+                                Ok(key)
+                            } else {
+                                not_match!(loc, key, &shapes)
+                            }
+                        },
+                        _ => not_matches!(token, &shapes),
+                    }
+                },
+            }?;
+        // Can only be a eol or inline comment.
+        self.match_eol_or_inline_comment()?;
+        // Match indent with shapes block spaces + 1 level (2 spaces).
+        self.match_spaces(1, 0)?;
+        let mut minor_radius = 0.0;
+        if shape == Keywords::Torus {
+            // A torus needs also the radius of its tube.
+            self.match_keyword(Keywords::Radius)?;
+            self.match_symbol(' ')?;
+            minor_radius = self.match_number_in(|x| x > 0. && x < 1., "in (0, 1)")?;
+            // Can only be a eol or inline comment.
+            self.match_eol_or_inline_comment()?;
+            // Match indent with shapes block spaces + 1 level (2 spaces).
+            self.match_spaces(1, 0)?;
+        }
+        self.match_keyword(Keywords::Material)?;
+        let (loc, material_id) = self.match_identifier()?;
+        // Match `material_id` from variables `var`.
+        let material =
+            var.materials
+                .get(&material_id)
+                .cloned()
+                .ok_or(SceneErr::UndefinedIdentifier {
+                    loc,
+                    msg: format!("{material_id:?} material not defined"),
+                })?;
+        // Can only be a eol or inline comment.
+        self.match_eol_or_inline_comment()?;
+        // Match indent with shapes block spaces + 1 level (2 spaces).
+        self.match_spaces(1, 0)?;
+        self.match_keyword(Keywords::Transformation)?;
+        let (loc, transformation_id) = self.match_identifier()?;
+        // Match `transformation_id` from variables `var`.
+        let transformation = var.transformations.get(&transformation_id).copied().ok_or(
+            SceneErr::UndefinedIdentifier {
+                loc,
+                msg: format!("{transformation_id:?} transformation not defined"),
+            },
+        )?;
+        let shape: Box<dyn RayIntersection> = match shape {
+            Keywords::Box => Box::new(Cube::new(transformation, material)),
+            Keywords::Cone => Box::new(Cone::new(transformation, material)),
+            Keywords::Cylinder => Box::new(Cylinder::new(transformation, material)),
+            Keywords::Disk => Box::new(Disk::new(transformation, material)),
+            Keywords::Plane => Box::new(Plane::new(transformation, material)),
+            Keywords::Sphere => Box::new(Sphere::new(transformation, material)),
+            Keywords::Torus => Box::new(Torus::new(transformation, material, minor_radius)),
+            Keywords::Triangle => Box::new(Triangle::new(transformation, material)),
+            Keywords::Mesh => Box::new(TriangleMesh::new(mesh.unwrap(), transformation, material)),
+            Keywords::Sdf => Box::new(SdfShape::new(transformation, material, sdf.unwrap())),
+            // This branch should never be triggered (a dummy error).
+            _ => {
+                return Err(SceneErr::UnexpectedMatch(String::from(
+                    "unexpected match (report it to devel)",
+                )))
+            },
+        };
+        Ok((name, shape))
+    }
+
+    /// Parse a [`CsgOperation`] from an identifier.\
+    /// Otherwise return a variant of [`SceneErr`] error.
+    fn parse_csg_operation(&mut self) -> Result<CsgOperation, SceneErr> {
+        let (loc, operation) = self.match_identifier()?;
+        match operation.as_str() {
+            "union" => Ok(CsgOperation::Union),
+            "intersection" => Ok(CsgOperation::Intersection),
+            "difference" => Ok(CsgOperation::Difference),
+            _ => Err(SceneErr::UndefinedIdentifier {
+                loc,
+                msg: format!(
+                    "{operation:?} csg operation not defined, available [union, intersection, difference]"
+                ),
+            }),
+        }
+    }
+
+    /// Parse a [`Csg`] shape (after `csg:` keyword) combining two `named` shapes.\
+    /// Otherwise return a variant of [`SceneErr`] error.
+    fn parse_csg(
+        &mut self,
+        named: &BTreeMap<String, Arc<dyn RayIntersection>>,
+    ) -> Result<Box<dyn RayIntersection>, SceneErr> {
+        let operation = self.parse_csg_operation()?;
+        // Can only be a eol or inline comment.
+        self.match_eol_or_inline_comment()?;
+        // Match indent with shapes block spaces + 1 level (2 spaces).
+        self.match_spaces(1, 0)?;
+        self.match_keyword(Keywords::Shapes)?;
+        self.match_symbol(' ')?;
+        self.match_symbol('[')?;
+        // Match `shape_id` from `named` shapes.
+        let named_shape = |(loc, shape_id): (SourceLocation, String)| {
+            named
+                .get(&shape_id)
+                .cloned()
+                .ok_or(SceneErr::UndefinedIdentifier {
+                    loc,
+                    msg: format!("{shape_id:?} shape not defined"),
+                })
+        };
+        let left = named_shape(self.read_identifier()?)?;
+        self.match_symbol(',')?;
+        let right = named_shape(self.match_identifier()?)?;
+        self.match_symbol(']')?;
+        Ok(Box::new(Csg::new(operation, left, right)))
+    }
+
+    /// Parse shapes inside shapes block iterating
+    /// [`parse_shape`](#method.parse_shape) until the block end.\
+    /// Otherwise return a variant of [`SceneErr`] error.
+    fn parse_shapes(&mut self, var: &Var) -> Result<World, SceneErr> {
+        // Init an empty world object.
+        let mut shapes = World::default();
+        // Named shapes are not added to the world, but they can be combined by csg shapes.
+        let mut named = BTreeMap::new();
+        // The keyword `Keywords::Shapes` is parsed inside `parse_scene`.
+        // After 'shapes:' can only be a eol or inline comment.
+        self.match_eol_or_inline_comment()?;
+        // A minimum of one space indent is absolutely needed.
+        self.match_symbol(' ')?;
+        // Count spaces for shapes block, used to parse indent.
+        self.count_spaces()?;
+        self.match_symbol('-')?;
+        self.match_symbol(' ')?;
+        loop {
+            match self.parse_shape(var, &named)? {
+                (Some(name), shape) => {
+                    named.insert(name, Arc::from(shape));
+                },
+                (None, shape) => shapes.add(shape),
+            }
+            // Can only be a eol or inline comment.
+            self.match_eol_or_inline_comment()?;
+            // Condition token: read a new shape or not?
+            let tk_nx = self.read_token()?;
+            // If there is a space a new shape can be parsed.
+            // Otherwise stop with shapes block.
+            // No other suppositions are made! To reduce grammar complexity.
+            // So for example no comment infra line of a block of shapes.
+            if matches!(tk_nx, Token::Symbol(_, sym) if sym==' ') {
+                // Unread a space token to complete parse the correct
+                // indent using `match_spaces`.
+                self.unread_token(tk_nx);
+                self.match_spaces(0, 0)?;
+                self.match_symbol('-')?;
+                self.match_symbol(' ')?;
+            } else {
+                // Unread condition token.
+                self.unread_token(tk_nx);
+                break;
+            }
+        }
+        // All shapes are parsed, now build the acceleration structure once.
+        shapes.build_bvh();
+        Ok(shapes)
+    }
+
+    /// Parse a point light inside lights block using `var.colors` and `var.vectors`.\
+    /// Otherwise return a variant of [`SceneErr`] error.
+    fn parse_light(&mut self, var: &Var) -> Result<PointLight, SceneErr> {
+        self.match_keyword(Keywords::Position)?;
+        self.match_symbol(' ')?;
+        let position = self.parse_vector(var)?;
+        // Can only be a eol or inline comment.
+        self.match_eol_or_inline_comment()?;
+        // Match indent with lights block spaces + 1 level (2 spaces).
+        self.match_spaces(1, 0)?;
+        self.match_keyword(Keywords::Color)?;
+        self.match_symbol(' ')?;
+        let color = self.parse_color(var)?;
+        // Can only be a eol or inline comment.
+        self.match_eol_or_inline_comment()?;
+        // Match indent with lights block spaces + 1 level (2 spaces).
+        self.match_spaces(1, 0)?;
+        self.match_keyword(Keywords::Radius)?;
+        self.match_symbol(' ')?;
+        let linear_radius = self.match_number()?;
+        Ok(PointLight::new(
+            Point::from((position.x, position.y, position.z)),
+            color,
+            linear_radius,
+        ))
+    }
+
+    /// Parse point lights inside lights block iterating
+    /// [`parse_light`](#method.parse_light) until the block end.\
+    /// Otherwise return a variant of [`SceneErr`] error.
+    fn parse_lights(&mut self, var: &Var) -> Result<Vec<PointLight>, SceneErr> {
+        let mut lights = Vec::new();
+        // The keyword `Keywords::Lights` is parsed inside `parse_scene`.
+        // After 'lights:' can only be a eol or inline comment.
+        self.match_eol_or_inline_comment()?;
+        // A minimum of one space indent is absolutely needed.
+        self.match_symbol(' ')?;
+        // Count spaces for lights block, used to parse indent.
+        self.count_spaces()?;
+        self.match_symbol('-')?;
+        self.match_symbol(' ')?;
+        lights.push(self.parse_light(var)?);
+        loop {
+            // Can only be a eol or inline comment.
+            self.match_eol_or_inline_comment()?;
+            // Condition token: read a new light or not?
+            let tk_nx = self.read_token()?;
+            // If there is a space a new light can be parsed.
+            // Otherwise stop with lights block.
+            if matches!(tk_nx, Token::Symbol(_, sym) if sym==' ') {
+                // Unread a space token to complete parse the correct
+                // indent using `match_spaces`.
+                self.unread_token(tk_nx);
+                self.match_spaces(0, 0)?;
+                self.match_symbol('-')?;
+                self.match_symbol(' ')?;
+                lights.push(self.parse_light(var)?);
+            } else {
+                // Unread the condition token.
+                self.unread_token(tk_nx);
+                break;
+            }
+        }
+        Ok(lights)
+    }
+
+    /// Parse camera inside camera block using `var.materials` and `var.transformations`,\
+    /// and optionally for particular identifiers read standard values from `cli`.\
+    /// Otherwise return a variant of [`SceneErr`] error.
+    fn parse_camera(&mut self, var: &Var, cli: Cli) -> Result<Camera, SceneErr> {
+        // The keyword `Keywords::Camera` is parsed inside `parse_scene`.
+        // After 'camera:' can only be a eol or inline comment.
+        self.match_eol_or_inline_comment()?;
+        // A minimum of one space indent is absolutely needed.
+        self.match_symbol(' ')?;
+        // Count spaces for camera block, used to parse indent.
+        self.count_spaces()?;
+        self.match_keyword(Keywords::Type)?;
+        self.match_symbol(' ')?;
+        // Fail fast if invalid camera type parsed.
+        let (loc, camera) = self.match_string()?;
+        if !(camera == "orthogonal" || camera == "perspective") {
+            return Err(SceneErr::InvalidCamera {
+                loc,
+                msg: format!("found {camera:?} camera expected [\"orthogonal\", \"perspective\"]"),
+            });
+        }
+        // Can only be a eol or inline comment.
+        self.match_eol_or_inline_comment()?;
+        // Match indent with camera block spaces.
+        self.match_spaces(0, 0)?;
+        self.match_keyword(Keywords::Ratio)?;
+        self.match_symbol(' ')?;
+        let ratio = self.match_number_cli(cli)?;
+        // Can only be a eol or inline comment.
+        self.match_eol_or_inline_comment()?;
+        // Init a default distance.
+        let mut distance = 1.0;
+        // Override it if the camera is perspective,
+        // otherwise will remain unused.
+        if camera == "perspective" {
+            // Match indent with camera block spaces.
+            self.match_spaces(0, 0)?;
+            self.match_keyword(Keywords::Distance)?;
+            self.match_symbol(' ')?;
+            distance = self.match_number_cli(cli)?;
+            // Can only be a eol or inline comment.
+            self.match_eol_or_inline_comment()?;
+        }
+        // Match indent with camera block spaces.
+        self.match_spaces(0, 0)?;
+        // Init a pinhole (no lens).
+        let mut aperture = 0.0;
+        let mut focal_distance = distance;
+        // A perspective camera could have a thin lens,
+        // defined by its aperture and its focus distance.
+        let keyword = if camera == "perspective" {
+            self.match_keywords(&vec![Keywords::Aperture, Keywords::Transformation])?
+        } else {
+            self.match_keywords(&vec![Keywords::Transformation])?
+        };
+        if keyword == Keywords::Aperture {
+            self.match_symbol(' ')?;
+            aperture = self.match_number()?;
+            // Can only be a eol or inline comment.
+            self.match_eol_or_inline_comment()?;
+            // Match indent with camera block spaces.
+            self.match_spaces(0, 0)?;
+            self.match_keyword(Keywords::Focus)?;
+            self.match_symbol(' ')?;
+            focal_distance = self.match_number()?;
+            // Can only be a eol or inline comment.
+            self.match_eol_or_inline_comment()?;
+            // Match indent with camera block spaces.
+            self.match_spaces(0, 0)?;
+            self.match_keyword(Keywords::Transformation)?;
+        }
+        let (loc, transformation_id) = self.match_identifier()?;
+        // Match `transformation_id` from variables `var`.
+        let transformation = rotation_z(f32::to_radians(cli.angle_deg))
+            * var.transformations.get(&transformation_id).copied().ok_or(
+                SceneErr::UndefinedIdentifier {
+                    loc,
+                    msg: format!("{transformation_id:?} transformation not defined"),
+                },
+            )?;
+        match camera.as_str() {
+            "orthogonal" => Ok(Camera::Orthogonal(OrthogonalCamera::new(
+                ratio,
+                transformation,
+            ))),
+            "perspective" => Ok(Camera::Perspective(PerspectiveCamera::thin_lens(
                 distance,
                 ratio,
+                aperture,
+                focal_distance,
                 transformation,
             ))),
             // This branch should never be triggered (a dummy error).
@@ -1223,6 +2072,101 @@ impl<R: Read> InputStream<R> {
         }
     }
 
+    /// Parse environment light inside environment block using `var.transformations`
+    /// and `var.vectors`,\
+    /// either an [`EnvironmentLight`] map or a [`SkyLight`] daylight.\
+    /// Otherwise return a variant of [`SceneErr`] error.
+    fn parse_environment(&mut self, var: &Var) -> Result<Environment, SceneErr> {
+        // The keyword `Keywords::Environment` is parsed inside `parse_scene`.
+        // After 'environment:' can only be a eol or inline comment.
+        self.match_eol_or_inline_comment()?;
+        // A minimum of one space indent is absolutely needed.
+        self.match_symbol(' ')?;
+        // Count spaces for environment block, used to parse indent.
+        self.count_spaces()?;
+        let environment = self.match_keywords(&vec![Keywords::Image, Keywords::Sun])?;
+        self.match_symbol(' ')?;
+        match environment {
+            Keywords::Image => {
+                let (loc, hdr_file) = self.match_string()?;
+                let image = HdrImage::read_hdr_file(Path::new(&hdr_file)).map_err(|err| {
+                    SceneErr::PfmFileReadFailure {
+                        loc,
+                        msg: format!("{hdr_file:?} hdr file read failure"),
+                        src: err,
+                    }
+                })?;
+                // Can only be a eol or inline comment.
+                self.match_eol_or_inline_comment()?;
+                // Match indent with environment block spaces.
+                self.match_spaces(0, 0)?;
+                self.match_keyword(Keywords::Intensity)?;
+                self.match_symbol(' ')?;
+                let intensity = self.match_number()?;
+                // Can only be a eol or inline comment.
+                self.match_eol_or_inline_comment()?;
+                // Match indent with environment block spaces.
+                self.match_spaces(0, 0)?;
+                self.match_keyword(Keywords::Transformation)?;
+                let (loc, transformation_id) = self.match_identifier()?;
+                // Match `transformation_id` from variables `var`.
+                let transformation = var.transformations.get(&transformation_id).copied().ok_or(
+                    SceneErr::UndefinedIdentifier {
+                        loc,
+                        msg: format!("{transformation_id:?} transformation not defined"),
+                    },
+                )?;
+                Ok(Environment::Map(EnvironmentLight::new(
+                    image,
+                    intensity,
+                    transformation,
+                )))
+            },
+            Keywords::Sun => {
+                let loc = self.location;
+                let sun_direction = self.parse_vector(var)?;
+                if sun_direction.norm() == 0. {
+                    return Err(SceneErr::InvalidValue {
+                        loc,
+                        msg: String::from("found zero vector expected sun direction"),
+                    });
+                }
+                // Can only be a eol or inline comment.
+                self.match_eol_or_inline_comment()?;
+                // Match indent with environment block spaces.
+                self.match_spaces(0, 0)?;
+                self.match_keyword(Keywords::Turbidity)?;
+                self.match_symbol(' ')?;
+                let turbidity =
+                    self.match_number_in(|x| (2.0..=10.0).contains(&x), "in [2, 10]")?;
+                // Can only be a eol or inline comment.
+                self.match_eol_or_inline_comment()?;
+                // Match indent with environment block spaces.
+                self.match_spaces(0, 0)?;
+                self.match_keyword(Keywords::Diameter)?;
+                self.match_symbol(' ')?;
+                let diameter = self.match_number_in(|x| x > 0. && x < 180., "in (0, 180)")?;
+                // Can only be a eol or inline comment.
+                self.match_eol_or_inline_comment()?;
+                // Match indent with environment block spaces.
+                self.match_spaces(0, 0)?;
+                self.match_keyword(Keywords::Intensity)?;
+                self.match_symbol(' ')?;
+                let intensity = self.match_number()?;
+                Ok(Environment::Sky(SkyLight::new(
+                    sun_direction,
+                    turbidity,
+                    f32::to_radians(diameter),
+                    intensity,
+                )))
+            },
+            // This branch should never be triggered (a dummy error).
+            _ => Err(SceneErr::UnexpectedMatch(String::from(
+                "unexpected match (report it to devel)",
+            ))),
+        }
+    }
+
     /// Parse a scene in all its entirety.
     ///
     /// Blocks that must exist:
@@ -1232,28 +2176,42 @@ impl<R: Read> InputStream<R> {
     ///
     /// Optionals:
     ///  * colors;
+    ///  * environment;
+    ///  * groups;
+    ///  * lights;
+    ///  * sdfs;
     ///  * transformations.
     ///
     /// Blocks can be separated by multiple break line.
     ///
-    /// When a camera and world (list of shapes) are parsed stop scene parsing.
+    /// When a camera and world (list of shapes) are parsed stop scene parsing,\
+    /// unless other blocks follow.
     fn parse_scene(&mut self, cli: Cli) -> Result<Scene, SceneErr> {
         let mut block;
         let mut var = Var::default();
         let mut scene = Scene::default();
+        let mut lights = Vec::new();
+        let mut environment = None;
         let mut blocks = vec![
             Keywords::Camera,
             Keywords::Colors,
+            Keywords::Environment,
+            Keywords::Groups,
+            Keywords::Lights,
             Keywords::Materials,
+            Keywords::Sdfs,
             Keywords::Shapes,
             Keywords::Transformations,
         ];
-        // Loop over expected blocks until `Camera` and `World` are created.
-        // Or until eof is reached.
+        // Loop over expected blocks until `Camera` and `World` are created
+        // and eof is reached.
         loop {
-            if !(scene.camera.is_some() && scene.shapes.is_some()) {
-                // Try to ignore whitespaces and comments infra-blocks.
-                self.match_whitespaces_and_comments()?;
+            // Try to ignore whitespaces and comments infra-blocks.
+            self.match_whitespaces_and_comments()?;
+            let token = self.read_token()?;
+            let eof = matches!(token, Token::Stop(_));
+            self.unread_token(token);
+            if !(eof && scene.camera.is_some() && scene.shapes.is_some()) {
                 block = self.match_keywords(&blocks)?;
                 match block {
                     // Build a `Camera` in `scene` using `var`.
@@ -1268,6 +2226,23 @@ impl<R: Read> InputStream<R> {
                         var.colors.append(&mut self.parse_colors(&var)?);
                         blocks.remove(blocks.iter().position(|&k| k == Keywords::Colors).unwrap());
                     },
+                    // Build the environment light, added to `World` at the end.
+                    // And remove it from `blocks`, because was found.
+                    Keywords::Environment => {
+                        environment = Some(self.parse_environment(&var)?);
+                        blocks.remove(
+                            blocks
+                                .iter()
+                                .position(|&k| k == Keywords::Environment)
+                                .unwrap(),
+                        );
+                    },
+                    // Update groups in `var` if groups block is found.
+                    // And remove it from `blocks`, because was found.
+                    Keywords::Groups => {
+                        var.groups = self.parse_groups(&var)?;
+                        blocks.remove(blocks.iter().position(|&k| k == Keywords::Groups).unwrap());
+                    },
                     // Update materials in `var` if materials block is found.
                     // And remove it from `blocks`, because was found.
                     Keywords::Materials => {
@@ -1279,6 +2254,18 @@ impl<R: Read> InputStream<R> {
                                 .unwrap(),
                         );
                     },
+                    // Collect point lights, added to `World` at the end.
+                    // And remove it from `blocks`, because was found.
+                    Keywords::Lights => {
+                        lights = self.parse_lights(&var)?;
+                        blocks.remove(blocks.iter().position(|&k| k == Keywords::Lights).unwrap());
+                    },
+                    // Update sdfs in `var` if sdfs block is found.
+                    // And remove it from `blocks`, because was found.
+                    Keywords::Sdfs => {
+                        var.sdfs.append(&mut self.parse_sdfs(&var)?);
+                        blocks.remove(blocks.iter().position(|&k| k == Keywords::Sdfs).unwrap());
+                    },
                     // Build a `World` in `scene` using `var`.
                     // And remove it from `blocks`, because was found.
                     Keywords::Shapes => {
@@ -1304,6 +2291,12 @@ impl<R: Read> InputStream<R> {
                 break;
             }
         }
+        if let Some(world) = scene.shapes.as_mut() {
+            lights.into_iter().for_each(|light| world.add_light(light));
+            if let Some(environment) = environment {
+                world.set_environment(environment);
+            }
+        }
         Ok(scene)
     }
 }
@@ -1317,8 +2310,12 @@ impl<R: Read> InputStream<R> {
 struct Var {
     /// Map of colors.
     colors: BTreeMap<String, Color>,
+    /// Map of groups of shapes.
+    groups: BTreeMap<String, Arc<dyn RayIntersection>>,
     /// Map of materials.
     materials: BTreeMap<String, Material>,
+    /// Map of signed distance fields.
+    sdfs: BTreeMap<String, Sdf>,
     /// Map of transformations.
     transformations: BTreeMap<String, Transformation>,
     /// Map of vectors.
@@ -1332,7 +2329,9 @@ impl Default for Var {
         let mut colors = BTreeMap::new();
         colors.insert(String::from("BLACK"), BLACK);
         colors.insert(String::from("WHITE"), WHITE);
+        let groups = BTreeMap::new();
         let materials = BTreeMap::new();
+        let sdfs = BTreeMap::new();
         let mut transformations = BTreeMap::new();
         transformations.insert(String::from("IDENTITY"), Transformation::default());
         let mut vectors = BTreeMap::new();
@@ -1341,7 +2340,9 @@ impl Default for Var {
         vectors.insert(String::from("E3"), E3);
         Self {
             colors,
+            groups,
             materials,
+            sdfs,
             transformations,
             vectors,
         }
@@ -1373,6 +2374,7 @@ impl Scene {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::misc::ByteOrder;
     use std::io::{BufWriter, Cursor, Write};
 
     #[test]
@@ -1597,6 +2599,38 @@ mod test {
             matches!(input.parse_camera(&var, cli), Ok(Camera::Orthogonal(cam)) if cam==OrthogonalCamera::new(0.5, Transformation::default()))
         );
 
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "camera:\n",
+            "  type: \"perspective\"\n",
+            "  ratio: 0.5\n",
+            "  distance: 2.0\n",
+            "  aperture: 0.1   # lens diameter\n",
+            "  focus: 4.5\n",
+            "  transformation: camera\n",
+        )));
+
+        assert!(input.match_whitespaces_and_comments().is_ok());
+        assert!(input.match_keyword(Keywords::Camera).is_ok());
+        assert!(
+            matches!(input.parse_camera(&var, cli), Ok(Camera::Perspective(cam)) if cam==PerspectiveCamera::thin_lens(2.0, 0.5, 0.1, 4.5, Transformation::default()))
+        );
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "camera:\n",
+            "  type: \"orthogonal\"\n",
+            "  ratio: 0.5\n",
+            "  aperture: 0.1\n",
+            "  focus: 4.5\n",
+            "  transformation: camera\n",
+        )));
+
+        assert!(input.match_whitespaces_and_comments().is_ok());
+        assert!(input.match_keyword(Keywords::Camera).is_ok());
+        assert!(matches!(
+            input.parse_camera(&var, cli),
+            Err(SceneErr::NotMatch { loc, .. }) if loc.line_num==4 && loc.col_num==3
+        ));
+
         let mut input = InputStream::new(Cursor::new(concat!(
             "\n",
             "\n  # This is a double spaced comment",
@@ -1764,45 +2798,190 @@ mod test {
             "    uniform: [2.1, 9.0, 7.3]\n",
         )));
 
-        assert!(input.match_whitespaces_and_comments().is_ok());
+        assert!(input.match_whitespaces_and_comments().is_ok());
+        assert!(input.match_keyword(Keywords::Materials).is_ok());
+        assert!(matches!(
+            input.parse_materials(&var),
+            Err(SceneErr::NotMatch { loc, .. }) if loc.line_num==3 && loc.col_num==5
+        ));
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "materials:\n",
+            "  - name: sky\n",
+            "    specular:\n",
+            "      uniform: [1.2, 1.3, 1.4]\n",
+            "    not_uniform: [2.1, 9.0, 7.3]\n",
+        )));
+
+        assert!(input.match_whitespaces_and_comments().is_ok());
+        assert!(input.match_keyword(Keywords::Materials).is_ok());
+        assert!(matches!(
+            input.parse_materials(&var),
+            Err(SceneErr::NotMatch { loc, .. }) if loc.line_num==5 && loc.col_num==5
+        ));
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "materials:\n",
+            "   - name: sky\n",
+            "     specular:\n",
+            "         uniform: [1.2, 0.9, 3.7]\n",
+            "     uniform: random # This is an inline comment\n",
+            "   - name: ground\n",
+            "     diffuse:\n",
+            "        checkered: [BLACK, WHITE, 7.]\n",
+            "     uniform: [2.1, 9.0, 7.3]\n",
+        )));
+
+        assert!(input.match_whitespaces_and_comments().is_ok());
+        assert!(input.match_keyword(Keywords::Materials).is_ok());
+        assert!(matches!(
+            input.parse_materials(&var),
+            Err(SceneErr::NotMatch { loc, .. }) if loc.line_num==4 && loc.col_num==9
+        ));
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "materials:\n",
+            "  - name: glass\n",
+            "    dielectric:\n",
+            "      uniform: WHITE\n",
+            "      ior: 1.33\n",
+            "    uniform: BLACK\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Materials).is_ok());
+        let materials = input.parse_materials(&var);
+        assert!(
+            matches!(materials.as_ref().unwrap().get("glass"), Some(glass) if matches!(&glass.brdf, BRDF::Dielectric(dl) if dl.ior==1.33 && matches!(dl.pigment, Pigment::Uniform(pg) if pg.color==WHITE)))
+        );
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "materials:\n",
+            "  - name: glass\n",
+            "    dielectric:\n",
+            "      uniform: WHITE\n",
+            "    uniform: BLACK\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Materials).is_ok());
+        assert!(matches!(
+            input.parse_materials(&var),
+            Err(SceneErr::NotMatch { loc, .. }) if loc.line_num==5 && loc.col_num==5
+        ));
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "materials:\n",
+            "  - name: gold\n",
+            "    microfacet:\n",
+            "      uniform: [1, 0.8, 0.3]\n",
+            "      roughness: 0.25\n",
+            "      metallic: 1\n",
+            "    uniform: BLACK\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Materials).is_ok());
+        let materials = input.parse_materials(&var);
+        assert!(
+            matches!(materials.as_ref().unwrap().get("gold"), Some(gold) if matches!(&gold.brdf, BRDF::Microfacet(mf) if mf.roughness==0.25 && mf.metallic==1. && matches!(mf.pigment, Pigment::Uniform(pg) if pg.color==Color::from((1., 0.8, 0.3)))))
+        );
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "materials:\n",
+            "  - name: gold\n",
+            "    microfacet:\n",
+            "      uniform: [1, 0.8, 0.3]\n",
+            "      roughness: 0.25\n",
+            "      metallic: 1.5\n",
+            "    uniform: BLACK\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Materials).is_ok());
+        assert!(matches!(
+            input.parse_materials(&var),
+            Err(SceneErr::InvalidValue { loc, .. }) if loc.line_num==6 && loc.col_num==17
+        ));
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "materials:\n",
+            "  - name: paint\n",
+            "    coated:\n",
+            "      ior: 1.5\n",
+            "      roughness: 0\n",
+            "      coated:\n",
+            "        ior: 1.3\n",
+            "        roughness: 0.2\n",
+            "        diffuse:\n",
+            "          uniform: [0.6, 0, 0]\n",
+            "    uniform: BLACK\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Materials).is_ok());
+        let materials = input.parse_materials(&var);
+        assert!(
+            matches!(materials.as_ref().unwrap().get("paint"), Some(paint) if matches!(&paint.brdf, BRDF::Coated(coat) if coat.ior==1.5 && coat.roughness==0. && matches!(coat.base.as_ref(), BRDF::Coated(inner) if inner.ior==1.3 && inner.roughness==0.2 && matches!(inner.base.as_ref(), BRDF::Diffuse(df) if matches!(df.pigment, Pigment::Uniform(pg) if pg.color==Color::from((0.6, 0., 0.)))))))
+        );
+
+        HdrImage::new(2, 2)
+            .write_pfm_file(
+                Path::new("/tmp/filter_reference.pfm"),
+                ByteOrder::LittleEndian,
+            )
+            .unwrap();
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "materials:\n",
+            "  - name: sky\n",
+            "    diffuse:\n",
+            "      image: ['/tmp/filter_reference.pfm', bicubic, mirror]\n",
+            "    image: ['/tmp/filter_reference.pfm', bilinear]\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Materials).is_ok());
+        let materials = input.parse_materials(&var);
+        assert!(
+            matches!(materials.as_ref().unwrap().get("sky"), Some(sky) if (matches!(&sky.brdf, BRDF::Diffuse(df) if matches!(&df.pigment, Pigment::Image(pg) if pg.filter==Filter::Bicubic && pg.wrap==Wrap::Mirror))) && (matches!(&sky.emitted_radiance, Pigment::Image(pg) if pg.filter==Filter::Bilinear && pg.wrap==Wrap::Clamp))
+            )
+        );
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "materials:\n",
+            "  - name: sky\n",
+            "    diffuse:\n",
+            "      image: ['/tmp/filter_reference.pfm', trilinear]\n",
+            "    uniform: BLACK\n",
+        )));
+
         assert!(input.match_keyword(Keywords::Materials).is_ok());
         assert!(matches!(
             input.parse_materials(&var),
-            Err(SceneErr::NotMatch { loc, .. }) if loc.line_num==3 && loc.col_num==5
+            Err(SceneErr::UndefinedIdentifier { loc, .. }) if loc.line_num==4 && loc.col_num==44
         ));
 
         let mut input = InputStream::new(Cursor::new(concat!(
             "materials:\n",
-            "  - name: sky\n",
-            "    specular:\n",
-            "      uniform: [1.2, 1.3, 1.4]\n",
-            "    not_uniform: [2.1, 9.0, 7.3]\n",
+            "  - name: stone\n",
+            "    diffuse:\n",
+            "      noise: [BLACK, WHITE, marble, 2.5, 4, 7, simplex, uv]\n",
+            "    noise: [BLACK, [0.5, 0.5, 0.5], fbm, 1, 3, 42]\n",
         )));
 
-        assert!(input.match_whitespaces_and_comments().is_ok());
         assert!(input.match_keyword(Keywords::Materials).is_ok());
-        assert!(matches!(
-            input.parse_materials(&var),
-            Err(SceneErr::NotMatch { loc, .. }) if loc.line_num==5 && loc.col_num==5
-        ));
+        let materials = input.parse_materials(&var);
+        assert!(
+            matches!(materials.as_ref().unwrap().get("stone"), Some(stone) if (matches!(&stone.brdf, BRDF::Diffuse(df) if matches!(&df.pigment, Pigment::Noise(pg) if pg.pattern==Pattern::Marble && pg.scale==2.5 && pg.octaves==4 && pg.space==Space::Uv && pg.color2==WHITE))) && (matches!(&stone.emitted_radiance, Pigment::Noise(pg) if pg.pattern==Pattern::Fbm && pg.space==Space::Object))
+            )
+        );
 
         let mut input = InputStream::new(Cursor::new(concat!(
             "materials:\n",
-            "   - name: sky\n",
-            "     specular:\n",
-            "         uniform: [1.2, 0.9, 3.7]\n",
-            "     uniform: random # This is an inline comment\n",
-            "   - name: ground\n",
-            "     diffuse:\n",
-            "        checkered: [BLACK, WHITE, 7.]\n",
-            "     uniform: [2.1, 9.0, 7.3]\n",
+            "  - name: stone\n",
+            "    diffuse:\n",
+            "      noise: [BLACK, WHITE, granite, 2.5, 4, 7]\n",
+            "    uniform: BLACK\n",
         )));
 
-        assert!(input.match_whitespaces_and_comments().is_ok());
         assert!(input.match_keyword(Keywords::Materials).is_ok());
         assert!(matches!(
             input.parse_materials(&var),
-            Err(SceneErr::NotMatch { loc, .. }) if loc.line_num==4 && loc.col_num==9
+            Err(SceneErr::UndefinedIdentifier { loc, .. }) if loc.line_num==4 && loc.col_num==29
         ))
     }
 
@@ -1938,6 +3117,210 @@ mod test {
         ))
     }
 
+    #[test]
+    fn test_groups_parser() {
+        let mut var: Var = Var::default();
+        var.materials
+            .insert(String::from("wood"), Material::default());
+        let left = translation(Vector::from((0., -2., 0.)));
+        var.transformations.insert(String::from("left"), left);
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "groups:\n",
+            "  - name: chair\n",
+            "    shapes:\n",
+            "      - shape: box\n",
+            "        material: wood\n",
+            "        transformation: IDENTITY\n",
+            "      - shape: torus\n",
+            "        radius: 0.1\n",
+            "        material: wood\n",
+            "        transformation: left # This is an inline comment\n",
+            "  - name: room\n",
+            "    shapes:\n",
+            "      - group: chair\n",
+            "        transformation: left\n",
+            "shapes:\n",
+            "  - group: room\n",
+            "    transformation: IDENTITY\n",
+        )));
+        let chair: Arc<dyn RayIntersection> = Arc::new(Group::new(vec![
+            Arc::new(Cube::new(Transformation::default(), Material::default())),
+            Arc::new(Torus::new(left, Material::default(), 0.1)),
+        ]));
+        let room: Arc<dyn RayIntersection> = Arc::new(Group::new(vec![Arc::new(Instance::new(
+            left,
+            chair.clone(),
+        ))]));
+
+        assert!(input.match_keyword(Keywords::Groups).is_ok());
+        let groups = input.parse_groups(&var);
+        assert!(groups.is_ok());
+        var.groups = groups.unwrap();
+        assert_eq!(var.groups.len(), 2);
+        assert_eq!(format!("{:?}", var.groups["chair"]), format!("{chair:?}"));
+        assert_eq!(format!("{:?}", var.groups["room"]), format!("{room:?}"));
+        // Instances share the same group.
+        assert_eq!(Arc::strong_count(&var.groups["chair"]), 2);
+
+        let mut world = World::default();
+        world.add(Box::new(Instance::new(Transformation::default(), room)));
+        world.build_bvh();
+        assert!(input.match_keyword(Keywords::Shapes).is_ok());
+        let shapes = input.parse_shapes(&var);
+        assert!(shapes.is_ok());
+        assert_eq!(format!("{:?}", shapes.unwrap()), format!("{world:?}"));
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "groups:\n",
+            "  - name: room\n",
+            "    shapes:\n",
+            "      - group: chair\n",
+            "        transformation: left\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Groups).is_ok());
+        assert!(matches!(
+            input.parse_groups(&Var::default()),
+            Err(SceneErr::UndefinedIdentifier { loc, .. }) if loc.line_num==4 && loc.col_num==16
+        ));
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "groups:\n",
+            "  - name: chair\n",
+            "    shapes:\n",
+            "      - shape: box\n",
+            "        material: wood\n",
+            "        transformation: IDENTITY\n",
+            "     - shape: box\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Groups).is_ok());
+        assert!(matches!(
+            input.parse_groups(&var),
+            Err(SceneErr::NotMatch { loc, .. }) if loc.line_num==7 && loc.col_num==6
+        ));
+    }
+
+    #[test]
+    fn test_sdfs_parser() {
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "sdfs:\n",
+            "  - name: ball\n",
+            "    sdf: [sphere, 1]\n",
+            "  - name: rounded\n",
+            "    sdf: [box, [1, 0.5, 0.5], 0.1]\n",
+            "  - name: blob\n",
+            "    sdf: [union, ball, rounded, 0.3] # This is an inline comment\n",
+            "  - name: grid\n",
+            "    sdf: [repetition, blob, [4, 4, 0]]\n",
+            "  - name: twisted\n",
+            "    sdf: [twist, rounded, 1.5]\n",
+            "  - name: moved\n",
+            "    sdf: [transform, twisted, IDENTITY]\n",
+            "  - name: bulb\n",
+            "    sdf: [mandelbulb, 8, 10]\n",
+            "  - name: ring\n",
+            "    sdf: [torus, 1, 0.25]\n",
+        )));
+        let var: Var = Var::default();
+        let ball = Sdf::Sphere(SphereSdf { radius: 1.0 });
+        let rounded = Sdf::Box(BoxSdf {
+            half_size: Vector::from((1., 0.5, 0.5)),
+            rounding: 0.1,
+        });
+        let blob = Sdf::Combine(CombineSdf::new(
+            CsgOperation::Union,
+            ball.clone(),
+            rounded.clone(),
+            0.3,
+        ));
+        let twisted = Sdf::Twist(TwistSdf::new(rounded.clone(), 1.5));
+
+        assert!(input.match_keyword(Keywords::Sdfs).is_ok());
+        let sdfs = input.parse_sdfs(&var).unwrap();
+        assert_eq!(sdfs.len(), 8);
+        for (name, sdf) in [
+            ("ball", ball),
+            ("rounded", rounded),
+            ("blob", blob.clone()),
+            (
+                "grid",
+                Sdf::Repetition(RepetitionSdf::new(blob, Vector::from((4., 4., 0.)))),
+            ),
+            ("twisted", twisted.clone()),
+            (
+                "moved",
+                Sdf::Transform(TransformSdf::new(twisted, Transformation::default())),
+            ),
+            (
+                "bulb",
+                Sdf::Mandelbulb(MandelbulbSdf {
+                    power: 8.0,
+                    iterations: 10,
+                }),
+            ),
+            (
+                "ring",
+                Sdf::Torus(TorusSdf {
+                    major_radius: 1.0,
+                    minor_radius: 0.25,
+                }),
+            ),
+        ] {
+            assert_eq!(format!("{:?}", sdfs[name]), format!("{sdf:?}"));
+        }
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "sdfs:\n",
+            "  - name: ball\n",
+            "    sdf: [sphere, 1]\n",
+            "  - name: blob\n",
+            "    sdf: [union, ball, cube, 0.3]\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Sdfs).is_ok());
+        assert!(matches!(
+            input.parse_sdfs(&var),
+            Err(SceneErr::UndefinedIdentifier { loc, .. }) if loc.line_num==5 && loc.col_num==24
+        ));
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "sdfs:\n",
+            "  - name: ball\n",
+            "    sdf: [cube, 1]\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Sdfs).is_ok());
+        assert!(matches!(
+            input.parse_sdfs(&var),
+            Err(SceneErr::UndefinedIdentifier { loc, .. }) if loc.line_num==3 && loc.col_num==11
+        ));
+
+        let mut var: Var = Var::default();
+        var.sdfs
+            .insert(String::from("ball"), Sdf::Sphere(SphereSdf { radius: 1.0 }));
+        var.materials
+            .insert(String::from("ball"), Material::default());
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "shapes:\n",
+            "  - sdf: ball\n",
+            "    material: ball\n",
+            "    transformation: IDENTITY\n",
+        )));
+        let mut world = World::default();
+        world.add(Box::new(SdfShape::new(
+            Transformation::default(),
+            Material::default(),
+            var.sdfs["ball"].clone(),
+        )));
+        world.build_bvh();
+
+        assert!(input.match_keyword(Keywords::Shapes).is_ok());
+        let shapes = input.parse_shapes(&var);
+        assert!(shapes.is_ok());
+        assert_eq!(format!("{:?}", shapes.unwrap()), format!("{world:?}"));
+    }
+
     #[test]
     fn test_shapes_parser() {
         let mut input = InputStream::new(Cursor::new(concat!(
@@ -1974,6 +3357,7 @@ mod test {
             sphere.clone(),
         )));
         world.add(Box::new(Plane::new(rot_x, sky.clone())));
+        world.build_bvh();
         var.transformations.insert(String::from("rotationx"), rot_x);
         var.materials.insert(String::from("sphere"), sphere);
         var.materials.insert(String::from("sky"), sky);
@@ -2019,6 +3403,304 @@ mod test {
             input.parse_shapes(&var),
             Err(SceneErr::NotMatch { loc, .. }) if loc.line_num==6 && loc.col_num==3
         ));
+
+        assert!(
+            BufWriter::new(File::create(Path::new("/tmp/obj_reference")).unwrap())
+                .write_all(b"v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nf 1//1 2//1 3//1\n")
+                .is_ok()
+        );
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "shapes:\n",
+            "  - shape: triangle\n",
+            "    material: sphere\n",
+            "    transformation: rotationx\n",
+            "  - mesh: \"/tmp/obj_reference\"\n",
+            "    material: sky\n",
+            "    transformation: IDENTITY\n",
+        )));
+        let mut world = World::default();
+        world.add(Box::new(Triangle::new(
+            rot_x,
+            var.materials["sphere"].clone(),
+        )));
+        world.add(Box::new(TriangleMesh::new(
+            Arc::new(Mesh::read_obj_file(Path::new("/tmp/obj_reference")).unwrap()),
+            Transformation::default(),
+            var.materials["sky"].clone(),
+        )));
+        world.build_bvh();
+
+        assert!(input.match_keyword(Keywords::Shapes).is_ok());
+        let shapes = input.parse_shapes(&var);
+        assert!(shapes.is_ok());
+        assert_eq!(format!("{:?}", shapes.unwrap()), format!("{world:?}"));
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "shapes:\n",
+            "  - mesh: \"not_found.obj\"\n",
+            "    material: sky\n",
+            "    transformation: IDENTITY\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Shapes).is_ok());
+        assert!(matches!(
+            input.parse_shapes(&var),
+            Err(SceneErr::ObjFileReadFailure { loc, .. }) if loc.line_num==2 && loc.col_num==11
+        ));
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "shapes:\n",
+            "  - name: ball\n",
+            "    shape: sphere\n",
+            "    material: sphere\n",
+            "    transformation: IDENTITY\n",
+            "  - name: ground\n",
+            "    shape: plane\n",
+            "    material: sky\n",
+            "    transformation: IDENTITY\n",
+            "  - csg: difference\n",
+            "    shapes: [ball, ground]\n",
+        )));
+        let mut world = World::default();
+        world.add(Box::new(Csg::new(
+            CsgOperation::Difference,
+            Arc::new(Sphere::new(
+                Transformation::default(),
+                var.materials["sphere"].clone(),
+            )),
+            Arc::new(Plane::new(
+                Transformation::default(),
+                var.materials["sky"].clone(),
+            )),
+        )));
+        world.build_bvh();
+
+        assert!(input.match_keyword(Keywords::Shapes).is_ok());
+        let shapes = input.parse_shapes(&var);
+        assert!(shapes.is_ok());
+        assert_eq!(format!("{:?}", shapes.unwrap()), format!("{world:?}"));
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "shapes:\n",
+            "  - name: ball\n",
+            "    shape: sphere\n",
+            "    material: sphere\n",
+            "    transformation: IDENTITY\n",
+            "  - csg: union\n",
+            "    shapes: [ball, cube]\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Shapes).is_ok());
+        assert!(matches!(
+            input.parse_shapes(&var),
+            Err(SceneErr::UndefinedIdentifier { loc, .. }) if loc.line_num==7 && loc.col_num==20
+        ));
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "shapes:\n",
+            "  - name: ball\n",
+            "    shape: sphere\n",
+            "    material: sphere\n",
+            "    transformation: IDENTITY\n",
+            "  - csg: union\n",
+            "    shapes: [cube, ball]\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Shapes).is_ok());
+        assert!(matches!(
+            input.parse_shapes(&var),
+            Err(SceneErr::UndefinedIdentifier { loc, .. }) if loc.line_num==7 && loc.col_num==14
+        ));
+
+        // Shapes named as keywords can be combined too.
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "shapes:\n",
+            "  - name: sphere\n",
+            "    shape: sphere\n",
+            "    material: sphere\n",
+            "    transformation: IDENTITY\n",
+            "  - name: plane\n",
+            "    shape: plane\n",
+            "    material: sky\n",
+            "    transformation: IDENTITY\n",
+            "  - csg: intersection\n",
+            "    shapes: [sphere, plane]\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Shapes).is_ok());
+        assert!(input.parse_shapes(&var).is_ok());
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "shapes:\n",
+            "  - shape: box\n",
+            "    material: sphere\n",
+            "    transformation: IDENTITY\n",
+            "  - shape: cylinder\n",
+            "    material: sphere\n",
+            "    transformation: IDENTITY\n",
+            "  - shape: cone\n",
+            "    material: sphere\n",
+            "    transformation: IDENTITY\n",
+            "  - shape: disk\n",
+            "    material: sky\n",
+            "    transformation: rotationx\n",
+            "  - shape: torus\n",
+            "    radius: 0.3\n",
+            "    material: sphere\n",
+            "    transformation: rotationx\n",
+        )));
+        let (sphere, sky) = (&var.materials["sphere"], &var.materials["sky"]);
+        let mut world = World::default();
+        world.add(Box::new(Cube::new(
+            Transformation::default(),
+            sphere.clone(),
+        )));
+        world.add(Box::new(Cylinder::new(
+            Transformation::default(),
+            sphere.clone(),
+        )));
+        world.add(Box::new(Cone::new(
+            Transformation::default(),
+            sphere.clone(),
+        )));
+        world.add(Box::new(Disk::new(rot_x, sky.clone())));
+        world.add(Box::new(Torus::new(rot_x, sphere.clone(), 0.3)));
+        world.build_bvh();
+
+        assert!(input.match_keyword(Keywords::Shapes).is_ok());
+        let shapes = input.parse_shapes(&var);
+        assert!(shapes.is_ok());
+        assert_eq!(format!("{:?}", shapes.unwrap()), format!("{world:?}"));
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "shapes:\n",
+            "  - shape: torus\n",
+            "    material: sphere\n",
+            "    transformation: IDENTITY\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Shapes).is_ok());
+        assert!(matches!(
+            input.parse_shapes(&var),
+            Err(SceneErr::NotMatch { loc, .. }) if loc.line_num==3 && loc.col_num==5
+        ));
+
+        for radius in ["0", "1", "-0.2"] {
+            let scene = format!(
+                "shapes:\n  - shape: torus\n    radius: {radius}\n    material: sphere\n    transformation: IDENTITY\n"
+            );
+            let mut input = InputStream::new(Cursor::new(scene));
+
+            assert!(input.match_keyword(Keywords::Shapes).is_ok());
+            assert!(matches!(
+                input.parse_shapes(&var),
+                Err(SceneErr::InvalidValue { loc, .. }) if loc.line_num==3 && loc.col_num==13
+            ));
+        }
+    }
+
+    #[test]
+    fn test_lights_parser() {
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "lights:\n",
+            "  - position: [1, 2, 3]\n",
+            "    color: [0.5, 0.5, 0.5]\n",
+            "    radius: 0.1 # This is an inline comment\n",
+            "  - position: E3\n",
+            "    color: WHITE\n",
+            "    radius: 0\n",
+        )));
+        let var: Var = Var::default();
+
+        assert!(input.match_keyword(Keywords::Lights).is_ok());
+        assert!(
+            matches!(input.parse_lights(&var), Ok(lights) if lights == vec![
+                PointLight::new(Point::from((1., 2., 3.)), Color::from((0.5, 0.5, 0.5)), 0.1),
+                PointLight::new(Point::from((0., 0., 1.)), WHITE, 0.),
+            ])
+        );
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "lights:\n",
+            "  - position: [1, 2, 3]\n",
+            "    color: [0.5, 0.5, 0.5]\n",
+            "  - position: E3\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Lights).is_ok());
+        assert!(matches!(
+            input.parse_lights(&var),
+            Err(SceneErr::NotMatch { loc, .. }) if loc.line_num==4 && loc.col_num==3
+        ));
+    }
+
+    #[test]
+    fn test_environment_parser() {
+        let mut sky = HdrImage::new(2, 1);
+        sky.set_pixel(1, 0, WHITE).unwrap();
+        sky.write_pfm_file(
+            Path::new("/tmp/environment_reference.pfm"),
+            ByteOrder::LittleEndian,
+        )
+        .unwrap();
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "environment:\n",
+            "  image: '/tmp/environment_reference.pfm'\n",
+            "  intensity: 2.5 # This is an inline comment\n",
+            "  transformation: IDENTITY\n",
+        )));
+        let var: Var = Var::default();
+
+        assert!(input.match_keyword(Keywords::Environment).is_ok());
+        assert!(matches!(
+            input.parse_environment(&var),
+            Ok(environment) if environment == Environment::Map(EnvironmentLight::new(sky, 2.5, Transformation::default()))
+        ));
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "environment:\n",
+            "    sun: [1, 0, 1]\n",
+            "    turbidity: 3\n",
+            "    diameter: 0.53\n",
+            "    intensity: 0.01\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Environment).is_ok());
+        assert!(matches!(
+            input.parse_environment(&var),
+            Ok(environment) if environment == Environment::Sky(SkyLight::new(Vector::from((1., 0., 1.)), 3., f32::to_radians(0.53), 0.01))
+        ));
+
+        let mut input = InputStream::new(Cursor::new(concat!(
+            "environment:\n",
+            "  image: '/tmp/environment_reference.pfm'\n",
+            "  intensity: 2.5\n",
+            "  transformation: rotate_sky\n",
+        )));
+
+        assert!(input.match_keyword(Keywords::Environment).is_ok());
+        assert!(matches!(
+            input.parse_environment(&var),
+            Err(SceneErr::UndefinedIdentifier { loc, .. }) if loc.line_num==4 && loc.col_num==19
+        ));
+
+        for (sun, turbidity, diameter, line_num, col_num) in [
+            ("[0, 0, 0]", "3", "0.53", 2, 10),
+            ("E3", "1", "0.53", 3, 16),
+            ("E3", "3", "0", 4, 15),
+            ("E3", "3", "180", 4, 15),
+        ] {
+            let scene = format!(
+                "environment:\n    sun: {sun}\n    turbidity: {turbidity}\n    diameter: {diameter}\n    intensity: 0.01\n"
+            );
+            let mut input = InputStream::new(Cursor::new(scene));
+
+            assert!(input.match_keyword(Keywords::Environment).is_ok());
+            assert!(matches!(
+                input.parse_environment(&var),
+                Err(SceneErr::InvalidValue { loc, .. }) if loc.line_num==line_num && loc.col_num==col_num
+            ));
+        }
     }
 
     #[test]
@@ -2077,6 +3759,12 @@ mod test {
             "  - shape: sphere\n",
             "    material: from_image\n",
             "    transformation: rot_y\n",
+            "\n",
+            "lights:\n",
+            "  - position: [-30, 30, 30]\n",
+            "    color: WHITE\n",
+            "    radius: 0.\n",
+            "# This is a trailing comment\n",
         )));
         // Build a cli
         let cli = Cli {
@@ -2144,6 +3832,8 @@ mod test {
             rotation_y(f32::to_radians(180.)),
             from_image,
         )));
+        world.build_bvh();
+        world.add_light(PointLight::new(Point::from((-30., 30., 30.)), WHITE, 0.));
         scene_ref.camera = Some(camera);
         scene_ref.shapes = Some(world);
 
@@ -0,0 +1,491 @@
+//! Signed Distance Field module.
+//!
+//! Provides [`Sdf`](enum@Sdf) enum, a composable tree of signed distance
+//! primitives and operators,\
+//! rendered by sphere tracing inside [`SdfShape`](struct@crate::shape::SdfShape).
+use crate::{
+    bvh::BoundingBox, point::Point, shape::CsgOperation, transformation::Transformation,
+    vector::Vector,
+};
+
+/// Escape radius of the [`MandelbulbSdf`] iteration.
+const BAILOUT: f32 = 2.0;
+
+/// A trait for evaluating a signed distance field.
+pub trait SignedDistance {
+    /// Return the signed distance between `point` and the surface,
+    /// negative inside of it.
+    ///
+    /// The distance can be underestimated (slowing down sphere tracing),
+    /// but never overestimated.
+    fn distance(&self, point: Point) -> f32;
+    /// Return a [`BoundingBox`](struct@BoundingBox) that contains the surface,\
+    /// or [`None`] if it is unbounded (e.g. [`RepetitionSdf`]).
+    fn bounding_box(&self) -> Option<BoundingBox>;
+}
+
+/// A sphere centered at the origin of axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SphereSdf {
+    /// Radius of the sphere.
+    pub radius: f32,
+}
+
+impl SignedDistance for SphereSdf {
+    fn distance(&self, point: Point) -> f32 {
+        Vector::from(point).norm() - self.radius
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let r = self.radius;
+        Some(BoundingBox::new(
+            Point::from((-r, -r, -r)),
+            Point::from((r, r, r)),
+        ))
+    }
+}
+
+/// An axis-aligned box centered at the origin of axis, with rounded edges.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoxSdf {
+    /// Half the sides length of the box.
+    pub half_size: Vector,
+    /// Radius of the rounded edges, `0` for sharp edges.
+    pub rounding: f32,
+}
+
+impl SignedDistance for BoxSdf {
+    fn distance(&self, point: Point) -> f32 {
+        let q = Vector::from((
+            point.x.abs() - self.half_size.x + self.rounding,
+            point.y.abs() - self.half_size.y + self.rounding,
+            point.z.abs() - self.half_size.z + self.rounding,
+        ));
+        let outside = Vector::from((q.x.max(0.0), q.y.max(0.0), q.z.max(0.0))).norm();
+        let inside = q.x.max(q.y).max(q.z).min(0.0);
+        outside + inside - self.rounding
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let h = self.half_size;
+        Some(BoundingBox::new(
+            Point::from((-h.x, -h.y, -h.z)),
+            Point::from((h.x, h.y, h.z)),
+        ))
+    }
+}
+
+/// A torus around the z axis, centered at the origin of axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TorusSdf {
+    /// Distance between the center of the tube and the z axis.
+    pub major_radius: f32,
+    /// Radius of the tube.
+    pub minor_radius: f32,
+}
+
+impl SignedDistance for TorusSdf {
+    fn distance(&self, point: Point) -> f32 {
+        let rho = (point.x * point.x + point.y * point.y).sqrt() - self.major_radius;
+        (rho * rho + point.z * point.z).sqrt() - self.minor_radius
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let (outer, r) = (self.major_radius + self.minor_radius, self.minor_radius);
+        Some(BoundingBox::new(
+            Point::from((-outer, -outer, -r)),
+            Point::from((outer, outer, r)),
+        ))
+    }
+}
+
+/// The [Mandelbulb](https://en.wikipedia.org/wiki/Mandelbulb) fractal,
+/// a 3D analogue of the Mandelbrot set.
+///
+/// Its distance is estimated from the running derivative of the
+/// iteration `z -> z^power + point`, in spherical coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MandelbulbSdf {
+    /// Power of the iteration, `8` for the classic bulb.
+    pub power: f32,
+    /// Number of iterations, more iterations give more details.
+    pub iterations: u32,
+}
+
+impl SignedDistance for MandelbulbSdf {
+    fn distance(&self, point: Point) -> f32 {
+        let c = Vector::from(point);
+        let (mut z, mut dr, mut r) = (c, 1.0, c.norm());
+        for _ in 0..self.iterations {
+            if r > BAILOUT || r == 0.0 {
+                break;
+            }
+            let theta = (z.z / r).clamp(-1.0, 1.0).acos() * self.power;
+            let phi = z.y.atan2(z.x) * self.power;
+            dr = r.powf(self.power - 1.0) * self.power * dr + 1.0;
+            z = Vector::from((
+                theta.sin() * phi.cos(),
+                theta.sin() * phi.sin(),
+                theta.cos(),
+            )) * r.powf(self.power)
+                + c;
+            r = z.norm();
+        }
+        if r == 0.0 {
+            // The origin is always inside the bulb.
+            return -f32::EPSILON;
+        }
+        0.5 * r.ln() * r / dr
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        Some(BoundingBox::new(
+            Point::from((-BAILOUT, -BAILOUT, -BAILOUT)),
+            Point::from((BAILOUT, BAILOUT, BAILOUT)),
+        ))
+    }
+}
+
+/// A boolean [`CsgOperation`](enum@CsgOperation) between two fields,
+/// smoothly blended.
+///
+/// The smooth minimum is the polynomial one from
+/// [Quilez](https://iquilezles.org/articles/smin/),\
+/// the two surfaces are blended where their distances differ less than `smoothness`.
+#[derive(Clone, Debug)]
+pub struct CombineSdf {
+    /// Boolean operation between the fields.
+    pub operation: CsgOperation,
+    /// First field.
+    pub left: Box<Sdf>,
+    /// Second field.
+    pub right: Box<Sdf>,
+    /// Size of the blending region, `0` for a sharp combination.
+    pub smoothness: f32,
+}
+
+impl CombineSdf {
+    /// Provides a constructor for [`CombineSdf`](struct@CombineSdf).
+    pub fn new(operation: CsgOperation, left: Sdf, right: Sdf, smoothness: f32) -> Self {
+        Self {
+            operation,
+            left: Box::new(left),
+            right: Box::new(right),
+            smoothness,
+        }
+    }
+
+    /// Return the smooth minimum between `a` and `b`.
+    fn smooth_min(&self, a: f32, b: f32) -> f32 {
+        let k = self.smoothness;
+        if k <= 0.0 {
+            return a.min(b);
+        }
+        let h = (k - (a - b).abs()).max(0.0) / k;
+        a.min(b) - h * h * k / 4.0
+    }
+}
+
+impl SignedDistance for CombineSdf {
+    fn distance(&self, point: Point) -> f32 {
+        let (a, b) = (self.left.distance(point), self.right.distance(point));
+        match self.operation {
+            CsgOperation::Union => self.smooth_min(a, b),
+            CsgOperation::Intersection => -self.smooth_min(-a, -b),
+            CsgOperation::Difference => -self.smooth_min(-a, b),
+        }
+    }
+
+    /// Blending can grow a union up to `smoothness/4` outside its fields.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let (left, right) = (self.left.bounding_box(), self.right.bounding_box());
+        match self.operation {
+            CsgOperation::Union => left.zip(right).map(|(left, right)| {
+                let pad = Vector::from((1., 1., 1.)) * (self.smoothness.max(0.0) / 4.0);
+                let bbox = left.union(right);
+                BoundingBox::new(bbox.min - pad, bbox.max + pad)
+            }),
+            CsgOperation::Intersection => left.or(right),
+            CsgOperation::Difference => left,
+        }
+    }
+}
+
+/// Infinite repetition of a field on a grid.
+///
+/// The repeated field should fit inside a single grid cell,
+/// otherwise its distance is not exact.
+#[derive(Clone, Debug)]
+pub struct RepetitionSdf {
+    /// Repeated field.
+    pub sdf: Box<Sdf>,
+    /// Period of the grid along each axis, `0` for no repetition along that axis.
+    pub period: Vector,
+}
+
+impl RepetitionSdf {
+    /// Provides a constructor for [`RepetitionSdf`](struct@RepetitionSdf).
+    pub fn new(sdf: Sdf, period: Vector) -> Self {
+        Self {
+            sdf: Box::new(sdf),
+            period,
+        }
+    }
+}
+
+impl SignedDistance for RepetitionSdf {
+    fn distance(&self, point: Point) -> f32 {
+        let repeat = |x: f32, period: f32| {
+            if period > 0.0 {
+                x - period * (x / period).round()
+            } else {
+                x
+            }
+        };
+        self.sdf.distance(Point::from((
+            repeat(point.x, self.period.x),
+            repeat(point.y, self.period.y),
+            repeat(point.z, self.period.z),
+        )))
+    }
+
+    /// A repetition is unbounded, unless it repeats along no axis.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        if self.period.x > 0.0 || self.period.y > 0.0 || self.period.z > 0.0 {
+            None
+        } else {
+            self.sdf.bounding_box()
+        }
+    }
+}
+
+/// A field twisted around the z axis.
+///
+/// Twisting is not an isometry, so the distance is divided by the
+/// Lipschitz constant of the twist at the point distance from the axis.
+#[derive(Clone, Debug)]
+pub struct TwistSdf {
+    /// Twisted field.
+    pub sdf: Box<Sdf>,
+    /// Rotation angle (in radians) per unit length along the z axis.
+    pub rate: f32,
+}
+
+impl TwistSdf {
+    /// Provides a constructor for [`TwistSdf`](struct@TwistSdf).
+    pub fn new(sdf: Sdf, rate: f32) -> Self {
+        Self {
+            sdf: Box::new(sdf),
+            rate,
+        }
+    }
+}
+
+impl SignedDistance for TwistSdf {
+    fn distance(&self, point: Point) -> f32 {
+        let (sin, cos) = (-self.rate * point.z).sin_cos();
+        let twisted = Point::from((
+            cos * point.x - sin * point.y,
+            sin * point.x + cos * point.y,
+            point.z,
+        ));
+        let rho = (point.x * point.x + point.y * point.y).sqrt();
+        self.sdf.distance(twisted) / (1.0 + (self.rate * rho).powi(2)).sqrt()
+    }
+
+    /// The field box, rotated by any angle around the z axis.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        self.sdf.bounding_box().map(|bbox| {
+            let rho = |x: f32, y: f32| (x * x + y * y).sqrt();
+            let r = rho(bbox.min.x.abs(), bbox.min.y.abs())
+                .max(rho(bbox.max.x.abs(), bbox.max.y.abs()))
+                .max(rho(bbox.min.x.abs(), bbox.max.y.abs()))
+                .max(rho(bbox.max.x.abs(), bbox.min.y.abs()));
+            BoundingBox::new(
+                Point::from((-r, -r, bbox.min.z)),
+                Point::from((r, r, bbox.max.z)),
+            )
+        })
+    }
+}
+
+/// A field moved by a [`Transformation`](struct@Transformation).
+///
+/// The distance is exact only for rigid motions and uniform scalings,\
+/// otherwise it is scaled by the smallest scaling factor along the axes.
+#[derive(Clone, Debug)]
+pub struct TransformSdf {
+    /// Transformed field.
+    pub sdf: Box<Sdf>,
+    /// Transformation applied to the field.
+    pub transformation: Transformation,
+}
+
+impl TransformSdf {
+    /// Provides a constructor for [`TransformSdf`](struct@TransformSdf).
+    pub fn new(sdf: Sdf, transformation: Transformation) -> Self {
+        Self {
+            sdf: Box::new(sdf),
+            transformation,
+        }
+    }
+}
+
+impl SignedDistance for TransformSdf {
+    fn distance(&self, point: Point) -> f32 {
+        let scale = [
+            Vector::from((1., 0., 0.)),
+            Vector::from((0., 1., 0.)),
+            Vector::from((0., 0., 1.)),
+        ]
+        .map(|axis| (self.transformation * axis).norm())
+        .into_iter()
+        .fold(f32::INFINITY, f32::min);
+        self.sdf.distance(self.transformation.inverse() * point) * scale
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        self.sdf
+            .bounding_box()
+            .map(|bbox| bbox.transform(self.transformation))
+    }
+}
+
+/// Enum of signed distance fields.
+#[derive(Clone, Debug)]
+pub enum Sdf {
+    Sphere(SphereSdf),
+    Box(BoxSdf),
+    Torus(TorusSdf),
+    Mandelbulb(MandelbulbSdf),
+    Combine(CombineSdf),
+    Repetition(RepetitionSdf),
+    Twist(TwistSdf),
+    Transform(TransformSdf),
+}
+
+impl SignedDistance for Sdf {
+    /// Return a different distance as function of [`Sdf`] variant.
+    fn distance(&self, point: Point) -> f32 {
+        match self {
+            Sdf::Sphere(sphere) => sphere.distance(point),
+            Sdf::Box(cube) => cube.distance(point),
+            Sdf::Torus(torus) => torus.distance(point),
+            Sdf::Mandelbulb(bulb) => bulb.distance(point),
+            Sdf::Combine(combine) => combine.distance(point),
+            Sdf::Repetition(repetition) => repetition.distance(point),
+            Sdf::Twist(twist) => twist.distance(point),
+            Sdf::Transform(transform) => transform.distance(point),
+        }
+    }
+
+    /// Return a different bounding box as function of [`Sdf`] variant.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        match self {
+            Sdf::Sphere(sphere) => sphere.bounding_box(),
+            Sdf::Box(cube) => cube.bounding_box(),
+            Sdf::Torus(torus) => torus.bounding_box(),
+            Sdf::Mandelbulb(bulb) => bulb.bounding_box(),
+            Sdf::Combine(combine) => combine.bounding_box(),
+            Sdf::Repetition(repetition) => repetition.bounding_box(),
+            Sdf::Twist(twist) => twist.bounding_box(),
+            Sdf::Transform(transform) => transform.bounding_box(),
+        }
+    }
+}
+
+impl Sdf {
+    /// Return the gradient of the field at `point`, the outward normal of its surface,
+    /// estimated by central differences along the vertices of a tetrahedron.
+    pub fn gradient(&self, point: Point, step: f32) -> Vector {
+        [(1., -1., -1.), (-1., -1., 1.), (-1., 1., -1.), (1., 1., 1.)]
+            .map(Vector::from)
+            .into_iter()
+            .fold(Vector::from((0., 0., 0.)), |gradient, k| {
+                gradient + k * self.distance(point + k * step)
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{misc::IsClose, transformation::translation};
+
+    #[test]
+    fn test_signed_distance() {
+        let sphere = Sdf::Sphere(SphereSdf { radius: 1.0 });
+        assert!(sphere.distance(Point::from((3., 0., 0.))).is_close(2.));
+        assert!(sphere.distance(Point::default()).is_close(-1.));
+        assert!(sphere
+            .gradient(Point::from((0., 2., 0.)), 1e-3)
+            .normalize()
+            .is_close(Vector::from((0., 1., 0.))));
+
+        let cube = Sdf::Box(BoxSdf {
+            half_size: Vector::from((1., 2., 3.)),
+            rounding: 0.5,
+        });
+        assert!(cube.distance(Point::from((3., 0., 0.))).is_close(2.));
+        assert!(cube.distance(Point::default()).is_close(-1.));
+        // Rounded corners are farther than sharp ones.
+        let corner = Point::from((2., 3., 4.));
+        assert!(cube.distance(corner).is_close(3.0_f32.sqrt() * 1.5 - 0.5));
+
+        let torus = Sdf::Torus(TorusSdf {
+            major_radius: 1.0,
+            minor_radius: 0.25,
+        });
+        assert!(torus.distance(Point::from((1., 0., 0.5))).is_close(0.25));
+        assert!(torus.distance(Point::default()).is_close(0.75));
+
+        let bulb = Sdf::Mandelbulb(MandelbulbSdf {
+            power: 8.0,
+            iterations: 10,
+        });
+        assert!(bulb.distance(Point::default()) < 0.0);
+        assert!(bulb.distance(Point::from((3., 0., 0.))) > 0.5);
+
+        let ball = |x: f32| {
+            Sdf::Transform(TransformSdf::new(
+                sphere.clone(),
+                translation(Vector::from((x, 0., 0.))),
+            ))
+        };
+        let combine = |operation, smoothness| {
+            Sdf::Combine(CombineSdf::new(
+                operation,
+                ball(-0.5),
+                ball(0.5),
+                smoothness,
+            ))
+        };
+        let point = Point::from((0., 0., 2.));
+        let (a, b) = (ball(-0.5).distance(point), ball(0.5).distance(point));
+        assert!(combine(CsgOperation::Union, 0.0)
+            .distance(point)
+            .is_close(a.min(b)));
+        // Smooth union is closer than the sharp one, where the fields blend.
+        assert!(combine(CsgOperation::Union, 0.5).distance(point) < a.min(b));
+        assert!(combine(CsgOperation::Intersection, 0.0)
+            .distance(Point::from((-1.25, 0., 0.)))
+            .is_close(0.75));
+        assert!(combine(CsgOperation::Difference, 0.0)
+            .distance(Point::from((-1.25, 0., 0.)))
+            .is_close(-0.25));
+        assert!(combine(CsgOperation::Union, 0.5).bounding_box().is_some());
+
+        let grid = Sdf::Repetition(RepetitionSdf::new(
+            sphere.clone(),
+            Vector::from((4., 0., 0.)),
+        ));
+        assert!(grid
+            .distance(Point::from((8., 0., 2.)))
+            .is_close(sphere.distance(Point::from((0., 0., 2.)))));
+        assert!(grid.bounding_box().is_none());
+
+        let twist = Sdf::Twist(TwistSdf::new(cube.clone(), 1.0));
+        assert!(twist.distance(Point::default()).is_close(-1.));
+        assert!(twist.distance(Point::from((0., 0., 3.5))).is_close(0.5));
+    }
+}
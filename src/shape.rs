@@ -4,15 +4,19 @@
 //! [`RayIntersection`](trait@RayIntersection) trait.
 
 use crate::{
+    bvh::{BoundingBox, Bvh},
+    light::{AreaLight, SphereLight},
     material::Material,
-    misc::{IsClose, Vector2D},
+    mesh::Mesh,
+    misc::{Footprint, IsClose, Vector2D},
     normal::Normal,
     point::Point,
     ray::Ray,
+    sdf::{Sdf, SignedDistance},
     transformation::Transformation,
     vector::Vector,
 };
-use std::f32::consts::PI;
+use std::{f32::consts::PI, sync::Arc};
 
 /// Trait to determine the intersections of an object with a [`Ray`](struct@Ray).
 ///
@@ -25,6 +29,22 @@ use std::f32::consts::PI;
 ///   * [`Sync`].
 pub trait RayIntersection: std::fmt::Debug + Send + Sync {
     fn ray_intersection(&self, ray: Ray) -> Option<HitRecord>;
+    /// Return all the intersections with `ray` (between `ray.tmin` and `ray.tmax`),
+    /// sorted by increasing `t`.
+    ///
+    /// Each [`HitRecord`](struct@HitRecord) is either an entry in the shape,
+    /// or an exit from it when `inside` is `true`,\
+    /// so that consecutive intersections bound the intervals of `ray` inside the shape
+    /// (as needed by [`Csg`](struct@Csg)).
+    fn all_intersections(&self, ray: Ray) -> Vec<HitRecord>;
+    /// Return the [`BoundingBox`](struct@BoundingBox) of the shape in world coordinates,\
+    /// or [`None`] if the shape is unbounded (e.g. [`Plane`](struct@Plane)).
+    fn bounding_box(&self) -> Option<BoundingBox>;
+    /// Return the [`AreaLight`](enum@AreaLight) geometry of the shape if it is emissive
+    /// and it can be explicitly sampled, otherwise [`None`].
+    fn area_light(&self) -> Option<AreaLight> {
+        None
+    }
 }
 
 /// Struct used to store the results of [`RayIntersection`](trait@RayIntersection).
@@ -32,10 +52,20 @@ pub trait RayIntersection: std::fmt::Debug + Send + Sync {
 pub struct HitRecord {
     /// Coordinates of the point of impact.
     pub world_point: Point,
-    /// Normal of the shape surface on the impact point.
+    /// Coordinates of the point of impact in the frame of reference of the shape.
+    pub local_point: Point,
+    /// Normal of the shape surface on the impact point,
+    /// always on the same side of the impacting ray.
     pub normal: Normal,
+    /// `true` if the ray hit the surface from the inside of the shape
+    /// (or from the back for open surfaces like [`Plane`](struct@Plane)),\
+    /// where the outward normal would have been flipped.
+    pub inside: bool,
     /// Coordinates of the point of impact in the frame of reference of the shape's surface.
     pub surface_point: Vector2D,
+    /// [`Footprint`](struct@Footprint) of the impacting ray differentials
+    /// around `surface_point`.
+    pub footprint: Footprint,
     /// Time the ray travelled before the impact.
     pub t: f32,
     /// The ray that impacted on the shape.
@@ -50,12 +80,63 @@ impl IsClose for HitRecord {
     fn is_close(&self, other: Self) -> bool {
         self.world_point.is_close(other.world_point)
             && self.normal.is_close(other.normal)
+            && self.inside == other.inside
             && self.surface_point.is_close(other.surface_point)
             && self.t.is_close(other.t)
             && self.ray.is_close(other.ray)
     }
 }
 
+/// Returns the [`Footprint`](struct@Footprint) of `ray` differentials on a shape's surface,
+/// everything in the shape frame of reference.
+///
+/// The offset rays are intersected with the plane tangent to the surface in `point`,
+/// of (not normalized) `normal`,\
+/// then their offsets are decomposed (in a least squares sense) along the partial
+/// derivatives `dp_du` and `dp_dv` of the surface parametrization.\
+/// Working in the shape frame of reference, the footprint is not affected by
+/// its [`Transformation`](struct@Transformation).
+///
+/// A zero footprint is returned for rays without differentials
+/// or for a degenerate parametrization.
+fn surface_footprint(
+    ray: Ray,
+    point: Point,
+    normal: Vector,
+    dp_du: Vector,
+    dp_dv: Vector,
+) -> Footprint {
+    let diff = match ray.differentials {
+        Some(diff) => diff,
+        None => return Footprint::default(),
+    };
+    let offset = |origin: Point, dir: Vector| {
+        let t = normal.dot(point - origin) / normal.dot(dir);
+        origin + dir * t - point
+    };
+    let (dp_dx, dp_dy) = (
+        offset(diff.rx_origin, diff.rx_dir),
+        offset(diff.ry_origin, diff.ry_dir),
+    );
+    let (a00, a01, a11) = (dp_du.dot(dp_du), dp_du.dot(dp_dv), dp_dv.dot(dp_dv));
+    let det = a00 * a11 - a01 * a01;
+    // Also catches offset rays parallel to the tangent plane (not finite offsets).
+    if det.is_nan() || det <= 1e-8 * a00 * a11 || !(dp_dx.norm() + dp_dy.norm()).is_finite() {
+        return Footprint::default();
+    }
+    let solve = |dp: Vector| {
+        let (b0, b1) = (dp_du.dot(dp), dp_dv.dot(dp));
+        Vector2D {
+            u: (a11 * b0 - a01 * b1) / det,
+            v: (a00 * b1 - a01 * b0) / det,
+        }
+    };
+    Footprint {
+        duv_dx: solve(dp_dx),
+        duv_dy: solve(dp_dy),
+    }
+}
+
 /// Geometrical shape corresponding to a sphere.
 #[derive(Debug, Default)]
 pub struct Sphere {
@@ -84,140 +165,1605 @@ impl Sphere {
 /// This Function is meant to be used inside [`Sphere`](struct@Sphere)'s
 /// [`RayIntersection`](trait@RayIntersection) implementation.\
 /// `ray_dir` is the direction of an impacting [`Ray`](struct@Ray) and\
-/// is used to determine on which side of the surface the normal is calculated.
-fn sphere_normal(point: Point, ray_dir: Vector) -> Normal {
+/// is used to determine on which side of the surface the normal is calculated,\
+/// returned together with `true` if the ray hits the sphere from the inside.
+fn sphere_normal(point: Point, ray_dir: Vector) -> (Normal, bool) {
     let result = Normal::from((point.x, point.y, point.z));
     if Vector::from(point).dot(ray_dir) < 0.0 {
-        result
+        (result, false)
+    } else {
+        (result.neg(), true)
+    }
+}
+
+/// Returns parametrization coordinates of a point on a sphere.
+///
+/// The sphere's surface is parametrized by two angles that correspond
+/// to latitude and longitude.
+fn sphere_point_to_uv(point: Point) -> Vector2D {
+    let mut u = point.y.atan2(point.x) / (2.0 * PI);
+    let v = point.z.acos() / PI;
+    if u < 0.0 {
+        u += 1.0
+    };
+    Vector2D { u, v }
+}
+
+/// Returns partial derivatives `(dp/du, dp/dv)` of a point on a sphere,
+/// with respect to its [parametrization](fn@sphere_point_to_uv).
+fn sphere_point_derivatives(point: Point) -> (Vector, Vector) {
+    let phi = point.y.atan2(point.x);
+    let rho = (point.x * point.x + point.y * point.y).sqrt();
+    (
+        Vector::from((-2.0 * PI * point.y, 2.0 * PI * point.x, 0.0)),
+        Vector::from((
+            PI * point.z * phi.cos(),
+            PI * point.z * phi.sin(),
+            -PI * rho,
+        )),
+    )
+}
+
+/// Returns the times `(t1, t2)`, with `t1 < t2`, of the intersections between
+/// `inv_ray` and the unit sphere, if any (whatever `inv_ray.tmin` and `inv_ray.tmax`).
+fn sphere_intersection_times(inv_ray: Ray) -> Option<(f32, f32)> {
+    let origin_vec = Vector::from(inv_ray.origin);
+    let a = inv_ray.dir.squared_norm();
+    let b = 2.0 * origin_vec.dot(inv_ray.dir);
+    let c = origin_vec.squared_norm() - 1.0;
+
+    let delta = b * b - 4.0 * a * c;
+    if delta <= 0.0 {
+        return None;
+    }
+    let sqrt_delta = delta.sqrt();
+    Some(((-b - sqrt_delta) / (2.0 * a), (-b + sqrt_delta) / (2.0 * a)))
+}
+
+impl Sphere {
+    /// Build the [`HitRecord`](struct@HitRecord) of `ray` impacting the sphere at time `t`,
+    /// where `inv_ray` is `ray` in the sphere frame of reference.
+    fn hit_record(&self, inv_ray: Ray, ray: Ray, t: f32) -> HitRecord {
+        let hit_point = inv_ray.at(t);
+        let (normal, inside) = sphere_normal(hit_point, inv_ray.dir);
+        let (dp_du, dp_dv) = sphere_point_derivatives(hit_point);
+        HitRecord {
+            world_point: self.transformation * hit_point,
+            local_point: hit_point,
+            normal: self.transformation * normal,
+            inside,
+            surface_point: sphere_point_to_uv(hit_point),
+            footprint: surface_footprint(inv_ray, hit_point, Vector::from(hit_point), dp_du, dp_dv),
+            t,
+            ray,
+            material: self.material.clone(),
+        }
+    }
+}
+
+impl RayIntersection for Sphere {
+    /// Finds intersections between a [`Ray`](struct@Ray) and a [`Sphere`](struct@Sphere).
+    fn ray_intersection(&self, ray: Ray) -> Option<HitRecord> {
+        let inv_ray = self.transformation.inverse() * ray;
+        let (t1, t2) = sphere_intersection_times(inv_ray)?;
+        let first_hit_t;
+        if (t1 > inv_ray.tmin) && (t1 < inv_ray.tmax) {
+            first_hit_t = t1;
+        } else if (t2 > inv_ray.tmin) && (t2 < inv_ray.tmax) {
+            first_hit_t = t2;
+        } else {
+            return None;
+        }
+        Some(self.hit_record(inv_ray, ray, first_hit_t))
+    }
+
+    /// Finds both the entry and the exit of a [`Ray`](struct@Ray)
+    /// through a [`Sphere`](struct@Sphere).
+    fn all_intersections(&self, ray: Ray) -> Vec<HitRecord> {
+        let inv_ray = self.transformation.inverse() * ray;
+        match sphere_intersection_times(inv_ray) {
+            Some((t1, t2)) => [t1, t2]
+                .into_iter()
+                .filter(|t| (*t > inv_ray.tmin) && (*t < inv_ray.tmax))
+                .map(|t| self.hit_record(inv_ray, ray, t))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Return the transformed box of the unit sphere,
+    /// i.e. the `[-1,1]x[-1,1]x[-1,1]` cube.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        Some(
+            BoundingBox::new(Point::from((-1., -1., -1.)), Point::from((1., 1., 1.)))
+                .transform(self.transformation),
+        )
+    }
+
+    /// Return a [`SphereLight`](struct@SphereLight) if the sphere is emissive
+    /// and its transformation preserves its shape (no ellipsoids).
+    fn area_light(&self) -> Option<AreaLight> {
+        if !self.material.is_emissive() {
+            return None;
+        }
+        let [ex, ey, ez] = [
+            Vector::from((1., 0., 0.)),
+            Vector::from((0., 1., 0.)),
+            Vector::from((0., 0., 1.)),
+        ]
+        .map(|axis| self.transformation * axis);
+        let radius = ex.norm();
+        // Transformed axes must be orthogonal and with the same length,
+        // up to a tolerance relative to the sphere size.
+        let tolerance = 1e-4 * radius * radius;
+        let is_uniform = (ey.squared_norm() - radius * radius).abs() < tolerance
+            && (ez.squared_norm() - radius * radius).abs() < tolerance
+            && ex.dot(ey).abs() < tolerance
+            && ey.dot(ez).abs() < tolerance
+            && ez.dot(ex).abs() < tolerance;
+        is_uniform.then(|| {
+            AreaLight::Sphere(SphereLight::new(
+                self.transformation * Point::default(),
+                radius,
+            ))
+        })
+    }
+}
+
+/// Geometrical shape corresponding to a plane.
+#[derive(Debug, Default)]
+pub struct Plane {
+    /// A generic plane is defined by means of a [`Transformation`](struct@Transformation)
+    /// on the X-Y plane.\
+    /// A [`scaling`](fn@crate::transformation::scaling) transformation has the
+    /// effect to change the sides length of the basic rectangle in the plane's
+    /// [parametrization](fn@plane_point_to_uv).
+    transformation: Transformation,
+    /// The [`Material`](struct@Material) of the sphere.
+    material: Material,
+}
+
+impl Plane {
+    /// Provides a constructor for [`Plane`](struct@Plane).
+    pub fn new(transformation: Transformation, material: Material) -> Self {
+        Plane {
+            transformation,
+            material,
+        }
+    }
+}
+
+/// Calculates normals to [`Plane`](struct@Plane)'s surface.
+///
+/// This Function is meant to be used inside [`Plane`](struct@Plane)'s
+/// [`RayIntersection`](trait@RayIntersection) implementation.\
+/// `ray_dir` is the direction of an impacting [`Ray`](struct@Ray)
+/// and is used to determine on which side of the surface the normal is calculated,\
+/// returned together with `true` if the ray hits the plane from the back.
+fn plane_normal(ray_dir: Vector) -> (Normal, bool) {
+    let normal = Normal::from((0., 0., 1.));
+    if Vector::from(normal).dot(ray_dir) < 0.0 {
+        (normal, false)
     } else {
-        result.neg()
+        (normal.neg(), true)
+    }
+}
+
+/// Returns parametrization coordinates of a point on a plane.
+///
+/// The plane is parametrized by the `[0,1]x[0,1]` square with periodic boundary conditions.
+fn plane_point_to_uv(point: Point) -> Vector2D {
+    Vector2D {
+        u: point.x - point.x.floor(),
+        v: point.y - point.y.floor(),
+    }
+}
+
+impl RayIntersection for Plane {
+    /// Finds intersections between a [`Ray`](struct@Ray) and a [`Plane`](struct@Plane).
+    fn ray_intersection(&self, ray: Ray) -> Option<HitRecord> {
+        let inv_ray = self.transformation.inverse() * ray;
+        if inv_ray.dir.z.abs() < 1e-5 {
+            return None;
+        }
+        let t = -inv_ray.origin.z / inv_ray.dir.z;
+        if (t <= inv_ray.tmin) || (t >= inv_ray.tmax) {
+            return None;
+        }
+        let hit_point = inv_ray.at(t);
+        let (normal, inside) = plane_normal(inv_ray.dir);
+        Some(HitRecord {
+            world_point: self.transformation * hit_point,
+            local_point: hit_point,
+            normal: self.transformation * normal,
+            inside,
+            surface_point: plane_point_to_uv(hit_point),
+            footprint: surface_footprint(
+                inv_ray,
+                hit_point,
+                Vector::from((0., 0., 1.)),
+                Vector::from((1., 0., 0.)),
+                Vector::from((0., 1., 0.)),
+            ),
+            t,
+            ray,
+            material: self.material.clone(),
+        })
+    }
+
+    /// A [`Plane`](struct@Plane) is crossed at most once,
+    /// leaving (or entering) the half-space below it.
+    fn all_intersections(&self, ray: Ray) -> Vec<HitRecord> {
+        self.ray_intersection(ray).into_iter().collect()
+    }
+
+    /// A [`Plane`](struct@Plane) is unbounded, always return [`None`].
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        None
+    }
+}
+
+/// Geometrical shape corresponding to a triangle.
+#[derive(Debug, Default)]
+pub struct Triangle {
+    /// A generic triangle is defined by means of a [`Transformation`](struct@Transformation)
+    /// on the unit triangle,\
+    /// with vertices `(0,0,0)`, `(1,0,0)` and `(0,1,0)` on the X-Y plane.
+    transformation: Transformation,
+    /// The [`Material`](struct@Material) of the triangle.
+    material: Material,
+}
+
+impl Triangle {
+    /// Provides a constructor for [`Triangle`](struct@Triangle).
+    pub fn new(transformation: Transformation, material: Material) -> Self {
+        Triangle {
+            transformation,
+            material,
+        }
+    }
+}
+
+/// Finds the intersection between a [`Ray`](struct@Ray) and a triangle
+/// of vertices `p0`, `p1` and `p2`.
+///
+/// Implements the Möller-Trumbore algorithm, returns the time of the impact\
+/// and the barycentric coordinates `(b1, b2)` of the point of impact
+/// with respect to `p1` and `p2`.
+fn triangle_intersection(p0: Point, p1: Point, p2: Point, ray: Ray) -> Option<(f32, f32, f32)> {
+    let edge1 = p1 - p0;
+    let edge2 = p2 - p0;
+    let p_vec = ray.dir * edge2;
+    let det = edge1.dot(p_vec);
+    if det.abs() < 1e-8 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let t_vec = ray.origin - p0;
+    let b1 = t_vec.dot(p_vec) * inv_det;
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+    let q_vec = t_vec * edge1;
+    let b2 = ray.dir.dot(q_vec) * inv_det;
+    if b2 < 0.0 || b1 + b2 > 1.0 {
+        return None;
+    }
+    let t = edge2.dot(q_vec) * inv_det;
+    if (t <= ray.tmin) || (t >= ray.tmax) {
+        return None;
+    }
+    Some((t, b1, b2))
+}
+
+/// Returns partial derivatives `(dp/du, dp/dv)` on the plane of a triangle,
+/// of vertices `points` with texture coordinates `uvs`.
+///
+/// Degenerate texture coordinates give zero derivatives.
+fn triangle_uv_derivatives(points: [Point; 3], uvs: [Vector2D; 3]) -> (Vector, Vector) {
+    let (dp02, dp12) = (points[0] - points[2], points[1] - points[2]);
+    let (du02, dv02) = (uvs[0].u - uvs[2].u, uvs[0].v - uvs[2].v);
+    let (du12, dv12) = (uvs[1].u - uvs[2].u, uvs[1].v - uvs[2].v);
+    let det = du02 * dv12 - dv02 * du12;
+    if det.abs() < 1e-8 {
+        return (Vector::from((0., 0., 0.)), Vector::from((0., 0., 0.)));
+    }
+    let inv_det = 1.0 / det;
+    (
+        (dp02 * dv12 - dp12 * dv02) * inv_det,
+        (dp12 * du02 - dp02 * du12) * inv_det,
+    )
+}
+
+/// Calculates normals to a triangle surface.
+///
+/// This Function is meant to be used inside [`Triangle`](struct@Triangle) and
+/// [`TriangleMesh`](struct@TriangleMesh) [`RayIntersection`](trait@RayIntersection)
+/// implementations.\
+/// `geometric` is the normal of the triangle plane, while `shading` is
+/// the (optionally interpolated) normal used for lighting.\
+/// `ray_dir` is the direction of an impacting [`Ray`](struct@Ray)
+/// and is used to determine on which side of the surface the normal is calculated,\
+/// returned together with `true` if the ray hits the triangle from the back.
+fn triangle_normal(geometric: Vector, shading: Vector, ray_dir: Vector) -> (Normal, bool) {
+    let inside = geometric.dot(ray_dir) >= 0.0;
+    let geometric = if inside { geometric.neg() } else { geometric };
+    let normal = Normal::from((shading.x, shading.y, shading.z));
+    if shading.dot(geometric) >= 0.0 {
+        (normal, inside)
+    } else {
+        (normal.neg(), inside)
+    }
+}
+
+impl RayIntersection for Triangle {
+    /// Finds intersections between a [`Ray`](struct@Ray) and a [`Triangle`](struct@Triangle).
+    fn ray_intersection(&self, ray: Ray) -> Option<HitRecord> {
+        let inv_ray = self.transformation.inverse() * ray;
+        let (t, b1, b2) = triangle_intersection(
+            Point::from((0., 0., 0.)),
+            Point::from((1., 0., 0.)),
+            Point::from((0., 1., 0.)),
+            inv_ray,
+        )?;
+        let normal = Vector::from((0., 0., 1.));
+        let hit_point = inv_ray.at(t);
+        let footprint = surface_footprint(
+            inv_ray,
+            hit_point,
+            normal,
+            Vector::from((1., 0., 0.)),
+            Vector::from((0., 1., 0.)),
+        );
+        let (normal, inside) = triangle_normal(normal, normal, inv_ray.dir);
+        Some(HitRecord {
+            world_point: self.transformation * hit_point,
+            local_point: hit_point,
+            normal: self.transformation * normal,
+            inside,
+            surface_point: Vector2D { u: b1, v: b2 },
+            footprint,
+            t,
+            ray,
+            material: self.material.clone(),
+        })
+    }
+
+    /// A [`Triangle`](struct@Triangle) is crossed at most once.
+    fn all_intersections(&self, ray: Ray) -> Vec<HitRecord> {
+        self.ray_intersection(ray).into_iter().collect()
+    }
+
+    /// Return the transformed box of the unit triangle.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        Some(
+            BoundingBox::new(Point::from((0., 0., 0.)), Point::from((1., 1., 0.)))
+                .transform(self.transformation),
+        )
+    }
+}
+
+/// Geometrical shape corresponding to a triangle mesh.
+#[derive(Debug)]
+pub struct TriangleMesh {
+    /// The [`Mesh`](struct@Mesh) buffers, shared between all the triangles
+    /// (and possibly between different meshes).
+    mesh: Arc<Mesh>,
+    /// A [`Bvh`](struct@Bvh) over the mesh faces, in the mesh frame of reference.
+    bvh: Bvh,
+    /// A generic mesh is placed in the world by means of a
+    /// [`Transformation`](struct@Transformation) on its vertices.
+    transformation: Transformation,
+    /// The [`Material`](struct@Material) of the mesh.
+    material: Material,
+}
+
+impl TriangleMesh {
+    /// Provides a constructor for [`TriangleMesh`](struct@TriangleMesh).
+    ///
+    /// Build the [`Bvh`](struct@Bvh) over `mesh` faces.
+    pub fn new(mesh: Arc<Mesh>, transformation: Transformation, material: Material) -> Self {
+        let bvh = Bvh::new(
+            (0..mesh.faces.len())
+                .map(|face| (face, mesh.face_bounding_box(face)))
+                .collect(),
+        );
+        TriangleMesh {
+            mesh,
+            bvh,
+            transformation,
+            material,
+        }
+    }
+
+    /// Finds the intersection between `inv_ray`, a [`Ray`](struct@Ray) in the mesh frame
+    /// of reference, and the `face`-th triangle.
+    ///
+    /// Vertex normals and texture coordinates, when present,
+    /// are interpolated on the point of impact.
+    fn face_intersection(&self, face: usize, inv_ray: Ray, ray: Ray) -> Option<HitRecord> {
+        let face = self.mesh.faces[face];
+        let [p0, p1, p2] = face.vertices.map(|i| self.mesh.vertices[i]);
+        let (t, b1, b2) = triangle_intersection(p0, p1, p2, inv_ray)?;
+        let b0 = 1.0 - b1 - b2;
+        let geometric = (p1 - p0) * (p2 - p0);
+        let shading = match face.normals {
+            Some(normals) => {
+                let [n0, n1, n2] = normals.map(|i| Vector::from(self.mesh.normals[i]));
+                n0 * b0 + n1 * b1 + n2 * b2
+            },
+            None => geometric,
+        };
+        let (surface_point, (dp_du, dp_dv)) = match face.uvs {
+            Some(uvs) => {
+                let [uv0, uv1, uv2] = uvs.map(|i| self.mesh.uvs[i]);
+                (
+                    Vector2D {
+                        u: uv0.u * b0 + uv1.u * b1 + uv2.u * b2,
+                        v: uv0.v * b0 + uv1.v * b1 + uv2.v * b2,
+                    },
+                    triangle_uv_derivatives([p0, p1, p2], [uv0, uv1, uv2]),
+                )
+            },
+            None => (Vector2D { u: b1, v: b2 }, (p1 - p0, p2 - p0)),
+        };
+        let hit_point = inv_ray.at(t);
+        let footprint = surface_footprint(inv_ray, hit_point, geometric, dp_du, dp_dv);
+        let (normal, inside) = triangle_normal(geometric, shading, inv_ray.dir);
+        Some(HitRecord {
+            world_point: self.transformation * hit_point,
+            local_point: hit_point,
+            normal: self.transformation * normal,
+            inside,
+            surface_point,
+            footprint,
+            t,
+            ray,
+            material: self.material.clone(),
+        })
+    }
+}
+
+impl RayIntersection for TriangleMesh {
+    /// Finds intersections between a [`Ray`](struct@Ray) and a
+    /// [`TriangleMesh`](struct@TriangleMesh).
+    fn ray_intersection(&self, ray: Ray) -> Option<HitRecord> {
+        let inv_ray = self.transformation.inverse() * ray;
+        self.bvh
+            .ray_intersection(inv_ray, |face| self.face_intersection(face, inv_ray, ray))
+    }
+
+    /// Collect the intersections with all the faces,
+    /// that bound the ray intervals inside a closed mesh.
+    fn all_intersections(&self, ray: Ray) -> Vec<HitRecord> {
+        let inv_ray = self.transformation.inverse() * ray;
+        let mut hits = Vec::new();
+        // Never report a closest hit, so that no face is pruned.
+        self.bvh.ray_intersection(inv_ray, |face| {
+            hits.extend(self.face_intersection(face, inv_ray, ray));
+            None
+        });
+        hits.sort_by(|a, b| a.t.total_cmp(&b.t));
+        hits
+    }
+
+    /// Return the transformed box of all the mesh vertices.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        (0..self.mesh.faces.len())
+            .map(|face| self.mesh.face_bounding_box(face))
+            .reduce(BoundingBox::union)
+            .map(|bbox| bbox.transform(self.transformation))
+    }
+}
+
+/// Intersection of a ray with the surface of a [`Primitive`](trait@Primitive) shape,
+/// everything in the shape frame of reference.
+struct LocalHit {
+    /// Time the ray travelled before the impact.
+    t: f32,
+    /// Outward normal (not normalized) of the surface on the point of impact.
+    normal: Vector,
+    /// Parametrization coordinates of the point of impact.
+    surface_point: Vector2D,
+    /// Partial derivatives `(dp/du, dp/dv)` of the parametrization on the point of impact.
+    derivatives: (Vector, Vector),
+}
+
+/// Orients an outward `normal` of a closed surface.
+///
+/// `ray_dir` is the direction of an impacting [`Ray`](struct@Ray) and\
+/// is used to determine on which side of the surface the normal is calculated,\
+/// returned together with `true` if the ray hits the surface from the inside.
+fn oriented_normal(normal: Vector, ray_dir: Vector) -> (Normal, bool) {
+    let result = Normal::from((normal.x, normal.y, normal.z));
+    if normal.dot(ray_dir) < 0.0 {
+        (result, false)
+    } else {
+        (result.neg(), true)
+    }
+}
+
+/// Common behaviour of the primitive shapes defined in unit object space,
+/// by their intersections with a ray in their frame of reference.
+trait Primitive {
+    /// Return the [`Transformation`](struct@Transformation) on the unit shape.
+    fn transformation(&self) -> Transformation;
+    /// Return the [`Material`](struct@Material) of the shape.
+    fn material(&self) -> &Material;
+    /// Return all the intersections between `inv_ray` and the unit shape,
+    /// whatever `inv_ray.tmin` and `inv_ray.tmax`.
+    fn local_hits(&self, inv_ray: Ray) -> Vec<LocalHit>;
+
+    /// Return the intersections between `ray` and the shape (between `ray.tmin` and `ray.tmax`),
+    /// sorted by increasing `t`.
+    fn hit_records(&self, ray: Ray) -> Vec<HitRecord> {
+        let inv_ray = self.transformation().inverse() * ray;
+        let mut hits: Vec<LocalHit> = self
+            .local_hits(inv_ray)
+            .into_iter()
+            .filter(|hit| (hit.t > inv_ray.tmin) && (hit.t < inv_ray.tmax))
+            .collect();
+        hits.sort_by(|a, b| a.t.total_cmp(&b.t));
+        hits.into_iter()
+            .map(|hit| self.hit_record(inv_ray, ray, hit))
+            .collect()
+    }
+
+    /// Build the [`HitRecord`](struct@HitRecord) of `ray` impacting the shape in `hit`,
+    /// where `inv_ray` is `ray` in the shape frame of reference.
+    fn hit_record(&self, inv_ray: Ray, ray: Ray, hit: LocalHit) -> HitRecord {
+        let hit_point = inv_ray.at(hit.t);
+        let (normal, inside) = oriented_normal(hit.normal, inv_ray.dir);
+        let (dp_du, dp_dv) = hit.derivatives;
+        HitRecord {
+            world_point: self.transformation() * hit_point,
+            local_point: hit_point,
+            normal: self.transformation() * normal,
+            inside,
+            surface_point: hit.surface_point,
+            footprint: surface_footprint(inv_ray, hit_point, hit.normal, dp_du, dp_dv),
+            t: hit.t,
+            ray,
+            material: self.material().clone(),
+        }
+    }
+}
+
+/// Returns the azimuthal angle of a point around the z axis, in `[0, 2π)`.
+fn azimuth(point: Point) -> f32 {
+    let phi = point.y.atan2(point.x);
+    if phi < 0.0 {
+        phi + 2.0 * PI
+    } else {
+        phi
+    }
+}
+
+/// Returns the partial derivative of a point with respect to the
+/// normalized azimuth `u = φ/2π` around the z axis.
+fn azimuth_derivative(point: Point) -> Vector {
+    Vector::from((-2.0 * PI * point.y, 2.0 * PI * point.x, 0.0))
+}
+
+/// Returns the unit radial direction of a point from the z axis.
+fn radial_direction(point: Point) -> Vector {
+    let phi = point.y.atan2(point.x);
+    Vector::from((phi.cos(), phi.sin(), 0.0))
+}
+
+/// Returns the real roots of `a t² + b t + c`, also when it degenerates to a linear equation.
+fn solve_quadratic(a: f32, b: f32, c: f32) -> Vec<f32> {
+    if a.abs() < 1e-8 {
+        if b.abs() < 1e-8 {
+            return Vec::new();
+        }
+        return vec![-c / b];
+    }
+    let delta = b * b - 4.0 * a * c;
+    if delta < 0.0 {
+        return Vec::new();
+    }
+    let sqrt_delta = delta.sqrt();
+    vec![(-b - sqrt_delta) / (2.0 * a), (-b + sqrt_delta) / (2.0 * a)]
+}
+
+/// Returns the intersection time of `inv_ray` with the `z = height` plane, if its
+/// point of impact is inside the unit disk, together with the point itself.
+fn cap_intersection(inv_ray: Ray, height: f32) -> Option<(f32, Point)> {
+    if inv_ray.dir.z.abs() < 1e-5 {
+        return None;
+    }
+    let t = (height - inv_ray.origin.z) / inv_ray.dir.z;
+    let point = inv_ray.at(t);
+    (point.x * point.x + point.y * point.y <= 1.0).then_some((t, point))
+}
+
+/// Geometrical shape corresponding to a disk.
+#[derive(Debug, Default)]
+pub struct Disk {
+    /// A generic disk is defined by means of a [`Transformation`](struct@Transformation)
+    /// on the unit disk centered at the origin of axis on the X-Y plane.\
+    /// The disk is parametrized by its azimuth `u = φ/2π` and radius `v = r`.
+    transformation: Transformation,
+    /// The [`Material`](struct@Material) of the disk.
+    material: Material,
+}
+
+impl Disk {
+    /// Provides a constructor for [`Disk`](struct@Disk).
+    pub fn new(transformation: Transformation, material: Material) -> Self {
+        Disk {
+            transformation,
+            material,
+        }
+    }
+}
+
+impl Primitive for Disk {
+    fn transformation(&self) -> Transformation {
+        self.transformation
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    /// A [`Disk`](struct@Disk) is an open surface, with outward normal along the z axis.
+    fn local_hits(&self, inv_ray: Ray) -> Vec<LocalHit> {
+        cap_intersection(inv_ray, 0.0)
+            .map(|(t, point)| LocalHit {
+                t,
+                normal: Vector::from((0., 0., 1.)),
+                surface_point: Vector2D {
+                    u: azimuth(point) / (2.0 * PI),
+                    v: (point.x * point.x + point.y * point.y).sqrt(),
+                },
+                derivatives: (azimuth_derivative(point), radial_direction(point)),
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+impl RayIntersection for Disk {
+    /// Finds intersections between a [`Ray`](struct@Ray) and a [`Disk`](struct@Disk).
+    fn ray_intersection(&self, ray: Ray) -> Option<HitRecord> {
+        self.hit_records(ray).into_iter().next()
+    }
+
+    /// A [`Disk`](struct@Disk) is crossed at most once.
+    fn all_intersections(&self, ray: Ray) -> Vec<HitRecord> {
+        self.hit_records(ray)
+    }
+
+    /// Return the transformed box of the unit disk.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        Some(
+            BoundingBox::new(Point::from((-1., -1., 0.)), Point::from((1., 1., 0.)))
+                .transform(self.transformation),
+        )
+    }
+}
+
+/// Geometrical shape corresponding to an axis-aligned box.
+#[derive(Debug, Default)]
+pub struct Cube {
+    /// A generic box is defined by means of a [`Transformation`](struct@Transformation)
+    /// on the `[-1,1]x[-1,1]x[-1,1]` cube.\
+    /// This means that you can also get a box with different sides length
+    /// using the proper [`scaling`](fn@crate::transformation::scaling).\
+    /// Each face is parametrized by a tile of a `3x2` atlas on the `[0,1]x[0,1]` square,
+    /// in order `+x`, `-x`, `+y` (bottom row) and `-y`, `+z`, `-z` (top row).
+    transformation: Transformation,
+    /// The [`Material`](struct@Material) of the box.
+    material: Material,
+}
+
+impl Cube {
+    /// Provides a constructor for [`Cube`](struct@Cube).
+    pub fn new(transformation: Transformation, material: Material) -> Self {
+        Cube {
+            transformation,
+            material,
+        }
+    }
+}
+
+/// Returns the [`LocalHit`](struct@LocalHit) of `inv_ray` on the face of the unit cube
+/// orthogonal to `axis` (`0`, `1` or `2` for x, y or z), at time `t`.
+fn cube_local_hit(inv_ray: Ray, t: f32, axis: usize) -> LocalHit {
+    let point = inv_ray.at(t);
+    let coords = [point.x, point.y, point.z];
+    let axes = [
+        Vector::from((1., 0., 0.)),
+        Vector::from((0., 1., 0.)),
+        Vector::from((0., 0., 1.)),
+    ];
+    let sign = if coords[axis] < 0.0 { -1.0 } else { 1.0 };
+    let face = 2 * axis + usize::from(sign < 0.0);
+    // Face coordinates along the other two axes, in `[0,1]`.
+    let (axis_u, axis_v) = ((axis + 1) % 3, (axis + 2) % 3);
+    let local = |axis: usize| ((coords[axis] + 1.0) / 2.0).clamp(0.0, 1.0);
+    LocalHit {
+        t,
+        normal: axes[axis] * sign,
+        surface_point: Vector2D {
+            u: ((face % 3) as f32 + local(axis_u)) / 3.0,
+            v: ((face / 3) as f32 + local(axis_v)) / 2.0,
+        },
+        derivatives: (axes[axis_u] * 6.0, axes[axis_v] * 4.0),
+    }
+}
+
+impl Primitive for Cube {
+    fn transformation(&self) -> Transformation {
+        self.transformation
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    /// Intersects the three slabs between opposite faces of the unit cube.
+    fn local_hits(&self, inv_ray: Ray) -> Vec<LocalHit> {
+        let origin = [inv_ray.origin.x, inv_ray.origin.y, inv_ray.origin.z];
+        let dir = [inv_ray.dir.x, inv_ray.dir.y, inv_ray.dir.z];
+        let (mut t_near, mut t_far) = ((f32::NEG_INFINITY, 0), (f32::INFINITY, 0));
+        for axis in 0..3 {
+            if dir[axis] == 0.0 {
+                if origin[axis].abs() > 1.0 {
+                    return Vec::new();
+                }
+                continue;
+            }
+            let t1 = (-1.0 - origin[axis]) / dir[axis];
+            let t2 = (1.0 - origin[axis]) / dir[axis];
+            let (t1, t2) = (t1.min(t2), t1.max(t2));
+            if t1 > t_near.0 {
+                t_near = (t1, axis);
+            }
+            if t2 < t_far.0 {
+                t_far = (t2, axis);
+            }
+        }
+        if t_near.0 > t_far.0 {
+            return Vec::new();
+        }
+        [t_near, t_far]
+            .into_iter()
+            .map(|(t, axis)| cube_local_hit(inv_ray, t, axis))
+            .collect()
+    }
+}
+
+impl RayIntersection for Cube {
+    /// Finds intersections between a [`Ray`](struct@Ray) and a [`Cube`](struct@Cube).
+    fn ray_intersection(&self, ray: Ray) -> Option<HitRecord> {
+        self.hit_records(ray).into_iter().next()
+    }
+
+    /// Finds both the entry and the exit of a [`Ray`](struct@Ray)
+    /// through a [`Cube`](struct@Cube).
+    fn all_intersections(&self, ray: Ray) -> Vec<HitRecord> {
+        self.hit_records(ray)
+    }
+
+    /// Return the transformed box of the unit cube.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        Some(
+            BoundingBox::new(Point::from((-1., -1., -1.)), Point::from((1., 1., 1.)))
+                .transform(self.transformation),
+        )
+    }
+}
+
+/// Geometrical shape corresponding to a closed cylinder.
+#[derive(Debug, Default)]
+pub struct Cylinder {
+    /// A generic cylinder is defined by means of a [`Transformation`](struct@Transformation)
+    /// on the unit cylinder of radius `1` around the z axis, between `z=0` and `z=1`,
+    /// closed by two caps.\
+    /// The cylinder is parametrized by its azimuth `u = φ/2π`, and by `v` going
+    /// from the bottom cap center (`v=0`) up the side (`v` in `[1/3,2/3]`)
+    /// to the top cap center (`v=1`).
+    transformation: Transformation,
+    /// The [`Material`](struct@Material) of the cylinder.
+    material: Material,
+}
+
+impl Cylinder {
+    /// Provides a constructor for [`Cylinder`](struct@Cylinder).
+    pub fn new(transformation: Transformation, material: Material) -> Self {
+        Cylinder {
+            transformation,
+            material,
+        }
+    }
+}
+
+impl Primitive for Cylinder {
+    fn transformation(&self) -> Transformation {
+        self.transformation
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    /// Intersects the side, where `x²+y²=1`, and the two caps of the unit cylinder.
+    fn local_hits(&self, inv_ray: Ray) -> Vec<LocalHit> {
+        let (o, d) = (inv_ray.origin, inv_ray.dir);
+        let mut hits: Vec<LocalHit> = solve_quadratic(
+            d.x * d.x + d.y * d.y,
+            2.0 * (o.x * d.x + o.y * d.y),
+            o.x * o.x + o.y * o.y - 1.0,
+        )
+        .into_iter()
+        .filter(|t| (0.0..=1.0).contains(&inv_ray.at(*t).z))
+        .map(|t| {
+            let point = inv_ray.at(t);
+            LocalHit {
+                t,
+                normal: Vector::from((point.x, point.y, 0.0)),
+                surface_point: Vector2D {
+                    u: azimuth(point) / (2.0 * PI),
+                    v: (1.0 + point.z) / 3.0,
+                },
+                derivatives: (azimuth_derivative(point), Vector::from((0., 0., 3.))),
+            }
+        })
+        .collect();
+        for (height, sign) in [(0.0, -1.0), (1.0, 1.0)] {
+            if let Some((t, point)) = cap_intersection(inv_ray, height) {
+                let radius = (point.x * point.x + point.y * point.y).sqrt();
+                hits.push(LocalHit {
+                    t,
+                    normal: Vector::from((0., 0., sign)),
+                    surface_point: Vector2D {
+                        u: azimuth(point) / (2.0 * PI),
+                        v: height - sign * radius / 3.0,
+                    },
+                    derivatives: (
+                        azimuth_derivative(point),
+                        radial_direction(point) * (-3.0 * sign),
+                    ),
+                });
+            }
+        }
+        hits
+    }
+}
+
+impl RayIntersection for Cylinder {
+    /// Finds intersections between a [`Ray`](struct@Ray) and a [`Cylinder`](struct@Cylinder).
+    fn ray_intersection(&self, ray: Ray) -> Option<HitRecord> {
+        self.hit_records(ray).into_iter().next()
+    }
+
+    /// Finds both the entry and the exit of a [`Ray`](struct@Ray)
+    /// through a [`Cylinder`](struct@Cylinder).
+    fn all_intersections(&self, ray: Ray) -> Vec<HitRecord> {
+        self.hit_records(ray)
+    }
+
+    /// Return the transformed box of the unit cylinder.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        Some(
+            BoundingBox::new(Point::from((-1., -1., 0.)), Point::from((1., 1., 1.)))
+                .transform(self.transformation),
+        )
+    }
+}
+
+/// Geometrical shape corresponding to a closed cone.
+#[derive(Debug, Default)]
+pub struct Cone {
+    /// A generic cone is defined by means of a [`Transformation`](struct@Transformation)
+    /// on the unit cone around the z axis, with base of radius `1` on `z=0`
+    /// and apex in `(0,0,1)`, closed by its base.\
+    /// The cone is parametrized by its azimuth `u = φ/2π`, and by `v` going
+    /// from the base center (`v=0`) up the side (`v` in `[1/2,1]`) to the apex.
+    transformation: Transformation,
+    /// The [`Material`](struct@Material) of the cone.
+    material: Material,
+}
+
+impl Cone {
+    /// Provides a constructor for [`Cone`](struct@Cone).
+    pub fn new(transformation: Transformation, material: Material) -> Self {
+        Cone {
+            transformation,
+            material,
+        }
+    }
+}
+
+impl Primitive for Cone {
+    fn transformation(&self) -> Transformation {
+        self.transformation
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    /// Intersects the side, where `x²+y²=(1-z)²`, and the base of the unit cone.
+    fn local_hits(&self, inv_ray: Ray) -> Vec<LocalHit> {
+        let (o, d) = (inv_ray.origin, inv_ray.dir);
+        let height = 1.0 - o.z;
+        let mut hits: Vec<LocalHit> = solve_quadratic(
+            d.x * d.x + d.y * d.y - d.z * d.z,
+            2.0 * (o.x * d.x + o.y * d.y + height * d.z),
+            o.x * o.x + o.y * o.y - height * height,
+        )
+        .into_iter()
+        // Discard the upper nappe of the double cone.
+        .filter(|t| (0.0..=1.0).contains(&inv_ray.at(*t).z))
+        .map(|t| {
+            let point = inv_ray.at(t);
+            LocalHit {
+                t,
+                normal: Vector::from((point.x, point.y, 1.0 - point.z)),
+                surface_point: Vector2D {
+                    u: azimuth(point) / (2.0 * PI),
+                    v: (1.0 + point.z) / 2.0,
+                },
+                derivatives: (
+                    azimuth_derivative(point),
+                    (Vector::from((0., 0., 1.)) - radial_direction(point)) * 2.0,
+                ),
+            }
+        })
+        .collect();
+        if let Some((t, point)) = cap_intersection(inv_ray, 0.0) {
+            hits.push(LocalHit {
+                t,
+                normal: Vector::from((0., 0., -1.)),
+                surface_point: Vector2D {
+                    u: azimuth(point) / (2.0 * PI),
+                    v: (point.x * point.x + point.y * point.y).sqrt() / 2.0,
+                },
+                derivatives: (azimuth_derivative(point), radial_direction(point) * 2.0),
+            });
+        }
+        hits
+    }
+}
+
+impl RayIntersection for Cone {
+    /// Finds intersections between a [`Ray`](struct@Ray) and a [`Cone`](struct@Cone).
+    fn ray_intersection(&self, ray: Ray) -> Option<HitRecord> {
+        self.hit_records(ray).into_iter().next()
+    }
+
+    /// Finds both the entry and the exit of a [`Ray`](struct@Ray)
+    /// through a [`Cone`](struct@Cone).
+    fn all_intersections(&self, ray: Ray) -> Vec<HitRecord> {
+        self.hit_records(ray)
+    }
+
+    /// Return the transformed box of the unit cone.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        Some(
+            BoundingBox::new(Point::from((-1., -1., 0.)), Point::from((1., 1., 1.)))
+                .transform(self.transformation),
+        )
+    }
+}
+
+/// Returns the largest real root of the cubic `x³ + a x² + b x + c`,
+/// with [Cardano](https://en.wikipedia.org/wiki/Cubic_equation#Cardano's_formula)'s
+/// formula or its trigonometric form, polished by a Newton step.
+fn largest_cubic_root(a: f64, b: f64, c: f64) -> f64 {
+    // Depressed cubic t³ + p t + q, with x = t - a/3.
+    let p = b - a * a / 3.0;
+    let q = 2.0 * a * a * a / 27.0 - a * b / 3.0 + c;
+    let discriminant = q * q / 4.0 + p * p * p / 27.0;
+    let t = if discriminant > 0.0 {
+        let sqrt_discriminant = discriminant.sqrt();
+        (-q / 2.0 + sqrt_discriminant).cbrt() + (-q / 2.0 - sqrt_discriminant).cbrt()
+    } else if p < 0.0 {
+        // Three real roots, the largest one.
+        let rho = (-p / 3.0).sqrt();
+        2.0 * rho * ((-q / (2.0 * rho * rho * rho)).clamp(-1.0, 1.0).acos() / 3.0).cos()
+    } else {
+        0.0
+    };
+    let x = t - a / 3.0;
+    let derivative = (3.0 * x + 2.0 * a) * x + b;
+    if derivative.abs() > 1e-12 {
+        x - (((x + a) * x + b) * x + c) / derivative
+    } else {
+        x
+    }
+}
+
+/// Returns the real roots of the quartic `x⁴ + a x³ + b x² + c x + d`,
+/// with [Ferrari](https://en.wikipedia.org/wiki/Quartic_function#Ferrari's_solution)'s
+/// method, polished by two Newton steps.
+fn solve_quartic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    // Depressed quartic y⁴ + p y² + q y + r, with x = y - a/4.
+    let a2 = a * a;
+    let p = b - 3.0 * a2 / 8.0;
+    let q = c - a * b / 2.0 + a2 * a / 8.0;
+    let r = d - a * c / 4.0 + a2 * b / 16.0 - 3.0 * a2 * a2 / 256.0;
+    // A positive root of the resolvent cubic factorizes the quartic in two quadratics.
+    let m = largest_cubic_root(p, p * p / 4.0 - r, -q * q / 8.0);
+    let mut roots = Vec::new();
+    if m > 1e-12 {
+        let sqrt_2m = (2.0 * m).sqrt();
+        for sign in [1.0, -1.0] {
+            let delta = -(2.0 * p + 2.0 * m + sign * 2.0 * q / sqrt_2m);
+            if delta >= 0.0 {
+                let sqrt_delta = delta.sqrt();
+                roots.push((sign * sqrt_2m + sqrt_delta) / 2.0);
+                roots.push((sign * sqrt_2m - sqrt_delta) / 2.0);
+            }
+        }
+    } else {
+        // Biquadratic quartic y⁴ + p y² + r.
+        let delta = p * p - 4.0 * r;
+        if delta >= 0.0 {
+            for z in [(-p + delta.sqrt()) / 2.0, (-p - delta.sqrt()) / 2.0] {
+                if z >= 0.0 {
+                    roots.push(z.sqrt());
+                    roots.push(-z.sqrt());
+                }
+            }
+        }
+    }
+    roots
+        .into_iter()
+        .map(|y| {
+            let mut x = y - a / 4.0;
+            for _ in 0..2 {
+                let value = (((x + a) * x + b) * x + c) * x + d;
+                let derivative = ((4.0 * x + 3.0 * a) * x + 2.0 * b) * x + c;
+                if derivative.abs() > 1e-12 {
+                    x -= value / derivative;
+                }
+            }
+            x
+        })
+        .collect()
+}
+
+/// Geometrical shape corresponding to a torus.
+#[derive(Debug)]
+pub struct Torus {
+    /// A generic torus is defined by means of a [`Transformation`](struct@Transformation)
+    /// on the unit torus around the z axis, with major radius `1`.\
+    /// The torus is parametrized by the azimuth `u = φ/2π` around the z axis
+    /// and by the angle `v = θ/2π` around its tube.
+    transformation: Transformation,
+    /// The [`Material`](struct@Material) of the torus.
+    material: Material,
+    /// The radius of the tube, smaller than the major radius `1`.
+    minor_radius: f32,
+}
+
+impl Default for Torus {
+    fn default() -> Self {
+        Torus {
+            transformation: Transformation::default(),
+            material: Material::default(),
+            minor_radius: 0.25,
+        }
+    }
+}
+
+impl Torus {
+    /// Provides a constructor for [`Torus`](struct@Torus).
+    pub fn new(transformation: Transformation, material: Material, minor_radius: f32) -> Self {
+        Torus {
+            transformation,
+            material,
+            minor_radius,
+        }
+    }
+}
+
+impl Primitive for Torus {
+    fn transformation(&self) -> Transformation {
+        self.transformation
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    /// Solves the quartic equation `(x²+y²+z²+1-r²)² = 4(x²+y²)` of the unit torus.
+    ///
+    /// For numerical stability, the ray origin is first moved to its point closest
+    /// to the torus center and the quartic is solved in double precision.
+    fn local_hits(&self, inv_ray: Ray) -> Vec<LocalHit> {
+        let (o, d) = (inv_ray.origin, inv_ray.dir);
+        let (o, d) = (
+            [o.x as f64, o.y as f64, o.z as f64],
+            [d.x as f64, d.y as f64, d.z as f64],
+        );
+        let dot = |a: [f64; 3], b: [f64; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+        let dd = dot(d, d);
+        let t0 = -dot(o, d) / dd;
+        let o = [o[0] + t0 * d[0], o[1] + t0 * d[1], o[2] + t0 * d[2]];
+        let minor_radius = self.minor_radius as f64;
+        let (od, e) = (dot(o, d), dot(o, o) + 1.0 - minor_radius * minor_radius);
+        let c4 = dd * dd;
+        solve_quartic(
+            4.0 * dd * od / c4,
+            (2.0 * dd * e + 4.0 * od * od - 4.0 * (d[0] * d[0] + d[1] * d[1])) / c4,
+            (4.0 * od * e - 8.0 * (o[0] * d[0] + o[1] * d[1])) / c4,
+            (e * e - 4.0 * (o[0] * o[0] + o[1] * o[1])) / c4,
+        )
+        .into_iter()
+        .map(|s| {
+            let t = (s + t0) as f32;
+            let point = inv_ray.at(t);
+            let rho = (point.x * point.x + point.y * point.y).sqrt();
+            let mut theta = point.z.atan2(rho - 1.0);
+            if theta < 0.0 {
+                theta += 2.0 * PI;
+            }
+            let radial = radial_direction(point);
+            LocalHit {
+                t,
+                normal: Vector::from(point) - radial,
+                surface_point: Vector2D {
+                    u: azimuth(point) / (2.0 * PI),
+                    v: theta / (2.0 * PI),
+                },
+                derivatives: (
+                    azimuth_derivative(point),
+                    (Vector::from((0., 0., rho - 1.0)) - radial * point.z) * (2.0 * PI),
+                ),
+            }
+        })
+        .collect()
+    }
+}
+
+impl RayIntersection for Torus {
+    /// Finds intersections between a [`Ray`](struct@Ray) and a [`Torus`](struct@Torus).
+    fn ray_intersection(&self, ray: Ray) -> Option<HitRecord> {
+        self.hit_records(ray).into_iter().next()
+    }
+
+    /// Finds all the (up to four) crossings of a [`Ray`](struct@Ray)
+    /// through a [`Torus`](struct@Torus).
+    fn all_intersections(&self, ray: Ray) -> Vec<HitRecord> {
+        self.hit_records(ray)
+    }
+
+    /// Return the transformed box of the unit torus.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let (outer, r) = (1.0 + self.minor_radius, self.minor_radius);
+        Some(
+            BoundingBox::new(
+                Point::from((-outer, -outer, -r)),
+                Point::from((outer, outer, r)),
+            )
+            .transform(self.transformation),
+        )
+    }
+}
+
+/// Maximum number of steps of a sphere tracing march.
+const MAX_MARCH_STEPS: usize = 512;
+/// Distance from an [`SdfShape`](struct@SdfShape) surface
+/// under which a sphere tracing march hits it.
+const HIT_DISTANCE: f32 = 1e-4;
+/// Range of a sphere tracing march along an unbounded [`SdfShape`](struct@SdfShape).
+const MAX_MARCH_DISTANCE: f32 = 1e3;
+
+/// Geometrical shape corresponding to the surface of a signed distance field.
+///
+/// The surface is found by [sphere tracing](https://doi.org/10.1007/s003710050084)
+/// (Hart, 1996):\
+/// the ray marches by steps as long as the distance from the surface,
+/// until it gets closer than a small threshold.\
+/// Normals are the gradient of the field, while the surface is parametrized
+/// by the spherical coordinates of the point (see [`sphere_point_to_uv`]),\
+/// so pigments with `object` space (see [`NoisePigment`](struct@crate::material::NoisePigment))
+/// suit better these shapes.
+#[derive(Debug)]
+pub struct SdfShape {
+    /// A generic field surface is defined by means of a [`Transformation`](struct@Transformation)
+    /// on the [`Sdf`](enum@Sdf) frame of reference.
+    transformation: Transformation,
+    /// The [`Material`](struct@Material) of the surface.
+    material: Material,
+    /// The signed distance field.
+    sdf: Sdf,
+}
+
+impl SdfShape {
+    /// Provides a constructor for [`SdfShape`](struct@SdfShape).
+    pub fn new(transformation: Transformation, material: Material, sdf: Sdf) -> Self {
+        SdfShape {
+            transformation,
+            material,
+            sdf,
+        }
+    }
+
+    /// Return the range of `inv_ray` times to march, inside the field bounding box.
+    ///
+    /// The box is padded, so that a march entering it never starts on the surface.
+    fn march_range(&self, inv_ray: Ray) -> Option<(f32, f32)> {
+        match self.sdf.bounding_box() {
+            Some(bbox) => {
+                let pad = Vector::from((1., 1., 1.)) * (4.0 * HIT_DISTANCE);
+                BoundingBox::new(bbox.min - pad, bbox.max + pad).ray_range(inv_ray)
+            },
+            None => {
+                let max_t = MAX_MARCH_DISTANCE / inv_ray.dir.norm();
+                Some((inv_ray.tmin.max(-max_t), inv_ray.tmax.min(max_t)))
+            },
+        }
+    }
+
+    /// Sphere trace `inv_ray` from time `t` up to `t_end`,
+    /// returning the time of the first crossing of the surface, if any.
+    ///
+    /// A ray starting on the surface (like a scattered ray, or a ray
+    /// continuing after a crossing) first steps away from it,
+    /// so that the side of the surface it marches on is known.
+    fn march(&self, inv_ray: Ray, mut t: f32, t_end: f32) -> Option<f32> {
+        let speed = inv_ray.dir.norm();
+        let mut distance = self.sdf.distance(inv_ray.at(t));
+        let mut steps = 0;
+        while distance.abs() < HIT_DISTANCE {
+            t += 2.0 * HIT_DISTANCE / speed;
+            distance = self.sdf.distance(inv_ray.at(t));
+            steps += 1;
+            if steps >= MAX_MARCH_STEPS || t > t_end {
+                return None;
+            }
+        }
+        let side = distance.signum();
+        for _ in steps..MAX_MARCH_STEPS {
+            if t > t_end {
+                return None;
+            }
+            let distance = side * self.sdf.distance(inv_ray.at(t));
+            if distance < HIT_DISTANCE {
+                return Some(t);
+            }
+            t += distance / speed;
+        }
+        None
+    }
+
+    /// Build the [`LocalHit`](struct@LocalHit) of `inv_ray` on the surface at time `t`.
+    fn local_hit(&self, inv_ray: Ray, t: f32) -> LocalHit {
+        let point = inv_ray.at(t);
+        let direction = Vector::from(point);
+        let surface_point = if direction.norm() > 0.0 {
+            let direction = direction.normalize();
+            sphere_point_to_uv(Point::from((direction.x, direction.y, direction.z)))
+        } else {
+            Vector2D { u: 0.0, v: 0.0 }
+        };
+        let zero = Vector::from((0., 0., 0.));
+        LocalHit {
+            t,
+            normal: self.sdf.gradient(point, HIT_DISTANCE),
+            surface_point,
+            // No parametrization derivatives, so pigments are not filtered.
+            derivatives: (zero, zero),
+        }
+    }
+}
+
+impl Primitive for SdfShape {
+    fn transformation(&self) -> Transformation {
+        self.transformation
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    /// Marching needs a finite range, so only the crossings
+    /// between `inv_ray.tmin` and `inv_ray.tmax` are returned.
+    fn local_hits(&self, inv_ray: Ray) -> Vec<LocalHit> {
+        let mut hits = Vec::new();
+        if let Some((mut t, t_end)) = self.march_range(inv_ray) {
+            while let Some(t_hit) = self.march(inv_ray, t, t_end) {
+                hits.push(self.local_hit(inv_ray, t_hit));
+                t = t_hit;
+            }
+        }
+        hits
+    }
+}
+
+impl RayIntersection for SdfShape {
+    /// Finds the first crossing between a [`Ray`](struct@Ray) and
+    /// an [`SdfShape`](struct@SdfShape) surface.
+    fn ray_intersection(&self, ray: Ray) -> Option<HitRecord> {
+        let inv_ray = self.transformation.inverse() * ray;
+        let (t, t_end) = self.march_range(inv_ray)?;
+        let t_hit = self.march(inv_ray, t, t_end)?;
+        Some(self.hit_record(inv_ray, ray, self.local_hit(inv_ray, t_hit)))
+    }
+
+    /// Finds all the crossings of a [`Ray`](struct@Ray)
+    /// through an [`SdfShape`](struct@SdfShape) surface.
+    fn all_intersections(&self, ray: Ray) -> Vec<HitRecord> {
+        self.hit_records(ray)
+    }
+
+    /// Return the transformed box of the field,
+    /// or [`None`] if it is unbounded.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        self.sdf
+            .bounding_box()
+            .map(|bbox| bbox.transform(self.transformation))
+    }
+}
+
+/// Variants of boolean operations between the two children of a [`Csg`](struct@Csg).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CsgOperation {
+    /// Points inside any of the children.
+    Union,
+    /// Points inside both the children.
+    Intersection,
+    /// Points inside the first child, but outside the second one.
+    Difference,
+}
+
+impl CsgOperation {
+    /// Return `true` if a point inside the first child (`in_left`)
+    /// and inside the second one (`in_right`) is inside their combination.
+    fn contains(&self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => in_left || in_right,
+            CsgOperation::Intersection => in_left && in_right,
+            CsgOperation::Difference => in_left && !in_right,
+        }
+    }
+}
+
+/// Geometrical shape combining two shapes with constructive solid geometry.
+///
+/// Children are combined by a boolean [`CsgOperation`](enum@CsgOperation)
+/// on the intervals of a ray inside each of them
+/// (see [`all_intersections`](trait@RayIntersection#tymethod.all_intersections)),\
+/// so they should be closed shapes (or half-spaces, like [`Plane`](struct@Plane)).\
+/// The surface of the combination keeps the [`Material`](struct@Material)
+/// of the child it belongs to.
+#[derive(Debug)]
+pub struct Csg {
+    /// Boolean operation between the children.
+    operation: CsgOperation,
+    /// First child.
+    left: Arc<dyn RayIntersection>,
+    /// Second child.
+    right: Arc<dyn RayIntersection>,
+}
+
+impl Csg {
+    /// Provides a constructor for [`Csg`](struct@Csg).
+    pub fn new(
+        operation: CsgOperation,
+        left: Arc<dyn RayIntersection>,
+        right: Arc<dyn RayIntersection>,
+    ) -> Self {
+        Csg {
+            operation,
+            left,
+            right,
+        }
+    }
+}
+
+impl RayIntersection for Csg {
+    /// Finds the closest intersection between a [`Ray`](struct@Ray)
+    /// and a [`Csg`](struct@Csg).
+    fn ray_intersection(&self, ray: Ray) -> Option<HitRecord> {
+        self.all_intersections(ray).into_iter().next()
+    }
+
+    /// Children are intersected along the whole ray line, to know if its origin is inside them,\
+    /// then only their intersections where the ray enters or leaves the combination are kept.
+    fn all_intersections(&self, ray: Ray) -> Vec<HitRecord> {
+        let line = Ray {
+            tmin: f32::NEG_INFINITY,
+            tmax: f32::INFINITY,
+            ..ray
+        };
+        let (left, right) = (
+            self.left.all_intersections(line),
+            self.right.all_intersections(line),
+        );
+        // A ray line starts inside a child if it first leaves it.
+        let mut in_children = [left.as_slice(), right.as_slice()]
+            .map(|hits| hits.first().is_some_and(|hit| hit.inside));
+        let mut crossings: Vec<(usize, HitRecord)> = left
+            .into_iter()
+            .map(|hit| (0, hit))
+            .chain(right.into_iter().map(|hit| (1, hit)))
+            .collect();
+        crossings.sort_by(|(_, a), (_, b)| a.t.total_cmp(&b.t));
+        let mut inside = self.operation.contains(in_children[0], in_children[1]);
+        let mut hits = Vec::new();
+        for (child, hit) in crossings {
+            in_children[child] = !hit.inside;
+            let now_inside = self.operation.contains(in_children[0], in_children[1]);
+            if now_inside == inside {
+                continue;
+            }
+            if (hit.t > ray.tmin) && (hit.t < ray.tmax) {
+                hits.push(HitRecord { inside, ray, ..hit });
+            }
+            inside = now_inside;
+        }
+        hits
+    }
+
+    /// Return the box of the children that can contain the combination,
+    /// or [`None`] if it is unbounded.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let (left, right) = (self.left.bounding_box(), self.right.bounding_box());
+        match self.operation {
+            CsgOperation::Union => left.zip(right).map(|(left, right)| left.union(right)),
+            CsgOperation::Intersection => left.or(right),
+            CsgOperation::Difference => left,
+        }
     }
 }
 
-/// Returns parametrization coordinates of a point on a sphere.
+/// Geometrical shape collecting a group of shapes, in a shared frame of reference.
 ///
-/// The sphere's surface is parametrized by two angles that correspond
-/// to latitude and longitude.
-fn sphere_point_to_uv(point: Point) -> Vector2D {
-    let mut u = point.y.atan2(point.x) / (2.0 * PI);
-    let v = point.z.acos() / PI;
-    if u < 0.0 {
-        u += 1.0
-    };
-    Vector2D { u, v }
+/// A group is meant to be built once and placed many times by [`Instance`](struct@Instance)s,
+/// so that its shapes (e.g. the geometry of a [`TriangleMesh`](struct@TriangleMesh))
+/// are shared in memory.\
+/// Bounded shapes are searched through a [`Bvh`](struct@Bvh), the unbounded ones one by one.
+#[derive(Debug)]
+pub struct Group {
+    /// Shapes of the group.
+    shapes: Vec<Arc<dyn RayIntersection>>,
+    /// A [`Bvh`](struct@Bvh) over the bounded shapes.
+    bvh: Bvh,
+    /// Indices of the unbounded shapes.
+    unbounded: Vec<usize>,
 }
 
-impl RayIntersection for Sphere {
-    /// Finds intersections between a [`Ray`](struct@Ray) and a [`Sphere`](struct@Sphere).
+impl Group {
+    /// Provides a constructor for [`Group`](struct@Group),
+    /// building the [`Bvh`](struct@Bvh) over its shapes.
+    pub fn new(shapes: Vec<Arc<dyn RayIntersection>>) -> Self {
+        let mut primitives = Vec::new();
+        let mut unbounded = Vec::new();
+        for (index, shape) in shapes.iter().enumerate() {
+            match shape.bounding_box() {
+                Some(bbox) => primitives.push((index, bbox)),
+                None => unbounded.push(index),
+            }
+        }
+        Group {
+            shapes,
+            bvh: Bvh::new(primitives),
+            unbounded,
+        }
+    }
+}
+
+impl RayIntersection for Group {
+    /// Finds the closest intersection between a [`Ray`](struct@Ray)
+    /// and the shapes of a [`Group`](struct@Group).
     fn ray_intersection(&self, ray: Ray) -> Option<HitRecord> {
-        let inv_ray = self.transformation.inverse() * ray;
-        let origin_vec = Vector::from(inv_ray.origin);
-        let a = inv_ray.dir.squared_norm();
-        let b = 2.0 * origin_vec.dot(inv_ray.dir);
-        let c = origin_vec.squared_norm() - 1.0;
+        let closest = self
+            .bvh
+            .ray_intersection(ray, |index| self.shapes[index].ray_intersection(ray));
+        self.unbounded
+            .iter()
+            .filter_map(|index| self.shapes[*index].ray_intersection(ray))
+            .chain(closest)
+            .min_by(|a, b| a.t.total_cmp(&b.t))
+    }
 
-        let delta = b * b - 4.0 * a * c;
-        if delta <= 0.0 {
-            return None;
-        }
-        let sqrt_delta = delta.sqrt();
-        let t1 = (-b - sqrt_delta) / (2.0 * a);
-        let t2 = (-b + sqrt_delta) / (2.0 * a);
-        let first_hit_t;
-        if (t1 > inv_ray.tmin) && (t1 < inv_ray.tmax) {
-            first_hit_t = t1;
-        } else if (t2 > inv_ray.tmin) && (t2 < inv_ray.tmax) {
-            first_hit_t = t2;
-        } else {
+    /// Collects the intersections of all the shapes of a [`Group`](struct@Group),
+    /// so its shapes should not overlap to be a [`Csg`](struct@Csg) child.
+    fn all_intersections(&self, ray: Ray) -> Vec<HitRecord> {
+        let mut hits: Vec<HitRecord> = self
+            .shapes
+            .iter()
+            .flat_map(|shape| shape.all_intersections(ray))
+            .collect();
+        hits.sort_by(|a, b| a.t.total_cmp(&b.t));
+        hits
+    }
+
+    /// Return the box of all the shapes,
+    /// or [`None`] if any of them is unbounded (or the group is empty).
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        if !self.unbounded.is_empty() {
             return None;
         }
-        let hit_point = inv_ray.at(first_hit_t);
-        Some(HitRecord {
-            world_point: self.transformation * hit_point,
-            normal: self.transformation * sphere_normal(hit_point, inv_ray.dir),
-            surface_point: sphere_point_to_uv(hit_point),
-            t: first_hit_t,
-            ray,
-            material: self.material.clone(),
-        })
+        self.shapes
+            .iter()
+            .filter_map(|shape| shape.bounding_box())
+            .reduce(BoundingBox::union)
     }
 }
 
-/// Geometrical shape corresponding to a plane.
-#[derive(Debug, Default)]
-pub struct Plane {
-    /// A generic plane is defined by means of a [`Transformation`](struct@Transformation)
-    /// on the X-Y plane.\
-    /// A [`scaling`](fn@crate::transformation::scaling) transformation has the
-    /// effect to change the sides length of the basic rectangle in the plane's
-    /// [parametrization](fn@plane_point_to_uv).
+/// Geometrical shape placing a shared shape (usually a [`Group`](struct@Group))
+/// by means of a [`Transformation`](struct@Transformation).
+///
+/// Instances of instances compose their transformations, building a hierarchy.\
+/// Emissive shapes inside an instance are not sampled as
+/// [`AreaLight`](enum@AreaLight)s, they are only hit by chance.
+#[derive(Debug)]
+pub struct Instance {
+    /// The [`Transformation`](struct@Transformation) on the shared shape.
     transformation: Transformation,
-    /// The [`Material`](struct@Material) of the sphere.
-    material: Material,
+    /// The shared shape.
+    shape: Arc<dyn RayIntersection>,
 }
 
-impl Plane {
-    /// Provides a constructor for [`Plane`](struct@Plane).
-    pub fn new(transformation: Transformation, material: Material) -> Self {
-        Plane {
+impl Instance {
+    /// Provides a constructor for [`Instance`](struct@Instance).
+    pub fn new(transformation: Transformation, shape: Arc<dyn RayIntersection>) -> Self {
+        Instance {
             transformation,
-            material,
+            shape,
         }
     }
-}
 
-/// Calculates normals to [`Plane`](struct@Plane)'s surface.
-///
-/// This Function is meant to be used inside [`Plane`](struct@Plane)'s
-/// [`RayIntersection`](trait@RayIntersection) implementation.\
-/// `ray_dir` is the direction of an impacting [`Ray`](struct@Ray)
-/// and is used to determine on which side of the surface the normal is calculated.
-fn plane_normal(ray_dir: Vector) -> Normal {
-    let normal = Normal::from((0., 0., 1.));
-    if Vector::from(normal).dot(ray_dir) < 0.0 {
-        normal
-    } else {
-        normal.neg()
+    /// Bring `hit` of the shared shape, impacted by `ray` in its frame of reference,
+    /// into the instance frame of reference.
+    ///
+    /// The impact time is unchanged, as transformations are linear.
+    fn transform_hit(&self, ray: Ray, hit: HitRecord) -> HitRecord {
+        HitRecord {
+            world_point: self.transformation * hit.world_point,
+            normal: self.transformation * hit.normal,
+            ray,
+            ..hit
+        }
     }
 }
 
-/// Returns parametrization coordinates of a point on a plane.
-///
-/// The plane is parametrized by the `[0,1]x[0,1]` square with periodic boundary conditions.
-fn plane_point_to_uv(point: Point) -> Vector2D {
-    Vector2D {
-        u: point.x - point.x.floor(),
-        v: point.y - point.y.floor(),
+impl RayIntersection for Instance {
+    /// Finds intersections between a [`Ray`](struct@Ray) and an [`Instance`](struct@Instance).
+    fn ray_intersection(&self, ray: Ray) -> Option<HitRecord> {
+        let inv_ray = self.transformation.inverse() * ray;
+        self.shape
+            .ray_intersection(inv_ray)
+            .map(|hit| self.transform_hit(ray, hit))
     }
-}
 
-impl RayIntersection for Plane {
-    /// Finds intersections between a [`Ray`](struct@Ray) and a [`Plane`](struct@Plane).
-    fn ray_intersection(&self, ray: Ray) -> Option<HitRecord> {
+    /// Finds all the intersections between a [`Ray`](struct@Ray)
+    /// and an [`Instance`](struct@Instance).
+    fn all_intersections(&self, ray: Ray) -> Vec<HitRecord> {
         let inv_ray = self.transformation.inverse() * ray;
-        if inv_ray.dir.z.abs() < 1e-5 {
-            return None;
-        }
-        let t = -inv_ray.origin.z / inv_ray.dir.z;
-        if (t <= inv_ray.tmin) || (t >= inv_ray.tmax) {
-            return None;
-        }
-        let hit_point = inv_ray.at(t);
-        Some(HitRecord {
-            world_point: self.transformation * hit_point,
-            normal: self.transformation * plane_normal(inv_ray.dir),
-            surface_point: plane_point_to_uv(hit_point),
-            t,
-            ray,
-            material: self.material.clone(),
-        })
+        self.shape
+            .all_intersections(inv_ray)
+            .into_iter()
+            .map(|hit| self.transform_hit(ray, hit))
+            .collect()
+    }
+
+    /// Return the transformed box of the shared shape.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        self.shape
+            .bounding_box()
+            .map(|bbox| bbox.transform(self.transformation))
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::transformation::{rotation_y, scaling, translation};
+    use crate::{
+        mesh::Face,
+        ray::RayDifferentials,
+        sdf::{RepetitionSdf, SphereSdf},
+        transformation::{rotation_y, rotation_z, scaling, translation},
+    };
 
     #[test]
     fn test_hit_sphere() {
@@ -240,8 +1786,11 @@ mod test {
         assert!(
             matches!(intersection1, Some(intersection) if intersection.is_close(HitRecord {
                 world_point: Point::from((0., 0., 1.)),
+                local_point: Point::default(),
                 normal: Normal::from((0., 0., 1.)),
+                inside: false,
                 surface_point: Vector2D { u: 0., v: 0. },
+                footprint: Footprint::default(),
                 t: 1.,
                 ray: ray1,
                 material: Material::default()
@@ -257,8 +1806,11 @@ mod test {
         assert!(
             matches!(intersection2, Some(intersection) if intersection.is_close(HitRecord {
                 world_point: Point::from((1., 0., 0.)),
+                local_point: Point::default(),
                 normal: Normal::from((1., 0., 0.)),
+                inside: false,
                 surface_point: Vector2D { u: 0., v: 0.5 },
+                footprint: Footprint::default(),
                 t: 2.,
                 ray: ray2,
                 material: Material::default()
@@ -274,8 +1826,11 @@ mod test {
         assert!(
             matches!(intersection3, Some(intersection) if intersection.is_close(HitRecord {
                 world_point: Point::from((1., 0., 0.)),
+                local_point: Point::default(),
                 normal: Normal::from((-1., 0., 0.)),
+                inside: true,
                 surface_point: Vector2D { u: 0., v: 0.5 },
+                footprint: Footprint::default(),
                 t: 1.,
                 ray: ray3,
                 material: Material::default()
@@ -299,8 +1854,11 @@ mod test {
         assert!(
             matches!(intersection1, Some(intersection) if intersection.is_close(HitRecord {
                 world_point: Point::from((10.0, 0.0, 1.0)),
+                local_point: Point::default(),
                 normal: Normal::from((0.0, 0.0, 1.0)),
+                inside: false,
                 surface_point: Vector2D { u: 0., v: 0. },
+                footprint: Footprint::default(),
                 t: 1.0,
                 ray: ray1,
                 material: Material::default()
@@ -315,8 +1873,11 @@ mod test {
         assert!(
             matches!(intersection2, Some(intersection) if intersection.is_close(HitRecord {
                 world_point: Point::from((11.0, 0.0, 0.0)),
+                local_point: Point::default(),
                 normal: Normal::from((1.0, 0.0, 0.0)),
+                inside: false,
                 surface_point: Vector2D { u: 0., v: 0.5 },
+                footprint: Footprint::default(),
                 t: 2.0,
                 ray: ray2,
                 material: Material::default()
@@ -434,6 +1995,18 @@ mod test {
             .is_close(Vector2D { u: 0., v: 2. / 3. }));
     }
 
+    #[test]
+    fn test_bounding_box() {
+        let sphere = Sphere::new(
+            translation(Vector::from((10., 0., 0.))) * scaling(Vector::from((2., 1., 1.))),
+            Material::default(),
+        );
+        assert!(matches!(sphere.bounding_box(), Some(bbox)
+            if bbox.min.is_close(Point::from((8., -1., -1.)))
+            && bbox.max.is_close(Point::from((12., 1., 1.)))));
+        assert!(Plane::default().bounding_box().is_none());
+    }
+
     #[test]
     fn test_hit_plane() {
         let plane = Plane::default();
@@ -446,8 +2019,11 @@ mod test {
         assert!(
             matches!(intersection1, Some(intersection) if intersection.is_close(HitRecord {
                 world_point: Point::default(),
+                local_point: Point::default(),
                 normal: Normal::from((0., 0., 1.)),
+                inside: false,
                 surface_point: Vector2D { u: 0., v: 0. },
+                footprint: Footprint::default(),
                 t: 1.,
                 ray: ray1,
                 material: Material::default()
@@ -488,8 +2064,11 @@ mod test {
         assert!(
             matches!(intersection1, Some(intersection) if intersection.is_close(HitRecord {
                 world_point: Point::default(),
+                local_point: Point::default(),
                 normal: Normal::from((1., 0., 0.)),
+                inside: false,
                 surface_point: Vector2D { u: 0., v: 0. },
+                footprint: Footprint::default(),
                 t: 1.,
                 ray: ray1,
                 material: Material::default()
@@ -566,4 +2145,533 @@ mod test {
             .surface_point
             .is_close(Vector2D { u: 0.75, v: 0.25 }));
     }
+
+    #[test]
+    fn test_surface_footprint() {
+        let differential_ray = |origin: Point, offset: Vector, dir: Vector| Ray {
+            origin,
+            dir,
+            differentials: Some(RayDifferentials {
+                rx_origin: origin + Vector::from((offset.x, 0., 0.)),
+                rx_dir: dir,
+                ry_origin: origin + Vector::from((0., offset.y, offset.z)),
+                ry_dir: dir,
+            }),
+            ..Default::default()
+        };
+
+        let ray = differential_ray(
+            Point::from((0.25, 0.75, 1.)),
+            Vector::from((0.1, 0.2, 0.)),
+            Vector::from((0., 0., -1.)),
+        );
+        let footprint = Plane::default().ray_intersection(ray).unwrap().footprint;
+        assert!(footprint.duv_dx.is_close(Vector2D { u: 0.1, v: 0. }));
+        assert!(footprint.duv_dy.is_close(Vector2D { u: 0., v: 0.2 }));
+        // Footprint is measured on the shape surface, whatever its transformation.
+        let plane = Plane::new(scaling(Vector::from((2., 2., 2.))), Material::default());
+        let footprint = plane.ray_intersection(ray).unwrap().footprint;
+        assert!(footprint.duv_dx.is_close(Vector2D { u: 0.05, v: 0. }));
+        assert!(footprint.duv_dy.is_close(Vector2D { u: 0., v: 0.1 }));
+
+        let ray = differential_ray(
+            Point::from((2., 0., 0.)),
+            Vector::from((0., 0., 0.01)),
+            Vector::from((-1., 0., 0.)),
+        );
+        let footprint = Sphere::default().ray_intersection(ray).unwrap().footprint;
+        assert!(footprint.duv_dx.is_close(Vector2D { u: 0., v: 0. }));
+        assert!(footprint.duv_dy.is_close(Vector2D {
+            u: 0.,
+            v: -0.01 / PI
+        }));
+
+        let ray = Ray {
+            differentials: None,
+            ..ray
+        };
+        assert_eq!(
+            Sphere::default().ray_intersection(ray).unwrap().footprint,
+            Footprint::default()
+        );
+    }
+
+    #[test]
+    fn test_hit_triangle() {
+        let triangle = Triangle::new(
+            translation(Vector::from((0., 0., 1.))) * rotation_z(PI / 2.),
+            Material::default(),
+        );
+        let ray1 = Ray {
+            origin: Point::from((-0.25, 0.5, 2.)),
+            dir: Vector::from((0., 0., -1.)),
+            ..Default::default()
+        };
+        let intersection1 = triangle.ray_intersection(ray1);
+        assert!(
+            matches!(intersection1, Some(intersection) if intersection.is_close(HitRecord {
+                world_point: Point::from((-0.25, 0.5, 1.)),
+                local_point: Point::default(),
+                normal: Normal::from((0., 0., 1.)),
+                inside: false,
+                surface_point: Vector2D { u: 0.5, v: 0.25 },
+                footprint: Footprint::default(),
+                t: 1.,
+                ray: ray1,
+                material: Material::default()
+            }))
+        );
+        let ray2 = Ray {
+            origin: Point::from((-0.5, 0.75, 0.)),
+            dir: Vector::from((0., 0., 1.)),
+            ..Default::default()
+        };
+        assert!(triangle.ray_intersection(ray2).is_none());
+        assert!(matches!(triangle.bounding_box(), Some(bbox)
+            if bbox.min.is_close(Point::from((-1., 0., 1.)))
+            && bbox.max.is_close(Point::from((0., 1., 1.)))));
+    }
+
+    #[test]
+    fn test_hit_triangle_mesh() {
+        // A unit square on the X-Y plane, with tilted vertex normals.
+        let mesh = Arc::new(Mesh {
+            vertices: vec![
+                Point::from((0., 0., 0.)),
+                Point::from((1., 0., 0.)),
+                Point::from((1., 1., 0.)),
+                Point::from((0., 1., 0.)),
+            ],
+            normals: vec![
+                Normal::from((-1., 0., 1.)).normalize(),
+                Normal::from((1., 0., 1.)).normalize(),
+            ],
+            uvs: vec![
+                Vector2D { u: 0., v: 1. },
+                Vector2D { u: 1., v: 1. },
+                Vector2D { u: 1., v: 0. },
+                Vector2D { u: 0., v: 0. },
+            ],
+            faces: vec![
+                Face {
+                    vertices: [0, 1, 2],
+                    normals: Some([0, 1, 1]),
+                    uvs: Some([0, 1, 2]),
+                },
+                Face {
+                    vertices: [0, 2, 3],
+                    normals: Some([0, 1, 0]),
+                    uvs: Some([0, 2, 3]),
+                },
+            ],
+        });
+        let triangle_mesh = TriangleMesh::new(
+            mesh,
+            translation(Vector::from((0., 0., -1.))),
+            Material::default(),
+        );
+        let ray1 = Ray {
+            origin: Point::from((0.5, 0.25, 1.)),
+            dir: Vector::from((0., 0., -1.)),
+            ..Default::default()
+        };
+        let intersection1 = triangle_mesh.ray_intersection(ray1);
+        assert!(
+            matches!(intersection1, Some(intersection) if intersection.is_close(HitRecord {
+                world_point: Point::from((0.5, 0.25, -1.)),
+                local_point: Point::default(),
+                normal: Normal::from((0., 0., 1. / 2_f32.sqrt())),
+                inside: false,
+                surface_point: Vector2D { u: 0.5, v: 0.75 },
+                footprint: Footprint::default(),
+                t: 2.,
+                ray: ray1,
+                material: Material::default()
+            }))
+        );
+        let ray2 = Ray {
+            origin: Point::from((0.25, 0.5, -2.)),
+            dir: Vector::from((0., 0., 1.)),
+            ..Default::default()
+        };
+        let intersection2 = triangle_mesh.ray_intersection(ray2);
+        assert!(matches!(intersection2, Some(intersection)
+            if intersection.world_point.is_close(Point::from((0.25, 0.5, -1.)))
+            && intersection.normal.is_close(Normal::from((0.5, 0., -1.)) * (1. / 2_f32.sqrt()))
+            && intersection.surface_point.is_close(Vector2D { u: 0.25, v: 0.5 })));
+        let ray3 = Ray {
+            origin: Point::from((1.5, 0.5, 1.)),
+            dir: Vector::from((0., 0., -1.)),
+            ..Default::default()
+        };
+        assert!(triangle_mesh.ray_intersection(ray3).is_none());
+        assert!(matches!(triangle_mesh.bounding_box(), Some(bbox)
+            if bbox.min.is_close(Point::from((0., 0., -1.)))
+            && bbox.max.is_close(Point::from((1., 1., -1.)))));
+    }
+
+    #[test]
+    fn test_hit_disk() {
+        let disk = Disk::default();
+        let ray = |x: f32| Ray {
+            origin: Point::from((x, 0., 1.)),
+            dir: Vector::from((0., 0., -1.)),
+            ..Default::default()
+        };
+        let hit = disk.ray_intersection(ray(0.5)).unwrap();
+        assert!(hit.t.is_close(1.));
+        assert!(hit.normal.is_close(Normal::from((0., 0., 1.))));
+        assert!(!hit.inside);
+        assert!(hit.surface_point.is_close(Vector2D { u: 0., v: 0.5 }));
+        assert!(disk.ray_intersection(ray(2.)).is_none());
+        assert_eq!(disk.all_intersections(ray(0.5)).len(), 1);
+    }
+
+    #[test]
+    fn test_hit_cube() {
+        let cube = Cube::default();
+        let ray = Ray {
+            origin: Point::from((-5., 0.5, 0.2)),
+            dir: Vector::from((1., 0., 0.)),
+            ..Default::default()
+        };
+        let hits = cube.all_intersections(ray);
+        assert_eq!(hits.len(), 2);
+        assert!(hits[0].t.is_close(4.) && !hits[0].inside);
+        assert!(hits[0].normal.is_close(Normal::from((-1., 0., 0.))));
+        assert!(hits[0].surface_point.is_close(Vector2D {
+            u: 1.75 / 3.,
+            v: 0.3
+        }));
+        assert!(hits[1].t.is_close(6.) && hits[1].inside);
+        assert!(hits[1].normal.is_close(Normal::from((-1., 0., 0.))));
+
+        let ray = Ray {
+            origin: Point::default(),
+            dir: Vector::from((0., 0., 1.)),
+            ..Default::default()
+        };
+        let hit = cube.ray_intersection(ray).unwrap();
+        assert!(hit.t.is_close(1.) && hit.inside);
+        assert!(hit.surface_point.is_close(Vector2D { u: 0.5, v: 0.75 }));
+
+        let cube = Cube::new(
+            translation(Vector::from((10., 0., 0.))),
+            Material::default(),
+        );
+        let ray = Ray {
+            origin: Point::from((10., 0., 5.)),
+            dir: Vector::from((0., 0., -1.)),
+            ..Default::default()
+        };
+        let hit = cube.ray_intersection(ray).unwrap();
+        assert!(hit.world_point.is_close(Point::from((10., 0., 1.))));
+        assert!(cube
+            .ray_intersection(Ray {
+                origin: Point::from((0., 0., 5.)),
+                ..ray
+            })
+            .is_none());
+    }
+
+    #[test]
+    fn test_hit_cylinder_and_cone() {
+        let hits = |shape: &dyn RayIntersection, origin: (f32, f32, f32), dir: (f32, f32, f32)| {
+            shape
+                .all_intersections(Ray {
+                    origin: Point::from(origin),
+                    dir: Vector::from(dir),
+                    ..Default::default()
+                })
+                .into_iter()
+                .map(|hit| (hit.t, hit.inside, hit.normal, hit.surface_point))
+                .collect::<Vec<(f32, bool, Normal, Vector2D)>>()
+        };
+
+        let cylinder = Cylinder::default();
+        let side = hits(&cylinder, (-5., 0., 0.5), (1., 0., 0.));
+        assert_eq!(side.len(), 2);
+        assert!(side[0].0.is_close(4.) && !side[0].1);
+        assert!(side[0].2.is_close(Normal::from((-1., 0., 0.))));
+        assert!(side[0].3.is_close(Vector2D { u: 0.5, v: 0.5 }));
+        assert!(side[1].0.is_close(6.) && side[1].1);
+        let caps = hits(&cylinder, (0.5, 0., -5.), (0., 0., 1.));
+        assert_eq!(caps.len(), 2);
+        assert!(caps[0].0.is_close(5.) && !caps[0].1);
+        assert!(caps[0].2.is_close(Normal::from((0., 0., -1.))));
+        assert!(caps[0].3.is_close(Vector2D { u: 0., v: 0.5 / 3. }));
+        assert!(caps[1].0.is_close(6.) && caps[1].1);
+        assert!(caps[1].3.is_close(Vector2D {
+            u: 0.,
+            v: 1. - 0.5 / 3.
+        }));
+        assert!(hits(&cylinder, (-5., 0., 2.), (1., 0., 0.)).is_empty());
+
+        let cone = Cone::default();
+        let side = hits(&cone, (-5., 0., 0.5), (1., 0., 0.));
+        assert_eq!(side.len(), 2);
+        assert!(side[0].0.is_close(4.5) && !side[0].1);
+        assert!(side[0].2.is_close(Normal::from((-0.5, 0., 0.5))));
+        assert!(side[0].3.is_close(Vector2D { u: 0.5, v: 0.75 }));
+        assert!(side[1].0.is_close(5.5) && side[1].1);
+        let base = hits(&cone, (0.2, 0., -5.), (0., 0., 1.));
+        assert_eq!(base.len(), 2);
+        assert!(base[0].0.is_close(5.) && !base[0].1);
+        assert!(base[0].2.is_close(Normal::from((0., 0., -1.))));
+        assert!(base[1].0.is_close(5.8) && base[1].1);
+        // The upper nappe of the double cone is not part of the shape.
+        assert!(hits(&cone, (-5., 0., 1.5), (1., 0., 0.)).is_empty());
+    }
+
+    #[test]
+    fn test_hit_torus() {
+        let mut roots = solve_quartic(-10., 35., -50., 24.);
+        roots.sort_by(f64::total_cmp);
+        assert_eq!(roots.len(), 4);
+        for (root, expected) in roots.into_iter().zip([1., 2., 3., 4.]) {
+            assert!((root - expected).abs() < 1e-9);
+        }
+
+        let torus = Torus::default();
+        let ray = Ray {
+            origin: Point::from((-5., 0., 0.)),
+            dir: Vector::from((1., 0., 0.)),
+            ..Default::default()
+        };
+        let hits: Vec<(f32, bool)> = torus
+            .all_intersections(ray)
+            .iter()
+            .map(|hit| (hit.t, hit.inside))
+            .collect();
+        assert_eq!(hits.len(), 4);
+        for ((t, inside), (expected_t, expected_inside)) in
+            hits.into_iter()
+                .zip([(3.75, false), (4.25, true), (5.75, false), (6.25, true)])
+        {
+            assert!(t.is_close(expected_t) && inside == expected_inside);
+        }
+        let hit = torus.ray_intersection(ray).unwrap();
+        assert!(hit.normal.normalize().is_close(Normal::from((-1., 0., 0.))));
+        assert!(hit.surface_point.is_close(Vector2D { u: 0.5, v: 0. }));
+
+        let ray = Ray {
+            origin: Point::from((1., 0., -5.)),
+            dir: Vector::from((0., 0., 1.)),
+            ..Default::default()
+        };
+        let hit = torus.ray_intersection(ray).unwrap();
+        assert!(hit.t.is_close(4.75));
+        assert!(hit.normal.normalize().is_close(Normal::from((0., 0., -1.))));
+        assert!(hit.surface_point.is_close(Vector2D { u: 0., v: 0.75 }));
+        assert!(torus
+            .ray_intersection(Ray {
+                origin: Point::from((0., 0., -5.)),
+                ..ray
+            })
+            .is_none());
+    }
+
+    #[test]
+    fn test_sdf_shape() {
+        // A sphere field matches the analytic sphere.
+        let sdf = SdfShape::new(
+            translation(Vector::from((10., 0., 0.))),
+            Material::default(),
+            Sdf::Sphere(SphereSdf { radius: 1.0 }),
+        );
+        let ray = Ray {
+            origin: Point::from((10., 0., 2.)),
+            dir: Vector::from((0., 0., -1.)),
+            ..Default::default()
+        };
+        let hit = sdf.ray_intersection(ray).unwrap();
+        assert!((hit.t - 1.0).abs() < 1e-3);
+        assert!(!hit.inside);
+        assert!(hit.world_point.is_close(Point::from((10., 0., 1.))));
+        assert!(hit.normal.normalize().is_close(Normal::from((0., 0., 1.))));
+
+        let hits = sdf.all_intersections(ray);
+        assert_eq!(hits.len(), 2);
+        assert!((hits[1].t - 3.0).abs() < 1e-3);
+        assert!(hits[1].inside);
+        assert!(hits[1]
+            .normal
+            .normalize()
+            .is_close(Normal::from((0., 0., 1.))));
+
+        // A ray leaving the surface does not hit it again.
+        let ray = Ray {
+            origin: hit.world_point,
+            dir: Vector::from((0., 0., 1.)),
+            ..Default::default()
+        };
+        assert!(sdf.ray_intersection(ray).is_none());
+        assert!(sdf
+            .ray_intersection(Ray {
+                origin: Point::from((0., 0., 2.)),
+                ..ray
+            })
+            .is_none());
+
+        // A ray starting inside leaves the surface.
+        let ray = Ray {
+            origin: Point::from((10., 0., 0.)),
+            dir: Vector::from((2., 0., 0.)),
+            ..Default::default()
+        };
+        let hit = sdf.ray_intersection(ray).unwrap();
+        assert!((hit.t - 0.5).abs() < 1e-3);
+        assert!(hit.inside);
+
+        // Unbounded fields are marched anyway.
+        let grid = SdfShape::new(
+            Transformation::default(),
+            Material::default(),
+            Sdf::Repetition(RepetitionSdf::new(
+                Sdf::Sphere(SphereSdf { radius: 0.5 }),
+                Vector::from((2., 2., 0.)),
+            )),
+        );
+        assert!(grid.bounding_box().is_none());
+        let hit = grid
+            .ray_intersection(Ray {
+                origin: Point::from((20., 40., 5.)),
+                dir: Vector::from((0., 0., -1.)),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!((hit.t - 4.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_group_instance() {
+        let group: Arc<dyn RayIntersection> = Arc::new(Group::new(vec![
+            Arc::new(Sphere::new(
+                translation(Vector::from((0., -2., 0.))),
+                Material::default(),
+            )),
+            Arc::new(Sphere::new(
+                translation(Vector::from((0., 2., 0.))),
+                Material::default(),
+            )),
+        ]));
+        let ray = |y: f32| Ray {
+            origin: Point::from((-5., y, 0.)),
+            dir: Vector::from((1., 0., 0.)),
+            ..Default::default()
+        };
+        assert!(group.ray_intersection(ray(0.)).is_none());
+        assert!(group.ray_intersection(ray(2.)).unwrap().t.is_close(4.));
+        assert_eq!(group.all_intersections(ray(-2.)).len(), 2);
+        assert_eq!(
+            group.bounding_box(),
+            Some(BoundingBox::new(
+                Point::from((-1., -3., -1.)),
+                Point::from((1., 3., 1.))
+            ))
+        );
+
+        // Nested instances compose their transformations.
+        let instance: Arc<dyn RayIntersection> = Arc::new(Instance::new(
+            translation(Vector::from((0., 0., 10.))),
+            group.clone(),
+        ));
+        let nested = Instance::new(rotation_z(f32::to_radians(90.)), instance);
+        let ray = Ray {
+            origin: Point::from((2., 0., 20.)),
+            dir: Vector::from((0., 0., -1.)),
+            ..Default::default()
+        };
+        let hit = nested.ray_intersection(ray).unwrap();
+        assert!(hit.t.is_close(9.));
+        assert!(hit.world_point.is_close(Point::from((2., 0., 11.))));
+        assert!(hit.normal.is_close(Normal::from((0., 0., 1.))));
+        assert!(hit.ray.is_close(ray));
+        let hits = nested.all_intersections(ray);
+        assert_eq!(hits.len(), 2);
+        assert!(hits[1].world_point.is_close(Point::from((2., 0., 9.))));
+        assert!(nested
+            .ray_intersection(Ray {
+                origin: Point::from((0., 2., 20.)),
+                ..ray
+            })
+            .is_none());
+        assert_eq!(Arc::strong_count(&group), 2);
+    }
+
+    #[test]
+    fn test_csg() {
+        let sphere = |x: f32| -> Arc<dyn RayIntersection> {
+            Arc::new(Sphere::new(
+                translation(Vector::from((x, 0., 0.))),
+                Material::default(),
+            ))
+        };
+        let csg = |operation| Csg::new(operation, sphere(-0.5), sphere(0.5));
+        let ray = Ray {
+            origin: Point::from((-5., 0., 0.)),
+            dir: Vector::from((1., 0., 0.)),
+            ..Default::default()
+        };
+        let hits = |shape: &Csg, ray: Ray| {
+            shape
+                .all_intersections(ray)
+                .iter()
+                .map(|hit| (hit.t, hit.inside))
+                .collect::<Vec<(f32, bool)>>()
+        };
+
+        let union = csg(CsgOperation::Union);
+        assert_eq!(hits(&union, ray), vec![(3.5, false), (6.5, true)]);
+        let intersection = csg(CsgOperation::Intersection);
+        assert_eq!(hits(&intersection, ray), vec![(4.5, false), (5.5, true)]);
+        let difference = csg(CsgOperation::Difference);
+        assert_eq!(hits(&difference, ray), vec![(3.5, false), (4.5, true)]);
+        let hit = difference
+            .all_intersections(ray)
+            .into_iter()
+            .last()
+            .unwrap();
+        assert!(hit.world_point.is_close(Point::from((-0.5, 0., 0.))));
+        assert!(hit.normal.is_close(Normal::from((-1., 0., 0.))));
+        assert!(hit.ray.is_close(ray));
+
+        // Rays starting inside a child, or limited by `tmax`.
+        let inner_ray = Ray {
+            origin: Point::default(),
+            ..ray
+        };
+        assert_eq!(hits(&intersection, inner_ray), vec![(0.5, true)]);
+        assert!(intersection
+            .ray_intersection(inner_ray)
+            .unwrap()
+            .world_point
+            .is_close(Point::from((0.5, 0., 0.))));
+        assert!(union.ray_intersection(Ray { tmax: 3., ..ray }).is_none());
+
+        // Half-spaces below planes can be combined too.
+        let hemisphere = Csg::new(
+            CsgOperation::Difference,
+            sphere(0.),
+            Arc::new(Plane::default()),
+        );
+        let ray = Ray {
+            origin: Point::from((0., 0., -5.)),
+            dir: Vector::from((0., 0., 1.)),
+            ..Default::default()
+        };
+        assert_eq!(hits(&hemisphere, ray), vec![(5., false), (6., true)]);
+
+        assert!(union.bounding_box().is_some());
+        assert!(hemisphere
+            .bounding_box()
+            .unwrap()
+            .max
+            .is_close(Point::from((1., 1., 1.))));
+        assert!(
+            Csg::new(CsgOperation::Union, sphere(0.), Arc::new(Plane::default()))
+                .bounding_box()
+                .is_none()
+        );
+    }
 }
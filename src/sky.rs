@@ -0,0 +1,209 @@
+//! Sky module.
+//!
+//! Provides [`Sky`](struct@Sky) struct, an analytic daylight sky model
+//! used by [`SkyLight`](../light/struct.SkyLight.html).
+use crate::{
+    color::{Color, BLACK},
+    vector::Vector,
+};
+use std::f32::consts::PI;
+
+/// Illuminance of the sun outside the atmosphere (klx),
+/// in the same units (kcd/m²) of the sky luminance.
+const SOLAR_ILLUMINANCE: f32 = 128.0;
+/// Representative wavelengths (μm) of the red, green and blue channels.
+const WAVELENGTHS: [f32; 3] = [0.680, 0.550, 0.440];
+/// Wavelength exponent of the aerosol optical depth (Ångström).
+const AEROSOL_ALPHA: f32 = 1.3;
+
+/// Coefficients of the Perez distribution of luminance `Y` and chromaticities `x`, `y`,
+/// each one as a linear function `[slope, intercept]` of turbidity.
+const PEREZ: [[[f32; 2]; 5]; 3] = [
+    [
+        [0.1787, -1.4630],
+        [-0.3554, 0.4275],
+        [-0.0227, 5.3251],
+        [0.1206, -2.5771],
+        [-0.0670, 0.3703],
+    ],
+    [
+        [-0.0193, -0.2592],
+        [-0.0665, 0.0008],
+        [-0.0004, 0.2125],
+        [-0.0641, -0.8989],
+        [-0.0033, 0.0452],
+    ],
+    [
+        [-0.0167, -0.2608],
+        [-0.0950, 0.0092],
+        [-0.0079, 0.2102],
+        [-0.0441, -1.6537],
+        [-0.0109, 0.0529],
+    ],
+];
+
+/// Coefficients of the zenith chromaticities `x` and `y`, as polynomials
+/// of the sun zenith angle (`[θ³, θ², θ, 1]`) for each power of turbidity (`[T², T, 1]`).
+const ZENITH_CHROMATICITY: [[[f32; 4]; 3]; 2] = [
+    [
+        [0.00166, -0.00375, 0.00209, 0.0],
+        [-0.02903, 0.06377, -0.03202, 0.00394],
+        [0.11693, -0.21196, 0.06052, 0.25886],
+    ],
+    [
+        [0.00275, -0.00610, 0.00317, 0.0],
+        [-0.04214, 0.08970, -0.04153, 0.00516],
+        [0.15346, -0.26756, 0.06670, 0.26688],
+    ],
+];
+
+/// The [Preetham](https://doi.org/10.1145/311535.311545) analytic model
+/// of a clear daylight sky (Preetham, Shirley and Smits, 1999).
+///
+/// The sky radiance is a function of the sun direction and of the turbidity,
+/// the haziness of the atmosphere: from `2` (very clear) to `10` (hazy).\
+/// Luminance is in kcd/m², with the `+z` axis pointing to the zenith.
+/// There is no sky below the horizon.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sky {
+    /// Normalized direction towards the sun.
+    sun_direction: Vector,
+    /// Turbidity of the atmosphere.
+    turbidity: f32,
+    /// Perez distribution coefficients `[A, B, C, D, E]` of `Y`, `x` and `y`.
+    perez: [[f32; 5]; 3],
+    /// Zenith values of `Y`, `x` and `y`,
+    /// already divided by the Perez distribution at the zenith.
+    zenith: [f32; 3],
+}
+
+impl Sky {
+    /// Create a new [`Sky`] lit by the sun in `sun_direction`,
+    /// with an atmosphere of `turbidity`.
+    ///
+    /// A sun below the horizon is clamped to the horizon.
+    pub fn new(sun_direction: Vector, turbidity: f32) -> Self {
+        let sun_direction = sun_direction.normalize();
+        let theta_s = sun_direction.z.clamp(0.0, 1.0).acos();
+        let perez = PEREZ.map(|coefficients| {
+            coefficients.map(|[slope, intercept]| slope * turbidity + intercept)
+        });
+        let chi = (4.0 / 9.0 - turbidity / 120.0) * (PI - 2.0 * theta_s);
+        let luminance = (4.0453 * turbidity - 4.9710) * chi.tan() - 0.2155 * turbidity + 2.4192;
+        let angles = [theta_s.powi(3), theta_s.powi(2), theta_s, 1.0];
+        let turbidities = [turbidity * turbidity, turbidity, 1.0];
+        let [x, y] = ZENITH_CHROMATICITY.map(|rows| {
+            rows.iter()
+                .zip(turbidities)
+                .map(|(row, t)| t * row.iter().zip(angles).map(|(c, a)| c * a).sum::<f32>())
+                .sum()
+        });
+        let mut zenith = [luminance, x, y];
+        for (value, coefficients) in zenith.iter_mut().zip(perez) {
+            *value /= perez_distribution(coefficients, 1.0, theta_s);
+        }
+        Self {
+            sun_direction,
+            turbidity,
+            perez,
+            zenith,
+        }
+    }
+
+    /// Return the normalized direction towards the sun.
+    pub fn sun_direction(&self) -> Vector {
+        self.sun_direction
+    }
+
+    /// Return the sky radiance coming from direction `dir`,
+    /// [`BLACK`] below the horizon.
+    pub fn radiance(&self, dir: Vector) -> Color {
+        let dir = dir.normalize();
+        if dir.z <= 0.0 {
+            return BLACK;
+        }
+        let gamma = dir.dot(self.sun_direction).clamp(-1.0, 1.0).acos();
+        let [luminance, x, y] = [0, 1, 2]
+            .map(|i| self.zenith[i] * perez_distribution(self.perez[i], dir.z.max(1e-3), gamma));
+        xyy_to_rgb(x, y, luminance)
+    }
+
+    /// Return the illuminance of the sun (klx) on a surface facing it,
+    /// attenuated by Rayleigh and aerosol scattering along its optical path.
+    ///
+    /// Ozone and water vapour absorption are neglected.
+    pub fn sun_illuminance(&self) -> Color {
+        let cos_theta = self.sun_direction.z;
+        if cos_theta <= 0.0 {
+            return BLACK;
+        }
+        // Relative optical mass (Kasten, 1966).
+        let theta_deg = cos_theta.acos().to_degrees();
+        let mass = 1.0 / (cos_theta + 0.15 * (93.885 - theta_deg).powf(-1.253));
+        let beta = 0.04608 * self.turbidity - 0.04586;
+        let [r, g, b] = WAVELENGTHS.map(|lambda| {
+            let rayleigh = 0.008735 * lambda.powf(-4.08);
+            let aerosol = beta * lambda.powf(-AEROSOL_ALPHA);
+            SOLAR_ILLUMINANCE * f32::exp(-(rayleigh + aerosol) * mass)
+        });
+        Color::from((r, g, b))
+    }
+}
+
+/// Perez sky luminance distribution, as function of the cosine of the zenith angle
+/// of a direction and of its angle `gamma` from the sun.
+fn perez_distribution([a, b, c, d, e]: [f32; 5], cos_theta: f32, gamma: f32) -> f32 {
+    let cos_gamma = gamma.cos();
+    (1.0 + a * f32::exp(b / cos_theta))
+        * (1.0 + c * f32::exp(d * gamma) + e * cos_gamma * cos_gamma)
+}
+
+/// Convert a color from CIE xyY to linear sRGB color space,
+/// clamping the negative (out of gamut) components.
+fn xyy_to_rgb(x: f32, y: f32, luminance: f32) -> Color {
+    if y <= 0.0 {
+        return BLACK;
+    }
+    let (cx, cz) = (x / y * luminance, (1.0 - x - y) / y * luminance);
+    Color::from((
+        (3.2406 * cx - 1.5372 * luminance - 0.4986 * cz).max(0.0),
+        (-0.9689 * cx + 1.8758 * luminance + 0.0415 * cz).max(0.0),
+        (0.0557 * cx - 0.2040 * luminance + 1.0570 * cz).max(0.0),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::misc::IsClose;
+
+    #[test]
+    fn test_sky() {
+        let sun = Vector::from((1.0, 0.0, 1.0));
+        let sky = Sky::new(sun, 3.0);
+        assert!(sky.sun_direction().is_close(sun.normalize()));
+        // The zenith luminance of the model is recovered.
+        let zenith = sky.radiance(Vector::from((0.0, 0.0, 2.0)));
+        let luminance = 0.2126 * zenith.r + 0.7152 * zenith.g + 0.0722 * zenith.b;
+        let chi = (4.0 / 9.0 - 3.0 / 120.0) * (PI / 2.0);
+        let expected = (4.0453 * 3.0 - 4.9710) * chi.tan() - 0.2155 * 3.0 + 2.4192;
+        assert!((luminance / expected - 1.0).abs() < 1e-2);
+        // A clear sky is blue, brighter around the sun, and absent below the horizon.
+        assert!(zenith.b > zenith.r);
+        let around_sun = sky.radiance(Vector::from((1.0, 0.1, 1.0)));
+        let opposite = sky.radiance(Vector::from((-1.0, 0.1, 1.0)));
+        assert!(around_sun.luminosity() > opposite.luminosity());
+        assert!(sky.radiance(Vector::from((1.0, 0.0, -0.1))).is_close(BLACK));
+
+        // The sunlight is reddened and dimmed when low on the horizon or through haze.
+        let noon = Sky::new(Vector::from((0.0, 0.0, 1.0)), 3.0).sun_illuminance();
+        let sunset = Sky::new(Vector::from((1.0, 0.0, 0.05)), 3.0).sun_illuminance();
+        let hazy = Sky::new(Vector::from((0.0, 0.0, 1.0)), 8.0).sun_illuminance();
+        assert!(noon.r < SOLAR_ILLUMINANCE && noon.g > 0.5 * SOLAR_ILLUMINANCE);
+        assert!(sunset.r / sunset.b > noon.r / noon.b);
+        assert!(hazy.luminosity() < noon.luminosity());
+        assert!(Sky::new(Vector::from((1.0, 0.0, -0.1)), 3.0)
+            .sun_illuminance()
+            .is_close(BLACK));
+    }
+}
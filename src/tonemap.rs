@@ -0,0 +1,199 @@
+//! Tone Mapping module.
+//!
+//! Provides [`ToneMap`](enum@ToneMap) enum
+//! and the operators that map normalized HDR color components into `[0, 1]`.
+use crate::color::Color;
+
+/// Uncharted 2 filmic curve shoulder strength.
+const HABLE_A: f32 = 0.15;
+/// Uncharted 2 filmic curve linear strength.
+const HABLE_B: f32 = 0.50;
+/// Uncharted 2 filmic curve linear angle.
+const HABLE_C: f32 = 0.10;
+/// Uncharted 2 filmic curve toe strength.
+const HABLE_D: f32 = 0.20;
+/// Uncharted 2 filmic curve toe numerator.
+const HABLE_E: f32 = 0.02;
+/// Uncharted 2 filmic curve toe denominator.
+const HABLE_F: f32 = 0.30;
+/// Uncharted 2 filmic curve linear white point.
+const HABLE_WHITE: f32 = 11.2;
+/// Uncharted 2 filmic curve exposure bias.
+const HABLE_EXPOSURE_BIAS: f32 = 2.0;
+
+/// A trait for tone mapping operators.
+///
+/// Operators are applied to each color component of an image already
+/// normalized with [`normalize_image`](../hdrimage/struct.HdrImage.html#method.normalize_image).
+pub trait ToneMapping {
+    /// Map a normalized color component `x` into `[0, 1]`.
+    fn tone_map(&self, x: f32) -> f32;
+
+    /// Map each component of a normalized [`Color`] into `[0, 1]`.
+    fn tone_map_color(&self, color: Color) -> Color {
+        Color::from((
+            self.tone_map(color.r),
+            self.tone_map(color.g),
+            self.tone_map(color.b),
+        ))
+    }
+}
+
+/// Reinhard tone mapping, `x / (1 + x)`.
+///
+/// Historical default, same curve of
+/// [`clamp_image`](../hdrimage/struct.HdrImage.html#method.clamp_image).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReinhardToneMap;
+
+impl ToneMapping for ReinhardToneMap {
+    fn tone_map(&self, x: f32) -> f32 {
+        x / (1.0 + x)
+    }
+}
+
+/// Extended Reinhard tone mapping with white point.
+///
+/// Components equal to `white` are mapped to one,
+/// so highlights up to the white point do not blow out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExtendedReinhardToneMap {
+    /// Smallest normalized component mapped to pure white.
+    pub white: f32,
+}
+
+impl ExtendedReinhardToneMap {
+    /// Provides a constructor for [`ExtendedReinhardToneMap`](struct@ExtendedReinhardToneMap).
+    pub fn new(white: f32) -> Self {
+        Self { white }
+    }
+}
+
+impl ToneMapping for ExtendedReinhardToneMap {
+    fn tone_map(&self, x: f32) -> f32 {
+        // Avoid `0 / 0` when the white point is zero (e.g. a black image).
+        if x <= 0.0 {
+            return 0.0;
+        }
+        (x * (1.0 + x / (self.white * self.white)) / (1.0 + x)).clamp(0.0, 1.0)
+    }
+}
+
+/// ACES filmic tone mapping.
+///
+/// Fitted curve by [Krzysztof Narkowicz](https://knarkowicz.wordpress.com/2016/01/06/aces-filmic-tone-mapping-curve/).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AcesToneMap;
+
+impl ToneMapping for AcesToneMap {
+    fn tone_map(&self, x: f32) -> f32 {
+        (x * (2.51 * x + 0.03) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0.0, 1.0)
+    }
+}
+
+/// Hable (Uncharted 2) filmic tone mapping.
+///
+/// See [John Hable](http://filmicworlds.com/blog/filmic-tonemapping-operators/)
+/// filmic curve, with a linear white point of `11.2`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HableToneMap;
+
+impl HableToneMap {
+    /// Uncharted 2 filmic curve, before white point scaling.
+    fn curve(x: f32) -> f32 {
+        (x * (HABLE_A * x + HABLE_C * HABLE_B) + HABLE_D * HABLE_E)
+            / (x * (HABLE_A * x + HABLE_B) + HABLE_D * HABLE_F)
+            - HABLE_E / HABLE_F
+    }
+}
+
+impl ToneMapping for HableToneMap {
+    fn tone_map(&self, x: f32) -> f32 {
+        (HableToneMap::curve(x * HABLE_EXPOSURE_BIAS) / HableToneMap::curve(HABLE_WHITE))
+            .clamp(0.0, 1.0)
+    }
+}
+
+/// Plain exposure and clip tone mapping.
+///
+/// Normalized components are only clipped into `[0, 1]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ClipToneMap;
+
+impl ToneMapping for ClipToneMap {
+    fn tone_map(&self, x: f32) -> f32 {
+        x.clamp(0.0, 1.0)
+    }
+}
+
+/// Enum of tone mapping operators.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToneMap {
+    Reinhard(ReinhardToneMap),
+    ExtendedReinhard(ExtendedReinhardToneMap),
+    Aces(AcesToneMap),
+    Hable(HableToneMap),
+    Clip(ClipToneMap),
+}
+
+impl Default for ToneMap {
+    /// Default tone mapping operator is [`ReinhardToneMap`](struct@ReinhardToneMap).
+    fn default() -> Self {
+        ToneMap::Reinhard(ReinhardToneMap)
+    }
+}
+
+impl ToneMapping for ToneMap {
+    /// Map a color component using a particular [`ToneMap`] variant.
+    fn tone_map(&self, x: f32) -> f32 {
+        match self {
+            ToneMap::Reinhard(reinhard) => reinhard.tone_map(x),
+            ToneMap::ExtendedReinhard(extended) => extended.tone_map(x),
+            ToneMap::Aces(aces) => aces.tone_map(x),
+            ToneMap::Hable(hable) => hable.tone_map(x),
+            ToneMap::Clip(clip) => clip.tone_map(x),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::misc::IsClose;
+
+    #[test]
+    fn test_tone_map() {
+        let operators = [
+            ToneMap::default(),
+            ToneMap::ExtendedReinhard(ExtendedReinhardToneMap::new(4.0)),
+            ToneMap::Aces(AcesToneMap),
+            ToneMap::Hable(HableToneMap),
+            ToneMap::Clip(ClipToneMap),
+        ];
+        for operator in operators {
+            assert!(operator.tone_map(0.0).is_close(0.0));
+            let mut previous = 0.0;
+            for x in [0.01, 0.1, 0.5, 1.0, 2.0, 10.0, 1000.0] {
+                let mapped = operator.tone_map(x);
+                assert!((0.0..=1.0).contains(&mapped));
+                assert!(mapped >= previous);
+                previous = mapped;
+            }
+        }
+
+        assert!(ReinhardToneMap.tone_map(1.0).is_close(0.5));
+        assert!(ExtendedReinhardToneMap::new(4.0)
+            .tone_map(4.0)
+            .is_close(1.0));
+        assert!(ExtendedReinhardToneMap::new(4.0).tone_map(1.0) > ReinhardToneMap.tone_map(1.0));
+        assert!(AcesToneMap.tone_map(1000.0).is_close(1.0));
+        assert!(HableToneMap
+            .tone_map(HABLE_WHITE / HABLE_EXPOSURE_BIAS)
+            .is_close(1.0));
+        assert!(ClipToneMap.tone_map(0.3).is_close(0.3));
+        assert!(ClipToneMap.tone_map(3.0).is_close(1.0));
+        assert!(ToneMap::Clip(ClipToneMap)
+            .tone_map_color(Color::from((0.5, 2.0, -1.0)))
+            .is_close(Color::from((0.5, 1.0, 0.0))));
+    }
+}
@@ -1,7 +1,13 @@
 //! 3D Homogeneous Transformation module.
 //!
 //! Provides [`Matrix`](struct@Matrix) and [`Transformation`](struct@Transformation) struct.
-use crate::{misc::IsClose, normal::Normal, point::Point, ray::Ray, vector::Vector};
+use crate::{
+    misc::IsClose,
+    normal::Normal,
+    point::Point,
+    ray::{Ray, RayDifferentials},
+    vector::Vector,
+};
 use std::ops::Mul;
 
 /// 4D Identity matrix.
@@ -204,6 +210,12 @@ impl Mul<Ray> for Transformation {
             tmin: ray.tmin,
             tmax: ray.tmax,
             depth: ray.depth,
+            differentials: ray.differentials.map(|diff| RayDifferentials {
+                rx_origin: self * diff.rx_origin,
+                rx_dir: self * diff.rx_dir,
+                ry_origin: self * diff.ry_origin,
+                ry_dir: self * diff.ry_dir,
+            }),
         }
     }
 }
@@ -2,6 +2,9 @@
 //!
 //! Provides [`World`](struct@World) struct.
 use crate::{
+    bvh::Bvh,
+    light::{AreaLight, Environment, PointLight},
+    point::Point,
     ray::Ray,
     shape::{HitRecord, RayIntersection},
 };
@@ -12,41 +15,158 @@ use crate::{
 /// You can add shapes to a world using [`add`](#method.add).\
 /// Typically, you call [`ray_intersection`](#method.ray_intersection) to check whether
 /// a light ray intersects any of the shapes in the world.
+///
+/// Once all shapes are added, call [`build_bvh`](#method.build_bvh) to speed up
+/// [`ray_intersection`](#method.ray_intersection) with a [`Bvh`](struct@Bvh).
 #[derive(Debug, Default)]
 pub struct World {
     /// A [`std::vec::Vec`] of [`std::boxed::Box`]-ed [`shapes`](../shape)
     /// that implement [`RayIntersection`] trait
     /// ([vector of traits](https://doc.rust-lang.org/stable/book/ch17-02-trait-objects.html)).
     shapes: Vec<Box<dyn RayIntersection>>,
+    /// A [`Bvh`](struct@Bvh) over the bounded shapes,
+    /// [`None`] until [`build_bvh`](#method.build_bvh) is called.
+    bvh: Option<Bvh>,
+    /// Indices of the unbounded shapes (e.g. [`Plane`](../shape/struct.Plane.html)),
+    /// always checked one by one.
+    unbounded: Vec<usize>,
+    /// A [`std::vec::Vec`] of [`PointLight`](struct@PointLight) sources.
+    lights: Vec<PointLight>,
+    /// Indices of the emissive shapes that can be sampled,
+    /// together with their [`AreaLight`](enum@AreaLight) geometry.
+    emitters: Vec<(usize, AreaLight)>,
+    /// An optional [`Environment`](enum@Environment) light source,
+    /// seen by the rays that miss every shape.
+    environment: Option<Environment>,
 }
 
 impl World {
     /// Append a new boxed shape to this [`World`].
+    ///
+    /// A previously built [`Bvh`](struct@Bvh) is dropped,
+    /// call again [`build_bvh`](#method.build_bvh) to rebuild it.
     pub fn add(&mut self, shape: Box<dyn RayIntersection>) {
+        if let Some(area_light) = shape.area_light() {
+            self.emitters.push((self.shapes.len(), area_light));
+        }
         self.shapes.push(shape);
+        self.bvh = None;
+        self.unbounded.clear();
+    }
+
+    /// Append a new point light source to this [`World`].
+    pub fn add_light(&mut self, light: PointLight) {
+        self.lights.push(light);
+    }
+
+    /// Return the point light sources of this [`World`].
+    pub fn lights(&self) -> &[PointLight] {
+        &self.lights
+    }
+
+    /// Set the environment light source of this [`World`].
+    pub fn set_environment(&mut self, environment: Environment) {
+        self.environment = Some(environment);
+    }
+
+    /// Return the environment light source of this [`World`], if any.
+    pub fn environment(&self) -> Option<&Environment> {
+        self.environment.as_ref()
+    }
+
+    /// Return the emissive shapes of this [`World`] that can be sampled,
+    /// as `(shape index, area light)` pairs.
+    pub fn emitters(&self) -> &[(usize, AreaLight)] {
+        &self.emitters
+    }
+
+    /// Build a [`Bvh`](struct@Bvh) over the shapes of this [`World`].
+    ///
+    /// Shapes without a [`bounding_box`](../shape/trait.RayIntersection.html#tymethod.bounding_box)
+    /// are kept outside the hierarchy.
+    pub fn build_bvh(&mut self) {
+        let mut primitives = Vec::new();
+        self.unbounded.clear();
+        for (index, shape) in self.shapes.iter().enumerate() {
+            match shape.bounding_box() {
+                Some(bbox) => primitives.push((index, bbox)),
+                None => self.unbounded.push(index),
+            }
+        }
+        self.bvh = Some(Bvh::new(primitives));
     }
 
     /// Determine whether a ray intersects any of the objects in this [`World`].
     ///
     /// Return [`HitRecord`] wrapped inside [`std::option::Option`].
     pub fn ray_intersection(&self, ray: Ray) -> Option<HitRecord> {
-        let mut closest: Option<HitRecord> = None;
-        for shape in self.shapes.iter() {
-            let old_closest = closest;
-            closest = match (old_closest, shape.ray_intersection(ray)) {
-                (Some(closest_hit), Some(shape_hit)) => {
-                    if shape_hit.t < closest_hit.t {
-                        Some(shape_hit)
-                    } else {
-                        Some(closest_hit)
+        self.shape_intersection(ray).map(|(_, hit)| hit)
+    }
+
+    /// Same as [`ray_intersection`](#method.ray_intersection),
+    /// but return also the index of the impacted shape.
+    pub fn shape_intersection(&self, ray: Ray) -> Option<(usize, HitRecord)> {
+        let intersect = |index: usize| {
+            self.shapes[index]
+                .ray_intersection(ray)
+                .map(|hit| (index, hit))
+        };
+        match &self.bvh {
+            Some(bvh) => {
+                // The bvh keeps the closest hit, remember which shape it belongs to.
+                let mut closest_index = None;
+                let closest = bvh.ray_intersection(ray, |index| {
+                    let (index, hit) = intersect(index)?;
+                    if closest_index.is_none_or(|(_, t)| hit.t < t) {
+                        closest_index = Some((index, hit.t));
                     }
-                },
-                (None, Some(shape_hit)) => Some(shape_hit),
-                (Some(closest_hit), None) => Some(closest_hit),
-                _ => None,
-            }
+                    Some(hit)
+                });
+                let closest = closest
+                    .zip(closest_index)
+                    .map(|(hit, (index, _))| (index, hit));
+                self.unbounded.iter().fold(closest, |closest, index| {
+                    closest_hit(closest, intersect(*index))
+                })
+            },
+            None => (0..self.shapes.len()).fold(None, |closest, index| {
+                closest_hit(closest, intersect(index))
+            }),
         }
-        closest
+    }
+
+    /// Determine whether `point` is visible from `observer_pos`,
+    /// i.e. no shape lies between them.
+    pub fn is_point_visible(&self, point: Point, observer_pos: Point) -> bool {
+        let direction = point - observer_pos;
+        let dir_norm = direction.norm();
+        let ray = Ray {
+            origin: observer_pos,
+            dir: direction,
+            tmin: 1e-2 / dir_norm,
+            tmax: 1.0,
+            ..Default::default()
+        };
+        self.ray_intersection(ray).is_none()
+    }
+}
+
+/// Return the closest between two `(shape index, HitRecord)` wrapped inside [`std::option::Option`].
+fn closest_hit(
+    closest: Option<(usize, HitRecord)>,
+    shape: Option<(usize, HitRecord)>,
+) -> Option<(usize, HitRecord)> {
+    match (closest, shape) {
+        (Some(closest_hit), Some(shape_hit)) => {
+            if shape_hit.1.t < closest_hit.1.t {
+                Some(shape_hit)
+            } else {
+                Some(closest_hit)
+            }
+        },
+        (None, Some(shape_hit)) => Some(shape_hit),
+        (Some(closest_hit), None) => Some(closest_hit),
+        _ => None,
     }
 }
 
@@ -54,8 +174,9 @@ impl World {
 mod test {
     use super::*;
     use crate::{
-        material::GetColor, misc::IsClose, point::Point, scaling, translation, vector::E1,
-        DiffuseBRDF, Material, Pigment, Sphere, UniformPigment, Vector, BLACK, BRDF, WHITE,
+        light::SphereLight, material::GetColor, misc::IsClose, point::Point, random::Pcg, scaling,
+        translation, vector::E1, DiffuseBRDF, Material, Pigment, Plane, Sphere, UniformPigment,
+        Vector, BLACK, BRDF, WHITE,
     };
 
     #[test]
@@ -88,6 +209,94 @@ mod test {
         );
         assert!(
             matches!(world.ray_intersection(ray3), Some(hit) if hit.material.emitted_radiance.get_color(hit.surface_point).is_close(WHITE))
-        )
+        );
+
+        assert!(matches!(world.shape_intersection(ray2), Some((0, _))));
+        assert!(matches!(world.shape_intersection(ray3), Some((1, _))));
+        world.build_bvh();
+        assert!(matches!(world.shape_intersection(ray2), Some((0, _))));
+        assert!(matches!(world.shape_intersection(ray3), Some((1, _))));
+        assert_eq!(
+            world.emitters(),
+            [(
+                1,
+                AreaLight::Sphere(SphereLight::new(Point::from((4., 0., 0.)), 2.))
+            )]
+        );
+    }
+
+    #[test]
+    fn test_bvh() {
+        let mut pcg = Pcg::default();
+        let mut world = World::default();
+        for _ in 0..100 {
+            let center = Vector::from((
+                pcg.random_float() * 20. - 10.,
+                pcg.random_float() * 20. - 10.,
+                pcg.random_float() * 20. - 10.,
+            ));
+            let radius = pcg.random_float() + 0.1;
+            world.add(Box::new(Sphere::new(
+                translation(center) * scaling(Vector::from((radius, radius, radius))),
+                Material::default(),
+            )));
+        }
+        world.add(Box::new(Plane::new(
+            translation(Vector::from((0., 0., -8.))),
+            Material::default(),
+        )));
+        let rays: Vec<Ray> = (0..1000)
+            .map(|_| Ray {
+                origin: Point::from((
+                    pcg.random_float() * 30. - 15.,
+                    pcg.random_float() * 30. - 15.,
+                    pcg.random_float() * 30. - 15.,
+                )),
+                dir: Vector::from((
+                    pcg.random_float() - 0.5,
+                    pcg.random_float() - 0.5,
+                    pcg.random_float() - 0.5,
+                )),
+                ..Default::default()
+            })
+            .collect();
+        let linear: Vec<Option<HitRecord>> = rays
+            .iter()
+            .map(|ray| world.ray_intersection(*ray))
+            .collect();
+        world.build_bvh();
+        let mut hits = 0;
+        for (ray, linear_hit) in rays.iter().zip(linear) {
+            match (world.ray_intersection(*ray), linear_hit) {
+                (Some(bvh_hit), Some(linear_hit)) => {
+                    assert!(bvh_hit.is_close(linear_hit));
+                    hits += 1
+                },
+                (None, None) => (),
+                _ => panic!("bvh and linear scan disagree on {ray:?}"),
+            }
+        }
+        assert!(hits > 0);
+    }
+
+    #[test]
+    fn test_is_point_visible() {
+        let mut world = World::default();
+        world.add(Box::new(Sphere::new(
+            translation(E1 * 2.),
+            Material::default(),
+        )));
+        world.add(Box::new(Sphere::new(
+            translation(E1 * 8.),
+            Material::default(),
+        )));
+        world.build_bvh();
+
+        assert!(!world.is_point_visible(Point::from((10., 0., 0.)), Point::default()));
+        assert!(!world.is_point_visible(Point::from((5., 0., 0.)), Point::default()));
+        assert!(world.is_point_visible(Point::from((5., 0., 0.)), Point::from((4., 0., 0.))));
+        assert!(world.is_point_visible(Point::from((0.5, 0., 0.)), Point::default()));
+        assert!(world.is_point_visible(Point::from((0., 10., 0.)), Point::default()));
+        assert!(world.is_point_visible(Point::from((0., 0., 10.)), Point::default()));
     }
 }